@@ -1,8 +1,18 @@
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
 use serde_json::Value;
 
 use crate::error::{Error, Result};
-use crate::validation::{validate_field, validate_optional_field};
+use crate::external_id::stable_id;
+use crate::logging::log_warn;
+use crate::paths::path_to_string;
+#[cfg(feature = "json")]
+use crate::validation::validate_extension_key;
+use crate::validation::{truncate, validate_field, validate_len, validate_optional_field};
 
 /// Maximum length of an annotation message.
 pub const MESSAGE_LIMIT: usize = 2000;
@@ -10,6 +20,9 @@ pub const MESSAGE_LIMIT: usize = 2000;
 /// Maximum length of an external identifier.
 pub const EXTERNAL_ID_LIMIT: usize = 450;
 
+/// Maximum number of annotations of a report.
+pub const ANNOTATIONS_LIMIT: usize = 1000;
+
 /// Holds all annotations that apply to a Code Insights report.
 ///
 /// A Code Insights report must have been created in Bitbucket Server before
@@ -18,9 +31,10 @@ pub const EXTERNAL_ID_LIMIT: usize = 450;
 ///
 /// This is the struct that should be serialized and POST:ed to Bitbucket
 /// Server's annotations endpoint.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Annotations {
-    annotations: Vec<Annotation>,
+    pub(crate) annotations: Vec<Annotation>,
 }
 
 impl Annotations {
@@ -29,10 +43,90 @@ impl Annotations {
             annotations: annotations.into(),
         }
     }
+
+    /// Returns the annotations.
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    /// Returns an iterator over the annotations.
+    pub fn iter(&self) -> std::slice::Iter<'_, Annotation> {
+        self.annotations.iter()
+    }
+
+    /// Validates every annotation, see [`Annotation::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` for the first annotation that is not valid, naming
+    /// its index, e.g. `annotations[3].message`.
+    pub fn validate(&self) -> Result<()> {
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            annotation.validate_fields().map_err(|error| match error {
+                Error::FieldTooLong { name, len, limit } => Error::FieldTooLong {
+                    name: format!("annotations[{index}].{name}"),
+                    len,
+                    limit,
+                },
+                error => error,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Removes the annotations that are less severe than `min`, returning the
+    /// number of removed annotations, e.g. to state how many low-severity
+    /// findings are hidden in the details of the report.
+    pub fn retain_severity(&mut self, min: Severity) -> usize {
+        let before = self.annotations.len();
+        self.annotations
+            .retain(|annotation| annotation.severity >= min);
+        before - self.annotations.len()
+    }
+
+    /// Sorts the annotations by path, then by line, then by severity from
+    /// high to low, e.g. for reproducible payloads.
+    ///
+    /// Annotations without a path come first, and file-level annotations,
+    /// without a line or on line 0, come before the line annotations of their
+    /// file. The sort is stable, so annotations that are equal in all three
+    /// keep their order.
+    pub fn sort(&mut self) {
+        self.annotations.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then_with(|| a.line.unwrap_or(0).cmp(&b.line.unwrap_or(0)))
+                .then_with(|| b.severity.cmp(&a.severity))
+        });
+    }
+
+    /// Returns the number of annotations.
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    /// Returns `true` if there are no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Shortens the messages that are longer than [`MESSAGE_LIMIT`] like
+    /// [`AnnotationBuilder::truncate_message`], returning the number of
+    /// shortened messages.
+    pub fn truncate_messages(&mut self) -> usize {
+        self.annotations
+            .iter_mut()
+            .map(|annotation| truncate(&mut annotation.message, MESSAGE_LIMIT))
+            .filter(|truncated| *truncated)
+            .count()
+    }
 }
 
 /// Represents the severity of an `Annotation`.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+///
+/// Severities are ordered from low to high.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Severity {
     Low,
@@ -40,8 +134,19 @@ pub enum Severity {
     High,
 }
 
+impl Severity {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Severity::High => "High",
+            Severity::Medium => "Medium",
+            Severity::Low => "Low",
+        }
+    }
+}
+
 /// Represents the type of an `Annotation`.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Type {
     Vulnerability,
@@ -49,6 +154,25 @@ pub enum Type {
     Bug,
 }
 
+impl Type {
+    /// Orders types from the most to the least important.
+    pub(crate) fn rank(self) -> u8 {
+        match self {
+            Type::Vulnerability => 0,
+            Type::Bug => 1,
+            Type::CodeSmell => 2,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Type::Vulnerability => "Vulnerability",
+            Type::Bug => "Bug",
+            Type::CodeSmell => "Code smell",
+        }
+    }
+}
+
 /// Represents a Code Insights annotation. Annotations enable Bitbucket Server
 /// integrations to highlight specific lines to display data from the result of
 /// an analysis.
@@ -59,19 +183,20 @@ pub enum Type {
 /// are on lines that have been changed in a pull request are displayed.
 /// Annotations can also be created on line 0 which will be displayed as a file
 /// level annotation on any file that has been modified.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Annotation {
     /// The message to display to users.
-    message: String,
+    pub(crate) message: String,
 
     /// The severity of the annotation.
-    severity: Severity,
+    pub(crate) severity: Severity,
 
     /// The type of annotation posted.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "type")]
-    annotation_type: Option<Type>,
+    pub(crate) annotation_type: Option<Type>,
 
     /// The path of the file on which this annotation should be placed. This is
     /// the path of the file relative to the git repository. If no path is
@@ -79,36 +204,174 @@ pub struct Annotation {
     /// requests where the tip of the branch is the given commit, regardless of
     /// which files were modified.
     #[serde(skip_serializing_if = "Option::is_none")]
-    path: Option<String>,
+    pub(crate) path: Option<String>,
 
     /// The line number that the annotation should belong to. If no line number
     /// is provided, then it will default to 0 and in a pull request it will
     /// appear at the top of the file specified by the path field.
     #[serde(skip_serializing_if = "Option::is_none")]
-    line: Option<u32>,
+    pub(crate) line: Option<u32>,
 
     /// An http or https URL representing the location of the annotation in the
     /// external tool.
     #[serde(skip_serializing_if = "Option::is_none")]
-    link: Option<String>,
+    pub(crate) link: Option<String>,
 
     /// If the caller requires a link to get or modify this annotation, then an
     /// ID must be provided. It is not used or required by Bitbucket, but only
     /// by the annotation creator for updating or deleting this specific
     /// annotation.
     #[serde(skip_serializing_if = "Option::is_none")]
-    external_id: Option<String>,
+    pub(crate) external_id: Option<String>,
+
+    /// Additional properties that this crate does not model, e.g. for
+    /// Bitbucket plugins.
+    #[cfg(feature = "json")]
+    #[serde(flatten)]
+    pub(crate) extensions: BTreeMap<String, Value>,
 }
 
+/// The serialized names of the fields of an `Annotation`.
+#[cfg(feature = "json")]
+const ANNOTATION_FIELDS: &[&str] = &[
+    "message",
+    "severity",
+    "type",
+    "path",
+    "line",
+    "link",
+    "externalId",
+];
+
 impl Annotation {
+    /// Returns the message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the severity.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the annotation type, if it is set.
+    pub fn annotation_type(&self) -> Option<Type> {
+        self.annotation_type
+    }
+
+    /// Returns the path, if it is set.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the line, if it is set.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Returns the link, if it is set.
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    /// Returns the external ID, if it is set.
+    pub fn external_id(&self) -> Option<&str> {
+        self.external_id.as_deref()
+    }
+
+    /// Returns the extension fields.
+    #[cfg(feature = "json")]
+    pub fn extensions(&self) -> &BTreeMap<String, Value> {
+        &self.extensions
+    }
+
+    /// Returns the extension field `key`, if it is set.
+    #[cfg(feature = "json")]
+    pub fn extension(&self, key: &str) -> Option<&Value> {
+        self.extensions.get(key)
+    }
+
+    /// Validates the annotation like [`AnnotationBuilder::build`], e.g. after
+    /// deserializing it from the output of another tool.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `message` or `external_id` are longer than
+    /// [`MESSAGE_LIMIT`] and [`EXTERNAL_ID_LIMIT`].
+    pub fn validate(&self) -> Result<()> {
+        self.validate_fields()
+    }
+
     /// Validates fields that have limits imposed on them by Bitbucket.
-    fn validate_fields(&self) -> Result<()> {
+    pub(crate) fn validate_fields(&self) -> Result<()> {
         validate_field!(self, message, MESSAGE_LIMIT);
         validate_optional_field!(self, external_id, EXTERNAL_ID_LIMIT);
         Ok(())
     }
 }
 
+/// Setters that change an annotation in place, e.g. after deserializing it
+/// from a previous run. Fields with limits are validated like by
+/// [`AnnotationBuilder::build`], and left unchanged if they are too long.
+impl Annotation {
+    /// Sets the message.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `message` is longer than [`MESSAGE_LIMIT`].
+    pub fn set_message<T: Into<String>>(&mut self, message: T) -> Result<&mut Self> {
+        let message = message.into();
+        validate_len("message", &message, MESSAGE_LIMIT)?;
+        self.message = message;
+        Ok(self)
+    }
+
+    /// Sets the severity.
+    pub fn set_severity(&mut self, severity: Severity) -> &mut Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Sets the annotation type.
+    pub fn set_annotation_type(&mut self, annotation_type: Type) -> &mut Self {
+        self.annotation_type = Some(annotation_type);
+        self
+    }
+
+    /// Sets the path to the annotated file, relative to the root of the
+    /// repository.
+    pub fn set_path<T: Into<String>>(&mut self, path: T) -> &mut Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the annotated line.
+    pub fn set_line(&mut self, line: u32) -> &mut Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Sets the link to the annotation in an external tool.
+    pub fn set_link<T: Into<String>>(&mut self, link: T) -> &mut Self {
+        self.link = Some(link.into());
+        self
+    }
+
+    /// Sets the external ID.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `external_id` is longer than
+    /// [`EXTERNAL_ID_LIMIT`].
+    pub fn set_external_id<T: Into<String>>(&mut self, external_id: T) -> Result<&mut Self> {
+        let external_id = external_id.into();
+        validate_len("external_id", &external_id, EXTERNAL_ID_LIMIT)?;
+        self.external_id = Some(external_id);
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "json")]
 impl TryFrom<Annotation> for String {
     type Error = Error;
 
@@ -118,6 +381,7 @@ impl TryFrom<Annotation> for String {
     }
 }
 
+#[cfg(feature = "json")]
 impl TryFrom<Annotation> for Value {
     type Error = Error;
 
@@ -127,14 +391,21 @@ impl TryFrom<Annotation> for Value {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct AnnotationBuilder {
-    message: String,
+    pub(crate) message: String,
     severity: Severity,
     annotation_type: Option<Type>,
     path: Option<String>,
     line: Option<u32>,
-    link: Option<String>,
+    pub(crate) link: Option<String>,
     external_id: Option<String>,
+    external_id_salt: Option<String>,
+    #[cfg(feature = "json")]
+    extensions: BTreeMap<String, Value>,
+    truncate_message: bool,
+    allow_absolute_path: bool,
+    quiet: bool,
 }
 
 impl AnnotationBuilder {
@@ -151,12 +422,29 @@ impl AnnotationBuilder {
             line: None,
             link: None,
             external_id: None,
+            external_id_salt: None,
+            #[cfg(feature = "json")]
+            extensions: BTreeMap::new(),
+            truncate_message: false,
+            allow_absolute_path: false,
+            quiet: false,
         }
     }
 
+    /// Shortens the message to [`MESSAGE_LIMIT`], ending it with an ellipsis,
+    /// if it is too long.
+    ///
+    /// This is useful for messages produced by other tools, which would
+    /// otherwise make [`build`](AnnotationBuilder::build) fail. The message is
+    /// shortened when the annotation is built.
+    pub fn truncate_message(mut self) -> Self {
+        self.set_truncate_message();
+        self
+    }
+
     /// Sets the annotation type.
     pub fn annotation_type(mut self, annotation_type: Type) -> Self {
-        self.annotation_type = Some(annotation_type);
+        self.set_annotation_type(annotation_type);
         self
     }
 
@@ -167,7 +455,31 @@ impl AnnotationBuilder {
     /// modal on all pull requests where the tip of the branch is the given
     /// commit, regardless of which files were modified.
     pub fn path<T: Into<String>>(mut self, path: T) -> Self {
-        self.path = Some(path.into());
+        self.set_path(path);
+        self
+    }
+
+    /// Sets the path to the file that is being annotated from a `Path`, turning
+    /// its separators into forward slashes.
+    ///
+    /// Unlike [`path`](AnnotationBuilder::path), this checks that the path can
+    /// be displayed by Bitbucket.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is not valid UTF-8, or if it is absolute
+    /// and [`allow_absolute_path`](AnnotationBuilder::allow_absolute_path)
+    /// was not called before.
+    pub fn path_from<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        self.set_path_from(path)?;
+        Ok(self)
+    }
+
+    /// Allows [`path_from`](AnnotationBuilder::path_from) to set absolute
+    /// paths, e.g. when they are made relative later with
+    /// [`Annotations::relativize_paths`].
+    pub fn allow_absolute_path(mut self) -> Self {
+        self.set_allow_absolute_path();
         self
     }
 
@@ -176,7 +488,7 @@ impl AnnotationBuilder {
     /// If no line is set, the annotation will displayed as an annotation that
     /// applies to the whole file.
     pub fn line(mut self, line: u32) -> Self {
-        self.line = Some(line);
+        self.set_line(line);
         self
     }
 
@@ -184,7 +496,7 @@ impl AnnotationBuilder {
     ///
     /// The link is the location of the annotation in an external tool.
     pub fn link<T: Into<String>>(mut self, link: T) -> Self {
-        self.link = Some(link.into());
+        self.set_link(link);
         self
     }
 
@@ -195,7 +507,39 @@ impl AnnotationBuilder {
     /// Bitbucket, but only by the annotation creator for updating or deleting
     /// this specific annotation.
     pub fn external_id<T: Into<String>>(mut self, external_id: T) -> Self {
-        self.external_id = Some(external_id.into());
+        self.set_external_id(external_id);
+        self
+    }
+
+    /// Sets the external ID to [`Annotation::stable_id`] with `salt`, e.g.
+    /// the name of the tool, when the annotation is built, unless an external
+    /// ID is set explicitly.
+    pub fn generate_external_id<T: Into<String>>(mut self, salt: T) -> Self {
+        self.set_generate_external_id(salt);
+        self
+    }
+
+    /// Sets an extension field, which is serialized next to the fields of the
+    /// annotation, e.g. for a Bitbucket plugin.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `key` is the name of a field of the annotation,
+    /// such as `message` or `externalId`.
+    #[cfg(feature = "json")]
+    pub fn extension<K, V>(mut self, key: K, value: V) -> Result<Self>
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.set_extension(key, value)?;
+        Ok(self)
+    }
+
+    /// Silences the warnings logged when the annotation is built, e.g. about
+    /// a truncated message, if the `log` feature is enabled.
+    pub fn quiet(mut self) -> Self {
+        self.set_quiet();
         self
     }
 
@@ -206,8 +550,29 @@ impl AnnotationBuilder {
     /// Will return `Err` if `message` or `external_id` are longer than the
     /// Bitbucket API allows, i.e. longer than [`MESSAGE_LIMIT`] and
     /// [`EXTERNAL_ID_LIMIT`].
-    pub fn build(self) -> Result<Annotation> {
+    pub fn build(mut self) -> Result<Annotation> {
+        if self.truncate_message && truncate(&mut self.message, MESSAGE_LIMIT) {
+            log_warn!(
+                self.quiet,
+                "truncated annotation message to {} bytes",
+                self.message.len()
+            );
+        }
+        if let (None, Some(salt)) = (&self.external_id, &self.external_id_salt) {
+            self.external_id = Some(stable_id(
+                salt,
+                self.path.as_deref(),
+                self.line,
+                &self.message,
+            ));
+        }
         self.validate_fields()?;
+        if self.path.is_none() && self.line.is_some() {
+            log_warn!(
+                self.quiet,
+                "annotation has a line but no path, so Bitbucket ignores the line"
+            );
+        }
 
         let AnnotationBuilder {
             message,
@@ -217,6 +582,12 @@ impl AnnotationBuilder {
             line,
             link,
             external_id,
+            external_id_salt: _,
+            #[cfg(feature = "json")]
+            extensions,
+            truncate_message: _,
+            allow_absolute_path: _,
+            quiet: _,
         } = self;
 
         Ok(Annotation {
@@ -227,6 +598,8 @@ impl AnnotationBuilder {
             line,
             link,
             external_id,
+            #[cfg(feature = "json")]
+            extensions,
         })
     }
 
@@ -238,6 +611,92 @@ impl AnnotationBuilder {
     }
 }
 
+/// Setters that take the builder by mutable reference, e.g. to set fields
+/// conditionally in a loop. They behave exactly like the consuming setters of
+/// the same name.
+impl AnnotationBuilder {
+    /// Like [`truncate_message`](AnnotationBuilder::truncate_message).
+    pub fn set_truncate_message(&mut self) -> &mut Self {
+        self.truncate_message = true;
+        self
+    }
+
+    /// Like [`quiet`](AnnotationBuilder::quiet).
+    pub fn set_quiet(&mut self) -> &mut Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Like [`annotation_type`](AnnotationBuilder::annotation_type).
+    pub fn set_annotation_type(&mut self, annotation_type: Type) -> &mut Self {
+        self.annotation_type = Some(annotation_type);
+        self
+    }
+
+    /// Like [`path`](AnnotationBuilder::path).
+    pub fn set_path<T: Into<String>>(&mut self, path: T) -> &mut Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Like [`path_from`](AnnotationBuilder::path_from).
+    ///
+    /// # Errors
+    ///
+    /// See [`path_from`](AnnotationBuilder::path_from).
+    pub fn set_path_from<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self> {
+        self.path = Some(path_to_string(path.as_ref(), self.allow_absolute_path)?);
+        Ok(self)
+    }
+
+    /// Like [`allow_absolute_path`](AnnotationBuilder::allow_absolute_path).
+    pub fn set_allow_absolute_path(&mut self) -> &mut Self {
+        self.allow_absolute_path = true;
+        self
+    }
+
+    /// Like [`line`](AnnotationBuilder::line).
+    pub fn set_line(&mut self, line: u32) -> &mut Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Like [`link`](AnnotationBuilder::link).
+    pub fn set_link<T: Into<String>>(&mut self, link: T) -> &mut Self {
+        self.link = Some(link.into());
+        self
+    }
+
+    /// Like [`external_id`](AnnotationBuilder::external_id).
+    pub fn set_external_id<T: Into<String>>(&mut self, external_id: T) -> &mut Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Like [`generate_external_id`](AnnotationBuilder::generate_external_id).
+    pub fn set_generate_external_id<T: Into<String>>(&mut self, salt: T) -> &mut Self {
+        self.external_id_salt = Some(salt.into());
+        self
+    }
+
+    /// Like [`extension`](AnnotationBuilder::extension).
+    ///
+    /// # Errors
+    ///
+    /// See [`extension`](AnnotationBuilder::extension).
+    #[cfg(feature = "json")]
+    pub fn set_extension<K, V>(&mut self, key: K, value: V) -> Result<&mut Self>
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        let key = key.into();
+        validate_extension_key(&key, ANNOTATION_FIELDS)?;
+        self.extensions.insert(key, value.into());
+        Ok(self)
+    }
+}
+
 #[cfg(test)]
 mod field_validataion {
     use super::*;
@@ -250,6 +709,17 @@ mod field_validataion {
             .is_err());
     }
 
+    #[test]
+    fn truncated_message() {
+        let message = "X".repeat(MESSAGE_LIMIT - 1) + "ÅÅ";
+        let annotation = AnnotationBuilder::new(message, Severity::Low)
+            .truncate_message()
+            .build()
+            .unwrap();
+        assert!(annotation.message.len() <= MESSAGE_LIMIT);
+        assert!(annotation.message.ends_with("X…"));
+    }
+
     #[test]
     fn external_id() {
         let invalid_external_id = "X".repeat(EXTERNAL_ID_LIMIT + 1);
@@ -258,4 +728,453 @@ mod field_validataion {
             .build()
             .is_err());
     }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialized() {
+        let json = serde_json::json!({
+            "annotations": [
+                {"message": "Valid", "severity": "LOW"},
+                {"message": "X".repeat(MESSAGE_LIMIT + 1), "severity": "LOW"}
+            ]
+        });
+        let annotations = serde_json::from_value::<Annotations>(json).unwrap();
+        assert!(annotations.annotations()[0].validate().is_ok());
+        assert!(matches!(
+            annotations.annotations()[1].validate(),
+            Err(Error::FieldTooLong { ref name, .. }) if name == "message"
+        ));
+
+        // Validating does not consume the annotations, so it can be repeated.
+        for _ in 0..2 {
+            assert!(matches!(
+                annotations.validate(),
+                Err(Error::FieldTooLong { ref name, len, limit: MESSAGE_LIMIT })
+                    if name == "annotations[1].message" && len == MESSAGE_LIMIT + 1
+            ));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod getters {
+    use super::*;
+
+    #[test]
+    fn deserialized() {
+        let annotations = serde_json::from_str::<Annotations>(
+            r#"{
+                "annotations": [
+                    {
+                        "message": "Possible SQL injection",
+                        "severity": "HIGH",
+                        "type": "VULNERABILITY",
+                        "path": "src/db.rs",
+                        "line": 42,
+                        "link": "https://analyzer.example.com/issues/7",
+                        "externalId": "analyzer-7"
+                    },
+                    {"message": "Unused import", "severity": "LOW"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(2, annotations.len());
+        assert!(!annotations.is_empty());
+
+        let annotation = &annotations.annotations()[0];
+        assert_eq!("Possible SQL injection", annotation.message());
+        assert_eq!(Severity::High, annotation.severity());
+        assert_eq!(Some(Type::Vulnerability), annotation.annotation_type());
+        assert_eq!(Some("src/db.rs"), annotation.path());
+        assert_eq!(Some(42), annotation.line());
+        assert_eq!(
+            Some("https://analyzer.example.com/issues/7"),
+            annotation.link()
+        );
+        assert_eq!(Some("analyzer-7"), annotation.external_id());
+
+        let annotation = &annotations.annotations()[1];
+        assert_eq!("Unused import", annotation.message());
+        assert_eq!(Severity::Low, annotation.severity());
+        assert_eq!(None, annotation.annotation_type());
+        assert_eq!(None, annotation.path());
+        assert_eq!(None, annotation.line());
+        assert_eq!(None, annotation.link());
+        assert_eq!(None, annotation.external_id());
+
+        assert_eq!(
+            vec!["Possible SQL injection", "Unused import"],
+            annotations
+                .iter()
+                .map(Annotation::message)
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod extension_fields {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let json = json!({
+            "message": "Possible panic",
+            "severity": "HIGH",
+            "line": 3,
+            "ruleId": "panic"
+        });
+        let annotation = serde_json::from_value::<Annotation>(json.clone()).unwrap();
+        assert_eq!(Some(&json!("panic")), annotation.extension("ruleId"));
+        assert_eq!(json, serde_json::to_value(&annotation).unwrap());
+    }
+
+    #[test]
+    fn collision() {
+        let result = AnnotationBuilder::new("Message", Severity::Low).extension("externalId", 1);
+        assert!(
+            matches!(result, Err(Error::InvalidValue { ref field, .. }) if field == "externalId")
+        );
+
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .extension("ruleId", 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(Some(&json!(1)), annotation.extension("ruleId"));
+    }
+}
+
+#[cfg(test)]
+mod path_conversion {
+    use super::*;
+
+    #[test]
+    fn relative() {
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .path_from(Path::new("src").join("lib.rs"))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(Some("src/lib.rs"), annotation.path.as_deref());
+
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .path_from("./src\\main.rs")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(Some("src/main.rs"), annotation.path.as_deref());
+    }
+
+    #[test]
+    fn absolute() {
+        let result = AnnotationBuilder::new("Message", Severity::Low).path_from("/src/lib.rs");
+        assert!(matches!(result, Err(Error::InvalidValue { ref field, .. }) if field == "path"));
+
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .allow_absolute_path()
+            .path_from("/src/lib.rs")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(Some("/src/lib.rs"), annotation.path.as_deref());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"src/\xff.rs"));
+        let Err(error) = AnnotationBuilder::new("Message", Severity::Low).path_from(path) else {
+            panic!("non-UTF-8 path was accepted");
+        };
+        assert!(error.to_string().contains("'path'"), "{error}");
+        assert!(
+            matches!(error, Error::InvalidValue { ref reason, .. } if reason.contains("UTF-8"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod severity_filter {
+    use super::*;
+
+    fn annotations() -> Annotations {
+        Annotations::new(
+            [
+                Severity::Low,
+                Severity::High,
+                Severity::Medium,
+                Severity::Low,
+            ]
+            .map(|severity| AnnotationBuilder::new("Message", severity).build().unwrap()),
+        )
+    }
+
+    fn severities(annotations: &Annotations) -> Vec<Severity> {
+        annotations
+            .iter()
+            .map(Annotation::severity)
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn thresholds() {
+        assert!(Severity::Low < Severity::Medium && Severity::Medium < Severity::High);
+
+        let mut low = annotations();
+        assert_eq!(0, low.retain_severity(Severity::Low));
+        assert_eq!(4, low.len());
+
+        let mut medium = annotations();
+        assert_eq!(2, medium.retain_severity(Severity::Medium));
+        assert_eq!(vec![Severity::High, Severity::Medium], severities(&medium));
+
+        let mut high = annotations();
+        assert_eq!(3, high.retain_severity(Severity::High));
+        assert_eq!(vec![Severity::High], severities(&high));
+    }
+
+    #[test]
+    fn empty_result() {
+        let mut annotations = Annotations::new(
+            [Severity::Low, Severity::Medium]
+                .map(|severity| AnnotationBuilder::new("Message", severity).build().unwrap()),
+        );
+        assert_eq!(2, annotations.retain_severity(Severity::High));
+        assert!(annotations.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod sorting {
+    use super::*;
+
+    fn annotation(message: &str, path: Option<&str>, line: Option<u32>) -> Annotation {
+        let mut builder = AnnotationBuilder::new(message, Severity::Medium).quiet();
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        if let Some(line) = line {
+            builder = builder.line(line);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn path_and_line() {
+        let mut high = annotation("High on line 3", Some("src/lib.rs"), Some(3));
+        high.severity = Severity::High;
+        let mut annotations = Annotations::new(vec![
+            annotation("Line 10", Some("src/lib.rs"), Some(10)),
+            annotation("Main", Some("src/main.rs"), Some(1)),
+            annotation("First on line 3", Some("src/lib.rs"), Some(3)),
+            annotation("Repository", None, None),
+            annotation("Second on line 3", Some("src/lib.rs"), Some(3)),
+            high,
+            annotation("Line 0", Some("src/lib.rs"), Some(0)),
+            annotation("File", Some("src/lib.rs"), None),
+            annotation("Build", None, None),
+        ]);
+        annotations.sort();
+
+        assert_eq!(
+            vec![
+                "Repository",
+                "Build",
+                "Line 0",
+                "File",
+                "High on line 3",
+                "First on line 3",
+                "Second on line 3",
+                "Line 10",
+                "Main",
+            ],
+            annotations
+                .iter()
+                .map(Annotation::message)
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod hashing {
+    use std::collections::HashSet;
+    use std::hash::{BuildHasher, RandomState};
+
+    use super::*;
+
+    fn annotation(message: &str) -> Annotation {
+        AnnotationBuilder::new(message, Severity::Medium)
+            .annotation_type(Type::CodeSmell)
+            .path("src/lib.rs")
+            .line(3)
+            .external_id("lint-3")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn identical_annotations() {
+        let state = RandomState::new();
+        assert_eq!(
+            state.hash_one(annotation("Unused import")),
+            state.hash_one(annotation("Unused import"))
+        );
+    }
+
+    #[test]
+    fn deduplicate() {
+        let annotations = [
+            annotation("Unused import"),
+            annotation("Possible panic"),
+            annotation("Unused import"),
+        ];
+        let unique = annotations.into_iter().collect::<HashSet<_>>();
+        assert_eq!(2, unique.len());
+        assert!(unique.contains(&annotation("Possible panic")));
+    }
+}
+
+#[cfg(test)]
+mod annotation_setters {
+    use super::*;
+
+    #[test]
+    fn mutation() {
+        let mut annotation = AnnotationBuilder::new("Possible panic", Severity::Low)
+            .path("crates/core/src/lib.rs")
+            .line(3)
+            .build()
+            .unwrap();
+        let message = format!("clippy: {}", annotation.message());
+        annotation
+            .set_message(message)
+            .unwrap()
+            .set_severity(Severity::High)
+            .set_annotation_type(Type::Bug)
+            .set_path("src/lib.rs")
+            .set_line(4)
+            .set_link("https://example.com/panic");
+        annotation.set_external_id("clippy-1").unwrap();
+
+        let expected = AnnotationBuilder::new("clippy: Possible panic", Severity::High)
+            .annotation_type(Type::Bug)
+            .path("src/lib.rs")
+            .line(4)
+            .link("https://example.com/panic")
+            .external_id("clippy-1")
+            .build()
+            .unwrap();
+        assert_eq!(expected, annotation);
+        #[cfg(feature = "json")]
+        assert_eq!(
+            serde_json::to_string(&expected).unwrap(),
+            String::try_from(annotation).unwrap()
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let mut annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            annotation.set_message("X".repeat(MESSAGE_LIMIT + 1)),
+            Err(Error::FieldTooLong { ref name, .. }) if name == "message"
+        ));
+        assert!(annotation
+            .set_external_id("X".repeat(EXTERNAL_ID_LIMIT + 1))
+            .is_err());
+        assert_eq!("Message", annotation.message());
+        assert_eq!(None, annotation.external_id());
+        assert!(annotation.validate().is_ok());
+    }
+}
+
+#[cfg(test)]
+mod mutable_setters {
+    use super::*;
+
+    #[test]
+    fn conditional_building() {
+        let findings = [
+            ("Unused import", Some("src/lib.rs"), Some(3), None),
+            ("Slow build", None, None, Some("a1")),
+            ("Possible panic", Some("src/main.rs"), None, None),
+        ];
+
+        let mut annotations = Vec::new();
+        for (message, path, line, external_id) in findings {
+            let mut builder = AnnotationBuilder::new(message, Severity::Low);
+            builder.set_annotation_type(Type::CodeSmell);
+            if let Some(path) = path {
+                builder.set_path(path);
+            }
+            if let Some(line) = line {
+                builder.set_line(line);
+            }
+            if let Some(external_id) = external_id {
+                builder.set_external_id(external_id);
+            }
+            annotations.push(builder.build().unwrap());
+        }
+
+        let expected = vec![
+            AnnotationBuilder::new("Unused import", Severity::Low)
+                .annotation_type(Type::CodeSmell)
+                .path("src/lib.rs")
+                .line(3)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Slow build", Severity::Low)
+                .annotation_type(Type::CodeSmell)
+                .external_id("a1")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Possible panic", Severity::Low)
+                .annotation_type(Type::CodeSmell)
+                .path("src/main.rs")
+                .build()
+                .unwrap(),
+        ];
+        assert_eq!(expected, annotations);
+    }
+
+    #[test]
+    fn validation() {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low);
+        #[cfg(feature = "json")]
+        assert!(builder.set_extension("line", 3).is_err());
+        assert!(builder.set_path_from("/src/lib.rs").is_err());
+        builder
+            .set_external_id("X".repeat(EXTERNAL_ID_LIMIT + 1))
+            .set_link("https://example.com");
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn cloned_template() {
+        let template = AnnotationBuilder::new("Called unwrap", Severity::Medium)
+            .annotation_type(Type::CodeSmell)
+            .link("https://example.com/rules/unwrap");
+        let first = template.clone().path("src/lib.rs").build().unwrap();
+        let second = template.path("src/main.rs").line(3).build().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(first.link, second.link);
+        assert_eq!(Some("src/lib.rs"), first.path.as_deref());
+        assert_eq!(Some(3), second.line);
+
+        let debug = format!(
+            "{:?}",
+            AnnotationBuilder::new("Called unwrap", Severity::Low)
+        );
+        assert!(debug.starts_with("AnnotationBuilder {"), "{debug}");
+        assert!(debug.contains("\"Called unwrap\""), "{debug}");
+    }
 }
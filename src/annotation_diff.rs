@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use crate::annotation::{Annotation, Annotations};
+use crate::report::{Data, Parameter};
+
+/// How [`Annotations::diff_with`] decides that an annotation of the current
+/// run is the same finding as one of the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchStrategy {
+    /// Matches annotations by external ID if both have one, and otherwise by
+    /// path and message, ignoring the line.
+    #[default]
+    ExternalIdOrLocation,
+    /// Matches annotations by external ID only, so annotations without one
+    /// never match.
+    ExternalId,
+    /// Matches annotations by path and message only, ignoring the line.
+    Location,
+}
+
+/// The difference between the annotations of two runs, computed by
+/// [`Annotations::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationDiff {
+    added: Vec<Annotation>,
+    removed: Vec<Annotation>,
+    unchanged: Vec<Annotation>,
+}
+
+impl AnnotationDiff {
+    /// Returns the annotations that are new in the current run.
+    pub fn added(&self) -> &[Annotation] {
+        &self.added
+    }
+
+    /// Returns the annotations of the baseline that are gone in the current
+    /// run, i.e. the fixed findings.
+    pub fn removed(&self) -> &[Annotation] {
+        &self.removed
+    }
+
+    /// Returns the annotations of the current run that were already in the
+    /// baseline.
+    pub fn unchanged(&self) -> &[Annotation] {
+        &self.unchanged
+    }
+
+    /// Returns the new annotations, e.g. to post only the findings introduced
+    /// by a pull request.
+    pub fn added_only(&self) -> Annotations {
+        Annotations::new(self.added.clone())
+    }
+
+    /// Returns the data fields "New findings" and "Fixed findings".
+    pub fn data(&self) -> Vec<Data> {
+        let count = |title: &str, count: usize| Data {
+            title: title.to_owned(),
+            parameter: Parameter::Number((count as u64).into()),
+        };
+        vec![
+            count("New findings", self.added.len()),
+            count("Fixed findings", self.removed.len()),
+        ]
+    }
+}
+
+impl Annotations {
+    /// Compares the annotations with those of a previous run, matching them by
+    /// external ID or by path and message, see
+    /// [`MatchStrategy::ExternalIdOrLocation`].
+    pub fn diff(&self, baseline: &Annotations) -> AnnotationDiff {
+        self.diff_with(baseline, MatchStrategy::default())
+    }
+
+    /// Compares the annotations with those of a previous run, matching them
+    /// with `strategy`.
+    ///
+    /// Every annotation of the baseline matches at most one annotation of the
+    /// current run, so a finding that occurs more often than before counts as
+    /// added.
+    pub fn diff_with(&self, baseline: &Annotations, strategy: MatchStrategy) -> AnnotationDiff {
+        let current = &self.annotations;
+        let baseline = &baseline.annotations;
+        let mut matched = vec![false; baseline.len()];
+        let mut matches = vec![false; current.len()];
+
+        if strategy != MatchStrategy::Location {
+            let mut ids = HashMap::<&str, Vec<usize>>::new();
+            for (index, annotation) in baseline.iter().enumerate().rev() {
+                if let Some(id) = &annotation.external_id {
+                    ids.entry(id).or_default().push(index);
+                }
+            }
+            for (index, annotation) in current.iter().enumerate() {
+                let candidates = annotation
+                    .external_id
+                    .as_deref()
+                    .and_then(|id| ids.get_mut(id));
+                if let Some(candidate) = candidates.and_then(Vec::pop) {
+                    matched[candidate] = true;
+                    matches[index] = true;
+                }
+            }
+        }
+
+        if strategy != MatchStrategy::ExternalId {
+            let fallback = strategy == MatchStrategy::ExternalIdOrLocation;
+            let mut locations = HashMap::<(Option<&str>, &str), Vec<usize>>::new();
+            for (index, annotation) in baseline.iter().enumerate().rev() {
+                if matched[index] || (fallback && annotation.external_id.is_some()) {
+                    continue;
+                }
+                locations
+                    .entry((annotation.path.as_deref(), &annotation.message))
+                    .or_default()
+                    .push(index);
+            }
+            for (index, annotation) in current.iter().enumerate() {
+                if matches[index] || (fallback && annotation.external_id.is_some()) {
+                    continue;
+                }
+                let key = (annotation.path.as_deref(), annotation.message.as_str());
+                if let Some(candidate) = locations.get_mut(&key).and_then(Vec::pop) {
+                    matched[candidate] = true;
+                    matches[index] = true;
+                }
+            }
+        }
+
+        let mut diff = AnnotationDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            unchanged: Vec::new(),
+        };
+        for (annotation, matches) in current.iter().zip(matches) {
+            if matches {
+                diff.unchanged.push(annotation.clone());
+            } else {
+                diff.added.push(annotation.clone());
+            }
+        }
+        for (annotation, matched) in baseline.iter().zip(matched) {
+            if !matched {
+                diff.removed.push(annotation.clone());
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod baseline_diff {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation(message: &str, line: u32, external_id: Option<&str>) -> Annotation {
+        let mut builder = AnnotationBuilder::new(message, Severity::Medium)
+            .path("src/lib.rs")
+            .line(line);
+        if let Some(external_id) = external_id {
+            builder = builder.external_id(external_id);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn added_removed_unchanged() {
+        let baseline = Annotations::new(vec![
+            annotation("Possible panic", 10, Some("a1")),
+            annotation("Unused import", 3, None),
+            annotation("Dead code", 50, Some("c3")),
+        ]);
+        let current = Annotations::new(vec![
+            annotation("Possible panic (moved)", 14, Some("a1")),
+            annotation("Unused import", 5, None),
+            annotation("Shadowed variable", 20, Some("d4")),
+        ]);
+
+        let diff = current.diff(&baseline);
+        assert_eq!(
+            &[annotation("Shadowed variable", 20, Some("d4"))],
+            diff.added()
+        );
+        assert_eq!(&[annotation("Dead code", 50, Some("c3"))], diff.removed());
+        assert_eq!(
+            &[
+                annotation("Possible panic (moved)", 14, Some("a1")),
+                annotation("Unused import", 5, None)
+            ],
+            diff.unchanged()
+        );
+        assert_eq!(
+            Annotations::new(vec![annotation("Shadowed variable", 20, Some("d4"))]),
+            diff.added_only()
+        );
+        assert_eq!(
+            vec![
+                Data {
+                    title: "New findings".to_owned(),
+                    parameter: Parameter::Number(1.into()),
+                },
+                Data {
+                    title: "Fixed findings".to_owned(),
+                    parameter: Parameter::Number(1.into()),
+                },
+            ],
+            diff.data()
+        );
+    }
+
+    #[test]
+    fn external_ids_take_precedence() {
+        let baseline = Annotations::new(vec![annotation("Possible panic", 10, Some("a1"))]);
+        let current = Annotations::new(vec![annotation("Possible panic", 10, Some("b2"))]);
+        let diff = current.diff(&baseline);
+        assert_eq!(1, diff.added().len());
+        assert_eq!(1, diff.removed().len());
+
+        let diff = current.diff_with(&baseline, MatchStrategy::Location);
+        assert!(diff.added().is_empty() && diff.removed().is_empty());
+    }
+
+    #[test]
+    fn strategies() {
+        let baseline = Annotations::new(vec![annotation("Unused import", 3, None)]);
+        let current = Annotations::new(vec![annotation("Unused import", 4, None)]);
+        assert_eq!(
+            1,
+            current
+                .diff_with(&baseline, MatchStrategy::ExternalId)
+                .added()
+                .len()
+        );
+        assert_eq!(1, current.diff(&baseline).unchanged().len());
+    }
+
+    #[test]
+    fn repeated_findings() {
+        let baseline = Annotations::new(vec![annotation("Unused import", 3, None)]);
+        let current = Annotations::new(vec![
+            annotation("Unused import", 3, None),
+            annotation("Unused import", 8, None),
+        ]);
+        let diff = current.diff(&baseline);
+        assert_eq!(&[annotation("Unused import", 8, None)], diff.added());
+        assert!(diff.removed().is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::annotation::{
+    Annotation, Annotations, Severity, Type, EXTERNAL_ID_LIMIT, MESSAGE_LIMIT,
+};
+use crate::error::{Error, Result};
+use crate::validation::{validate_field, validate_optional_field};
+
+/// A borrowed mirror of [`Annotation`], for serializing annotations whose
+/// strings are already held elsewhere without copying them, and for
+/// deserializing annotations without copying their strings out of the input.
+///
+/// It serializes exactly like an `Annotation` with the same fields. Extension
+/// fields are not supported, and are ignored when deserializing.
+///
+/// When deserialized, e.g. with [`from_json`](AnnotationRef::from_json), the
+/// strings point into the input, which must therefore outlive the
+/// annotation. Strings containing JSON escapes such as `\n` or `\"` cannot
+/// be borrowed, since they differ from the input, and are copied instead.
+/// Call [`to_owned`](AnnotationRef::to_owned) to keep an annotation after
+/// the input is dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationRef<'a> {
+    #[serde(borrow)]
+    message: Cow<'a, str>,
+    severity: Severity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "type")]
+    annotation_type: Option<Type>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(borrow, deserialize_with = "borrow_optional")]
+    path: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(borrow, deserialize_with = "borrow_optional")]
+    link: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(borrow, deserialize_with = "borrow_optional")]
+    external_id: Option<Cow<'a, str>>,
+}
+
+/// Deserializes an optional string, borrowing it if possible. Serde only
+/// borrows `Cow` fields themselves, not `Cow`s in an `Option`.
+fn borrow_optional<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Cow<'de, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Option::<Borrowed>::deserialize(deserializer).map(|value| value.map(|value| value.0))
+}
+
+impl<'a> AnnotationRef<'a> {
+    /// Constructs an annotation with a message and severity.
+    pub fn new(message: &'a str, severity: Severity) -> Self {
+        AnnotationRef {
+            message: Cow::Borrowed(message),
+            severity,
+            annotation_type: None,
+            path: None,
+            line: None,
+            link: None,
+            external_id: None,
+        }
+    }
+
+    /// Like [`AnnotationBuilder::annotation_type`](crate::AnnotationBuilder::annotation_type).
+    pub fn with_annotation_type(mut self, annotation_type: Type) -> Self {
+        self.annotation_type = Some(annotation_type);
+        self
+    }
+
+    /// Like [`AnnotationBuilder::path`](crate::AnnotationBuilder::path).
+    pub fn with_path(mut self, path: &'a str) -> Self {
+        self.path = Some(Cow::Borrowed(path));
+        self
+    }
+
+    /// Like [`AnnotationBuilder::line`](crate::AnnotationBuilder::line).
+    pub fn with_line(mut self, line: u32) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Like [`AnnotationBuilder::link`](crate::AnnotationBuilder::link).
+    pub fn with_link(mut self, link: &'a str) -> Self {
+        self.link = Some(Cow::Borrowed(link));
+        self
+    }
+
+    /// Like [`AnnotationBuilder::external_id`](crate::AnnotationBuilder::external_id).
+    pub fn with_external_id(mut self, external_id: &'a str) -> Self {
+        self.external_id = Some(Cow::Borrowed(external_id));
+        self
+    }
+
+    /// Deserializes an annotation from JSON, borrowing its strings from
+    /// `json` where possible.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is not a valid annotation, or if it has
+    /// fields that are longer than Bitbucket allows.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &'a str) -> Result<Self> {
+        let annotation = serde_json::from_str::<AnnotationRef>(json)?;
+        annotation.validate()?;
+        Ok(annotation)
+    }
+
+    /// Returns the message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the severity.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the annotation type, if it is set.
+    pub fn annotation_type(&self) -> Option<Type> {
+        self.annotation_type
+    }
+
+    /// Returns the path, if it is set.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the line, if it is set.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Returns the link, if it is set.
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    /// Returns the external ID, if it is set.
+    pub fn external_id(&self) -> Option<&str> {
+        self.external_id.as_deref()
+    }
+
+    /// Validates fields that have limits imposed on them by Bitbucket.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `message` or `external_id` are longer than
+    /// [`MESSAGE_LIMIT`] and [`EXTERNAL_ID_LIMIT`].
+    pub fn validate(&self) -> Result<()> {
+        validate_field!(self, message, MESSAGE_LIMIT);
+        validate_optional_field!(self, external_id, EXTERNAL_ID_LIMIT);
+        Ok(())
+    }
+
+    /// Copies the annotation into an owned [`Annotation`].
+    pub fn to_owned(&self) -> Annotation {
+        Annotation {
+            message: self.message.clone().into_owned(),
+            severity: self.severity,
+            annotation_type: self.annotation_type,
+            path: self.path.clone().map(Cow::into_owned),
+            line: self.line,
+            link: self.link.clone().map(Cow::into_owned),
+            external_id: self.external_id.clone().map(Cow::into_owned),
+            #[cfg(feature = "json")]
+            extensions: Default::default(),
+        }
+    }
+}
+
+impl<'a> From<&'a Annotation> for AnnotationRef<'a> {
+    /// Borrows the fields of `annotation`, apart from its extension fields.
+    fn from(annotation: &'a Annotation) -> Self {
+        AnnotationRef {
+            message: Cow::Borrowed(&annotation.message),
+            severity: annotation.severity,
+            annotation_type: annotation.annotation_type,
+            path: annotation.path.as_deref().map(Cow::Borrowed),
+            line: annotation.line,
+            link: annotation.link.as_deref().map(Cow::Borrowed),
+            external_id: annotation.external_id.as_deref().map(Cow::Borrowed),
+        }
+    }
+}
+
+/// A borrowed mirror of [`Annotations`], which serializes to the same
+/// `{"annotations": [...]}` payload.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationsRef<'a> {
+    #[serde(borrow)]
+    annotations: Vec<AnnotationRef<'a>>,
+}
+
+impl<'a> AnnotationsRef<'a> {
+    pub fn new<T: Into<Vec<AnnotationRef<'a>>>>(annotations: T) -> Self {
+        AnnotationsRef {
+            annotations: annotations.into(),
+        }
+    }
+
+    /// Deserializes an `{"annotations": [...]}` payload, borrowing the
+    /// strings of the annotations from `json` where possible, see
+    /// [`AnnotationRef`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is not a valid payload, or if any
+    /// annotation has fields that are longer than Bitbucket allows.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &'a str) -> Result<Self> {
+        let annotations = serde_json::from_str::<AnnotationsRef>(json)?;
+        annotations.validate()?;
+        Ok(annotations)
+    }
+
+    /// Returns the annotations.
+    pub fn annotations(&self) -> &[AnnotationRef<'a>] {
+        &self.annotations
+    }
+
+    /// Validates every annotation, see [`AnnotationRef::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` for the first annotation that is not valid, naming
+    /// its index.
+    pub fn validate(&self) -> Result<()> {
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            annotation.validate().map_err(|error| match error {
+                Error::FieldTooLong { name, len, limit } => Error::FieldTooLong {
+                    name: format!("annotations[{index}].{name}"),
+                    len,
+                    limit,
+                },
+                error => error,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Copies the annotations into owned [`Annotations`].
+    pub fn to_owned(&self) -> Annotations {
+        Annotations::new(
+            self.annotations
+                .iter()
+                .map(AnnotationRef::to_owned)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<'a> From<&'a Annotations> for AnnotationsRef<'a> {
+    fn from(annotations: &'a Annotations) -> Self {
+        AnnotationsRef::new(
+            annotations
+                .annotations
+                .iter()
+                .map(AnnotationRef::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod borrowed_annotations {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    const PATHS: [&str; 2] = ["src/lib.rs", "src/main.rs"];
+
+    fn borrowed(messages: &[String]) -> AnnotationsRef<'_> {
+        AnnotationsRef::new(
+            messages
+                .iter()
+                .enumerate()
+                .map(|(index, message)| {
+                    let annotation = AnnotationRef::new(message, Severity::Medium)
+                        .with_path(PATHS[index % 2])
+                        .with_line(index as u32);
+                    if index % 2 == 0 {
+                        annotation
+                            .with_annotation_type(Type::Bug)
+                            .with_link("https://example.com/issue")
+                            .with_external_id(message)
+                    } else {
+                        annotation
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn identical_serialization() {
+        let messages = (0..4).map(|n| format!("Issue {n}")).collect::<Vec<_>>();
+        let borrowed = borrowed(&messages);
+        let owned = Annotations::new(
+            messages
+                .iter()
+                .enumerate()
+                .map(|(index, message)| {
+                    let mut builder = AnnotationBuilder::new(message, Severity::Medium)
+                        .path(PATHS[index % 2])
+                        .line(index as u32);
+                    if index % 2 == 0 {
+                        builder = builder
+                            .annotation_type(Type::Bug)
+                            .link("https://example.com/issue")
+                            .external_id(message);
+                    }
+                    builder.build().unwrap()
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            serde_json::to_string(&owned).unwrap(),
+            serde_json::to_string(&borrowed).unwrap()
+        );
+        assert_eq!(owned, borrowed.to_owned());
+        assert_eq!(borrowed, AnnotationsRef::from(&owned));
+    }
+
+    #[test]
+    fn validation() {
+        let long = "X".repeat(MESSAGE_LIMIT + 1);
+        let annotations = AnnotationsRef::new(vec![
+            AnnotationRef::new("Fine", Severity::Low),
+            AnnotationRef::new(&long, Severity::Low),
+        ]);
+        assert_eq!(
+            "field 'annotations[1].message' too long, its length 2001 is longer than the allowed limit 2000",
+            annotations.validate().unwrap_err().to_string()
+        );
+        assert!(AnnotationRef::new("Fine", Severity::Low)
+            .with_external_id(&long)
+            .validate()
+            .is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn borrowed_deserialization() {
+        let owned = Annotations::new(
+            (0..10_000)
+                .map(|index| {
+                    // Every tenth message needs escaping, so it cannot be
+                    // borrowed.
+                    let message = if index % 10 == 0 {
+                        format!("Issue \"{index}\"")
+                    } else {
+                        format!("Issue {index}")
+                    };
+                    AnnotationBuilder::new(message, Severity::Low)
+                        .path(format!("src/module_{index}.rs"))
+                        .line(index)
+                        .build()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>(),
+        );
+        let json = serde_json::to_string(&owned).unwrap();
+
+        let borrowed = AnnotationsRef::from_json(&json).unwrap();
+        assert_eq!(owned, borrowed.to_owned());
+        let input = json.as_bytes().as_ptr_range();
+        for (index, annotation) in borrowed.annotations().iter().enumerate() {
+            let path = annotation.path().unwrap();
+            assert!(input.contains(&path.as_ptr()));
+            assert!(matches!(annotation.path, Some(Cow::Borrowed(_))));
+            if index % 10 == 0 {
+                assert!(matches!(annotation.message, Cow::Owned(_)));
+            } else {
+                assert!(input.contains(&annotation.message().as_ptr()));
+                assert!(matches!(annotation.message, Cow::Borrowed(_)));
+            }
+        }
+
+        let single = AnnotationRef::from_json(r#"{"message":"Fine","severity":"LOW"}"#).unwrap();
+        assert_eq!("Fine", single.message());
+        assert_eq!(None, single.path());
+        assert!(AnnotationRef::from_json(r#"{"severity":"LOW"}"#).is_err());
+    }
+}
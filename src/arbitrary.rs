@@ -0,0 +1,249 @@
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::annotation::{
+    Annotation, Annotations, Severity, Type, EXTERNAL_ID_LIMIT, MESSAGE_LIMIT,
+};
+use crate::number::Number;
+use crate::report::{
+    Data, Parameter, Report, ReportResult, ReportType, DATA_LIMIT, DETAILS_LIMIT, REPORTER_LIMIT,
+    TITLE_LIMIT,
+};
+
+/// Decides whether the [`Arbitrary`] strategies of reports and annotations
+/// generate values within the limits Bitbucket imposes.
+///
+/// The default strategies, as used by `any::<Report>()`, respect the limits.
+/// Use `any_with::<Report>(ArbitraryLimits::Exceed)` to generate values that
+/// fail validation, e.g. for negative tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArbitraryLimits {
+    /// All fields are within their limits.
+    #[default]
+    Respect,
+
+    /// At least one field of every value exceeds its limit: the message of an
+    /// annotation and the title of a report.
+    Exceed,
+}
+
+/// Generates strings of up to `limit` characters, or of more than `limit`
+/// characters when the limits are exceeded.
+fn text(limit: usize, limits: ArbitraryLimits) -> BoxedStrategy<String> {
+    match limits {
+        ArbitraryLimits::Respect => {
+            // Long strings make failures hard to read without finding more
+            // bugs, so most are short.
+            prop_oneof![
+                9 => "\\PC{0,32}",
+                1 => vec(any::<char>(), 0..=limit).prop_map(move |chars| {
+                    let mut text = chars.into_iter().collect::<String>();
+                    while text.len() > limit {
+                        text.pop();
+                    }
+                    text
+                }),
+            ]
+            .boxed()
+        }
+        ArbitraryLimits::Exceed => (1..=32usize)
+            .prop_map(move |extra| "X".repeat(limit + extra))
+            .boxed(),
+    }
+}
+
+impl Arbitrary for Severity {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Severity>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Severity::Low),
+            Just(Severity::Medium),
+            Just(Severity::High),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Type {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Type>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Type::Vulnerability),
+            Just(Type::CodeSmell),
+            Just(Type::Bug),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for ReportType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<ReportType>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![
+            Just(ReportType::Security),
+            Just(ReportType::Coverage),
+            Just(ReportType::Test),
+            Just(ReportType::Bug),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for ReportResult {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<ReportResult>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![Just(ReportResult::Pass), Just(ReportResult::Fail)].boxed()
+    }
+}
+
+impl Arbitrary for Parameter {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Parameter>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![
+            any::<bool>().prop_map(Parameter::Boolean),
+            any::<u64>().prop_map(Parameter::Date),
+            any::<u64>().prop_map(Parameter::Duration),
+            (
+                "\\PC{0,32}",
+                "https://[a-z]{1,16}\\.example\\.com/[a-z0-9/]{0,16}"
+            )
+                .prop_map(|(linktext, href)| Parameter::Link { linktext, href }),
+            any::<i64>().prop_map(|number| Parameter::Number(Number::from(number))),
+            (0..=100u8).prop_map(Parameter::Percentage),
+            "\\PC{0,64}".prop_map(Parameter::Text),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Data {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Data>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        ("\\PC{0,32}", any::<Parameter>())
+            .prop_map(|(title, parameter)| Data { title, parameter })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Annotation {
+    type Parameters = ArbitraryLimits;
+    type Strategy = BoxedStrategy<Annotation>;
+
+    fn arbitrary_with(limits: ArbitraryLimits) -> Self::Strategy {
+        (
+            text(MESSAGE_LIMIT, limits),
+            any::<Severity>(),
+            option::of(any::<Type>()),
+            option::of("[a-z0-9_]{1,16}(/[a-z0-9_]{1,16}){0,3}\\.[a-z]{1,4}"),
+            option::of(any::<u32>()),
+            option::of("https://[a-z]{1,16}\\.example\\.com/[a-z0-9/]{0,16}"),
+            option::of(text(EXTERNAL_ID_LIMIT, ArbitraryLimits::Respect)),
+        )
+            .prop_map(
+                |(message, severity, annotation_type, path, line, link, external_id)| Annotation {
+                    message,
+                    severity,
+                    annotation_type,
+                    path,
+                    line,
+                    link,
+                    external_id,
+                    #[cfg(feature = "json")]
+                    extensions: BTreeMap::new(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for Annotations {
+    type Parameters = ArbitraryLimits;
+    type Strategy = BoxedStrategy<Annotations>;
+
+    fn arbitrary_with(limits: ArbitraryLimits) -> Self::Strategy {
+        let len = match limits {
+            ArbitraryLimits::Respect => 0..=32usize,
+            ArbitraryLimits::Exceed => 1..=32usize,
+        };
+        vec(any_with::<Annotation>(limits), len)
+            .prop_map(Annotations::new)
+            .boxed()
+    }
+}
+
+impl Arbitrary for Report {
+    type Parameters = ArbitraryLimits;
+    type Strategy = BoxedStrategy<Report>;
+
+    fn arbitrary_with(limits: ArbitraryLimits) -> Self::Strategy {
+        let respect = ArbitraryLimits::Respect;
+        (
+            text(TITLE_LIMIT, limits),
+            option::of(text(DETAILS_LIMIT, respect)),
+            option::of(any::<ReportResult>()),
+            option::of(vec(any::<Data>(), 0..=DATA_LIMIT)),
+            option::of(text(REPORTER_LIMIT, respect)),
+            option::of("https://[a-z]{1,16}\\.example\\.com/[a-z0-9/]{0,16}"),
+            option::of("https://[a-z]{1,16}\\.example\\.com/[a-z0-9/]{0,16}\\.svg"),
+            option::of(any::<ReportType>()),
+        )
+            .prop_map(
+                |(title, details, result, data, reporter, link, logo_url, report_type)| Report {
+                    title,
+                    details,
+                    result,
+                    data,
+                    reporter,
+                    link,
+                    logo_url,
+                    report_type,
+                    #[cfg(feature = "json")]
+                    extensions: BTreeMap::new(),
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod properties {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn report_round_trip(report in any::<Report>()) {
+            let expected = serde_json::to_value(&report).unwrap();
+            let json = serde_json::to_string(&report).unwrap();
+            let report = serde_json::from_str::<Report>(&json).unwrap();
+            prop_assert_eq!(expected, serde_json::to_value(&report).unwrap());
+        }
+
+        #[test]
+        fn exceeding_limits(
+            report in any_with::<Report>(ArbitraryLimits::Exceed),
+            annotations in any_with::<Annotations>(ArbitraryLimits::Exceed),
+        ) {
+            prop_assert!(report.validate_fields().is_err());
+            prop_assert!(annotations
+                .annotations
+                .iter()
+                .all(|annotation| annotation.validate_fields().is_err()));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotation, Annotations};
+use crate::error::Result;
+use crate::file::{read_json, write_json};
+use crate::fingerprint::location_independent;
+
+/// The findings that were known when adopting a tool, so that only new
+/// findings are annotated.
+///
+/// Findings are identified by a fingerprint of their annotation that leaves
+/// out the line and link, so that they are still recognized after lines
+/// above them were added or removed. As a consequence, a new finding with the
+/// same message in the same file as a known one is suppressed as well.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    fingerprints: BTreeSet<String>,
+}
+
+impl Baseline {
+    /// Constructs an empty baseline.
+    pub fn new() -> Self {
+        Baseline::default()
+    }
+
+    /// Records the findings of `annotations`.
+    pub fn from_annotations(annotations: &Annotations) -> Self {
+        Baseline {
+            fingerprints: annotations
+                .annotations
+                .iter()
+                .map(location_independent)
+                .collect(),
+        }
+    }
+
+    /// Returns the number of recorded findings.
+    pub fn len(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    /// Returns `true` if no findings are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+
+    /// Returns `true` if the finding of `annotation` is recorded.
+    pub fn contains(&self, annotation: &Annotation) -> bool {
+        self.fingerprints
+            .contains(&location_independent(annotation))
+    }
+
+    /// Removes the findings that are no longer among the `current`
+    /// annotations, e.g. because they were fixed, returning the number of
+    /// removed findings.
+    pub fn prune(&mut self, current: &Annotations) -> usize {
+        let current = current
+            .annotations
+            .iter()
+            .map(location_independent)
+            .collect::<BTreeSet<_>>();
+        let before = self.fingerprints.len();
+        self.fingerprints
+            .retain(|fingerprint| current.contains(fingerprint));
+        before - self.fingerprints.len()
+    }
+
+    /// Reads a baseline from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or is not a valid
+    /// baseline.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Baseline> {
+        read_json(path.as_ref())
+    }
+
+    /// Writes the baseline to a JSON file, replacing it atomically if it
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be written.
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_json(path.as_ref(), self)
+    }
+}
+
+impl Annotations {
+    /// Removes the annotations whose findings are recorded in `baseline`,
+    /// returning the number of removed annotations.
+    pub fn suppress(&mut self, baseline: &Baseline) -> usize {
+        let before = self.annotations.len();
+        self.annotations
+            .retain(|annotation| !baseline.contains(annotation));
+        before - self.annotations.len()
+    }
+}
+
+#[cfg(test)]
+mod suppression {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation(message: &str, path: &str, line: u32) -> Annotation {
+        AnnotationBuilder::new(message, Severity::Medium)
+            .path(path)
+            .line(line)
+            .link(format!("https://example.com/{path}#L{line}"))
+            .build()
+            .unwrap()
+    }
+
+    fn legacy() -> Annotations {
+        Annotations::new(vec![
+            annotation("Unused import", "src/lib.rs", 3),
+            annotation("Possible panic", "src/lib.rs", 40),
+            annotation("Possible panic", "src/main.rs", 12),
+        ])
+    }
+
+    #[test]
+    fn suppress() {
+        let baseline = Baseline::from_annotations(&legacy());
+        assert_eq!(3, baseline.len());
+
+        let mut annotations = Annotations::new(vec![
+            annotation("Unused import", "src/lib.rs", 3),
+            annotation("Unused import", "src/main.rs", 3),
+            annotation("Possible panic", "src/main.rs", 12),
+        ]);
+        assert_eq!(2, annotations.suppress(&baseline));
+        assert_eq!(
+            Annotations::new(vec![annotation("Unused import", "src/main.rs", 3)]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn moved_lines() {
+        let baseline = Baseline::from_annotations(&legacy());
+        let mut annotations = Annotations::new(vec![
+            annotation("Unused import", "src/lib.rs", 5),
+            annotation("Possible panic", "src/lib.rs", 42),
+        ]);
+        assert_eq!(2, annotations.suppress(&baseline));
+        assert!(annotations.annotations.is_empty());
+
+        // Other changes are new findings.
+        let mut annotations = Annotations::new(vec![AnnotationBuilder::new(
+            "Possible panic",
+            Severity::High,
+        )
+        .path("src/lib.rs")
+        .line(40)
+        .build()
+        .unwrap()]);
+        assert_eq!(0, annotations.suppress(&baseline));
+    }
+
+    #[test]
+    fn prune() {
+        let mut baseline = Baseline::from_annotations(&legacy());
+        let current = Annotations::new(vec![annotation("Possible panic", "src/main.rs", 20)]);
+        assert_eq!(2, baseline.prune(&current));
+        assert_eq!(1, baseline.len());
+        assert!(baseline.contains(&annotation("Possible panic", "src/main.rs", 1)));
+        assert!(!baseline.contains(&annotation("Possible panic", "src/lib.rs", 40)));
+
+        assert_eq!(1, baseline.prune(&Annotations::new(vec![])));
+        assert!(baseline.is_empty());
+    }
+
+    #[test]
+    fn json_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("baseline.json");
+        let baseline = Baseline::from_annotations(&legacy());
+        baseline.to_json_file(&path).unwrap();
+        assert_eq!(baseline, Baseline::from_json_file(&path).unwrap());
+    }
+}
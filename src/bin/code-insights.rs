@@ -0,0 +1,202 @@
+//! Assembles Code Insights payloads in shell-based pipelines.
+//!
+//! Reads annotations as JSON or JSON Lines from stdin, filters them, builds a
+//! report from the flags and prints both as a validated bundle. Exits with 0
+//! if the report passed, 1 if it failed and 2 on any error.
+
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::process::ExitCode;
+
+use code_insights::{
+    Annotations, ChangedLines, Data, InsightBundle, Number, Parameter, PathFilter, Percentage,
+    ReportBuilder, ReportKey, ReportResult, Severity,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+const USAGE: &str = "\
+Usage: code-insights --key KEY --title TITLE [OPTIONS] < annotations.json
+
+Reads annotations from stdin and prints the report and the annotations as a
+validated bundle.
+
+Options:
+  --key KEY              Key the report is published under
+  --title TITLE          Title of the report
+  --details DETAILS      Details of the report
+  --reporter REPORTER    Tool or company that created the report
+  --link URL             Link to the results in an external tool
+  --result pass|fail     Result of the report
+  --data TITLE=VALUE     Data field, e.g. coverage=87% (repeatable)
+  --ndjson               Read the annotations as JSON Lines
+  --min-severity LEVEL   Drop annotations below low, medium or high
+  --include GLOB         Keep only annotations on matching paths (repeatable)
+  --exclude GLOB         Drop annotations on matching paths (repeatable)
+  --diff FILE            Keep only annotations on lines changed by a unified diff
+  --help                 Print this help
+
+Exits with 0 if the report passed, 1 if it failed and 2 on errors.";
+
+#[derive(Default)]
+struct Options {
+    key: Option<String>,
+    title: Option<String>,
+    details: Option<String>,
+    reporter: Option<String>,
+    link: Option<String>,
+    result: Option<ReportResult>,
+    data: Vec<Data>,
+    ndjson: bool,
+    min_severity: Option<Severity>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    diff: Option<String>,
+}
+
+/// Parses `value` as the uppercase string a payload enum is serialized as.
+fn parse_enum<T: DeserializeOwned>(flag: &str, value: &str) -> Result<T, String> {
+    serde_json::from_value(Value::String(value.to_ascii_uppercase()))
+        .map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
+
+/// Parses a data field, guessing the type of the value: `87%` is a
+/// percentage, `true` and `false` are booleans, numbers are numbers and
+/// anything else is text.
+fn parse_data(field: &str) -> Result<Data, String> {
+    let (title, value) = field
+        .split_once('=')
+        .ok_or_else(|| format!("invalid data field '{field}', expected TITLE=VALUE"))?;
+    let parameter = if let Some(percentage) = value.strip_suffix('%') {
+        let percentage = percentage
+            .parse::<u8>()
+            .map_err(|_| format!("invalid percentage '{value}'"))?;
+        Parameter::from(Percentage::new(percentage).map_err(|error| error.to_string())?)
+    } else if let Ok(boolean) = value.parse::<bool>() {
+        Parameter::Boolean(boolean)
+    } else if let Ok(integer) = value.parse::<i64>() {
+        Parameter::Number(Number::from(integer))
+    } else if let Some(float) = value.parse::<f64>().ok().and_then(Number::from_f64) {
+        Parameter::Number(float)
+    } else {
+        Parameter::Text(value.to_owned())
+    };
+    Ok(Data {
+        title: title.to_owned(),
+        parameter,
+    })
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            return Ok(None);
+        }
+        if flag == "--ndjson" {
+            options.ndjson = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--key" => options.key = Some(value),
+            "--title" => options.title = Some(value),
+            "--details" => options.details = Some(value),
+            "--reporter" => options.reporter = Some(value),
+            "--link" => options.link = Some(value),
+            "--result" => options.result = Some(parse_enum(&flag, &value)?),
+            "--data" => options.data.push(parse_data(&value)?),
+            "--min-severity" => options.min_severity = Some(parse_enum(&flag, &value)?),
+            "--include" => options.include.push(value),
+            "--exclude" => options.exclude.push(value),
+            "--diff" => options.diff = Some(value),
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn run(options: Options) -> Result<ReportResult, String> {
+    let key = options.key.ok_or("missing --key")?;
+    let key = ReportKey::new(key).map_err(|error| error.to_string())?;
+    let title = options.title.ok_or("missing --title")?;
+
+    let stdin = io::stdin().lock();
+    let mut annotations = if options.ndjson {
+        Annotations::from_ndjson(stdin)
+    } else {
+        Annotations::from_reader(stdin)
+    }
+    .map_err(|error| format!("invalid annotations: {error}"))?;
+
+    if let Some(min) = options.min_severity {
+        annotations.retain_severity(min);
+    }
+    if !options.include.is_empty() || !options.exclude.is_empty() {
+        let include = options
+            .include
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let exclude = options
+            .exclude
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let filter = PathFilter::new(&include, &exclude).map_err(|error| error.to_string())?;
+        annotations.filter_paths(&filter);
+    }
+    if let Some(path) = options.diff {
+        let file = File::open(&path).map_err(|error| format!("cannot open '{path}': {error}"))?;
+        let changed = ChangedLines::from_unified_diff(BufReader::new(file))
+            .map_err(|error| error.to_string())?;
+        annotations.retain_changed(&changed);
+    }
+
+    let mut builder = ReportBuilder::new(title);
+    if !options.data.is_empty() {
+        builder.set_data(options.data);
+    }
+    if let Some(details) = options.details {
+        builder.set_details(details);
+    }
+    if let Some(reporter) = options.reporter {
+        builder.set_reporter(reporter);
+    }
+    if let Some(link) = options.link {
+        builder.set_link(link);
+    }
+    if let Some(result) = options.result {
+        builder.set_result(result);
+    }
+    let report = builder.build().map_err(|error| error.to_string())?;
+
+    let bundle = InsightBundle::new(key, report, annotations);
+    bundle.validate().map_err(|error| error.to_string())?;
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &bundle)
+        .map_err(|error| error.to_string())
+        .and_then(|()| writeln!(stdout).map_err(|error| error.to_string()))?;
+    Ok(options.result.unwrap_or(ReportResult::Pass))
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(std::env::args().skip(1)).and_then(|options| match options {
+        Some(options) => run(options).map(Some),
+        None => Ok(None),
+    });
+    match result {
+        Ok(None) => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Ok(Some(ReportResult::Pass)) => ExitCode::SUCCESS,
+        Ok(Some(ReportResult::Fail)) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("code-insights: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
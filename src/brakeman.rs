@@ -0,0 +1,271 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::annotation::{Annotations, Severity, Type};
+use crate::error::{Error, Result};
+use crate::finding::{Finding, FindingMapper, SeverityMap};
+use crate::report::{Data, Parameter};
+
+/// The result of converting the output of a security scanner.
+///
+/// Besides the annotations for the findings, it holds data fields summarizing
+/// the scan and the fingerprints of obsolete findings, i.e. findings that are
+/// ignored by the scanner but no longer occur. Those can be used to delete
+/// previously posted annotations through their external IDs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityInsight {
+    annotations: Annotations,
+    data: Vec<Data>,
+    obsolete: Vec<String>,
+}
+
+impl SecurityInsight {
+    /// Returns the annotations for the findings and scan errors.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Returns the data fields summarizing the scan.
+    pub fn data(&self) -> &[Data] {
+        &self.data
+    }
+
+    /// Returns the fingerprints of obsolete findings.
+    pub fn obsolete(&self) -> &[String] {
+        &self.obsolete
+    }
+
+    /// Consumes the insight, returning the annotations, the data fields and
+    /// the obsolete fingerprints.
+    pub fn into_parts(self) -> (Annotations, Vec<Data>, Vec<String>) {
+        (self.annotations, self.data, self.obsolete)
+    }
+}
+
+#[derive(Deserialize)]
+struct BrakemanOutput {
+    scan_info: BrakemanScanInfo,
+    warnings: Vec<BrakemanWarning>,
+    #[serde(default)]
+    errors: Vec<BrakemanError>,
+    #[serde(default)]
+    obsolete: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BrakemanScanInfo {
+    security_warnings: u64,
+    number_of_controllers: u64,
+    number_of_models: u64,
+    number_of_templates: u64,
+}
+
+#[derive(Deserialize)]
+struct BrakemanWarning {
+    warning_type: String,
+    fingerprint: String,
+    message: String,
+    file: String,
+    line: Option<u32>,
+    link: Option<String>,
+    confidence: String,
+}
+
+#[derive(Deserialize)]
+struct BrakemanError {
+    error: String,
+    location: Option<String>,
+}
+
+/// Converts the output of `brakeman --format json`.
+///
+/// Every warning becomes a [`Type::Vulnerability`] annotation whose severity
+/// is derived from the confidence: `High` becomes [`Severity::High`],
+/// `Medium` becomes [`Severity::Medium`] and `Weak` becomes [`Severity::Low`].
+/// The Brakeman fingerprint is used as the external ID. Other severities can
+/// be configured through `severity_map`, where warnings are identified by
+/// their warning type.
+///
+/// Errors that occurred during the scan become repository-level annotations,
+/// and the number of security warnings, errors, controllers, models and
+/// templates are returned as data fields.
+///
+/// # Errors
+///
+/// Will return `Err` if `value` is not Brakeman JSON output.
+pub fn from_brakeman_json(
+    value: &Value,
+    severity_map: Option<&SeverityMap>,
+) -> Result<SecurityInsight> {
+    let output = BrakemanOutput::deserialize(value)
+        .map_err(|error| Error::parse_source("Brakeman", error))?;
+
+    let warnings = output.warnings.into_iter().map(|warning| {
+        let severity = match warning.confidence.as_str() {
+            "High" => Severity::High,
+            "Medium" => Severity::Medium,
+            _ => Severity::Low,
+        };
+
+        Finding {
+            rule_id: Some(warning.warning_type),
+            path: Some(warning.file),
+            line: warning.line,
+            link: warning.link,
+            severity: Some(severity),
+            finding_type: Some(Type::Vulnerability),
+            fingerprint: Some(warning.fingerprint),
+            ..Finding::new("Brakeman", warning.message, warning.confidence)
+        }
+    });
+    let errors = output.errors.iter().map(|error| {
+        let message = match &error.location {
+            Some(location) => format!("Brakeman error in {location}: {}", error.error),
+            None => format!("Brakeman error: {}", error.error),
+        };
+
+        Finding {
+            severity: Some(Severity::Medium),
+            ..Finding::new("Brakeman", message, "error")
+        }
+    });
+    let annotations = FindingMapper::new()
+        .severity_map(severity_map.cloned().unwrap_or_default())
+        .map_all(warnings.chain(errors))?;
+
+    let scan_info = output.scan_info;
+    let data = [
+        ("Security warnings", scan_info.security_warnings),
+        ("Errors", output.errors.len() as u64),
+        ("Controllers", scan_info.number_of_controllers),
+        ("Models", scan_info.number_of_models),
+        ("Templates", scan_info.number_of_templates),
+    ]
+    .into_iter()
+    .map(|(title, count)| Data {
+        title: title.to_owned(),
+        parameter: Parameter::Number(count.into()),
+    })
+    .collect();
+
+    Ok(SecurityInsight {
+        annotations,
+        data,
+        obsolete: output.obsolete,
+    })
+}
+
+#[cfg(test)]
+mod conversion {
+    use super::*;
+    use serde_json::json;
+
+    fn output() -> Value {
+        json!({
+            "scan_info": {
+                "app_path": "/home/ci/app",
+                "rails_version": "7.0.4",
+                "security_warnings": 2,
+                "start_time": "2023-03-01 10:00:00 +0000",
+                "end_time": "2023-03-01 10:00:02 +0000",
+                "duration": 2.1,
+                "checks_performed": ["SQL", "Render"],
+                "number_of_controllers": 12,
+                "number_of_models": 8,
+                "number_of_templates": 30,
+                "ruby_version": "3.2.0",
+                "brakeman_version": "5.4.1"
+            },
+            "ignored_warnings": [],
+            "warnings": [
+                {
+                    "warning_type": "SQL Injection",
+                    "warning_code": 0,
+                    "fingerprint": "6f1d1f3b0a1b0c9e",
+                    "check_name": "SQL",
+                    "message": "Possible SQL injection",
+                    "file": "app/models/user.rb",
+                    "line": 10,
+                    "link": "https://brakemanscanner.org/docs/warning_types/sql_injection/",
+                    "code": "where(\"name = '#{params[:name]}'\")",
+                    "render_path": null,
+                    "user_input": "params[:name]",
+                    "confidence": "High",
+                    "cwe_id": [89]
+                },
+                {
+                    "warning_type": "Dynamic Render Path",
+                    "warning_code": 15,
+                    "fingerprint": "a9b8c7d6e5f40312",
+                    "check_name": "Render",
+                    "message": "Render path contains parameter value",
+                    "file": "app/controllers/pages_controller.rb",
+                    "line": null,
+                    "link": "https://brakemanscanner.org/docs/warning_types/dynamic_render_paths/",
+                    "confidence": "Weak"
+                }
+            ],
+            "errors": [
+                {"error": "Could not parse template", "location": "app/views/broken.html.erb"}
+            ],
+            "obsolete": ["0123456789abcdef"]
+        })
+    }
+
+    #[test]
+    fn warnings_and_errors() {
+        let expected = json!({"annotations": [
+            {
+                "message": "[Brakeman::SQL_Injection] Possible SQL injection",
+                "severity": "HIGH",
+                "type": "VULNERABILITY",
+                "path": "app/models/user.rb",
+                "line": 10,
+                "link": "https://brakemanscanner.org/docs/warning_types/sql_injection/",
+                "externalId": "6f1d1f3b0a1b0c9e"
+            },
+            {
+                "message": "[Brakeman::Dynamic_Render_Path] Render path contains parameter value",
+                "severity": "LOW",
+                "type": "VULNERABILITY",
+                "path": "app/controllers/pages_controller.rb",
+                "link": "https://brakemanscanner.org/docs/warning_types/dynamic_render_paths/",
+                "externalId": "a9b8c7d6e5f40312"
+            },
+            {
+                "message": "Brakeman error in app/views/broken.html.erb: Could not parse template",
+                "severity": "MEDIUM"
+            }
+        ]});
+        let insight = from_brakeman_json(&output(), None).unwrap();
+        assert_eq!(
+            expected,
+            serde_json::to_value(insight.annotations()).unwrap()
+        );
+    }
+
+    #[test]
+    fn obsolete_fingerprints() {
+        let insight = from_brakeman_json(&output(), None).unwrap();
+        assert_eq!(["0123456789abcdef"], insight.obsolete());
+    }
+
+    #[test]
+    fn scan_info_data() {
+        let expected = json!([
+            {"title": "Security warnings", "type": "NUMBER", "value": 2},
+            {"title": "Errors", "type": "NUMBER", "value": 1},
+            {"title": "Controllers", "type": "NUMBER", "value": 12},
+            {"title": "Models", "type": "NUMBER", "value": 8},
+            {"title": "Templates", "type": "NUMBER", "value": 30}
+        ]);
+        let (_, data, _) = from_brakeman_json(&output(), None).unwrap().into_parts();
+        assert_eq!(expected, serde_json::to_value(data).unwrap());
+    }
+
+    #[test]
+    fn malformed() {
+        let result = from_brakeman_json(&json!({"warnings": []}), None);
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,973 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotation, Annotations, Severity, MESSAGE_LIMIT};
+use crate::validation::{truncate, ELLIPSIS};
+
+/// Decides how [`Annotations::rollup`] makes annotations fit within a budget.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RollupStrategy {
+    /// Keeps the most important annotations of every file and replaces the
+    /// remaining annotations of each file by a file-level summary.
+    PerFile,
+}
+
+/// Decides what happens to the annotations exceeding the cap of
+/// [`Annotations::cap_per_file`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowBehavior {
+    /// Removes the annotations.
+    Drop,
+
+    /// Replaces the annotations by a file-level annotation stating their
+    /// number and highest severity.
+    Summarize,
+}
+
+/// Decides which annotations [`Annotations::collapse_duplicates`] considers
+/// duplicates of each other.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CollapseScope {
+    /// Annotations with the same message on the same file.
+    PerFile,
+
+    /// Annotations with the same message anywhere.
+    Global,
+}
+
+/// Decides how [`Annotations::sample`] groups annotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKey {
+    /// Annotations on the same file.
+    Path,
+
+    /// Annotations of the same tool and rule, as set by
+    /// [`AnnotationBuilder::rule`](crate::AnnotationBuilder::rule).
+    RulePrefix,
+
+    /// Annotations of the same rule on the same file.
+    PathAndRule,
+}
+
+/// A group of annotations that [`Annotations::sample`] removed annotations
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampledGroup {
+    path: Option<String>,
+    rule: Option<String>,
+    removed: usize,
+}
+
+impl SampledGroup {
+    /// Returns the path of the group, or `None` if the annotations are not
+    /// grouped by path or have no path.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the rule of the group as `tool::rule`, or `None` if the
+    /// annotations are not grouped by rule or have no rule prefix.
+    pub fn rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    /// Returns the number of annotations removed from the group.
+    pub fn removed(&self) -> usize {
+        self.removed
+    }
+}
+
+impl Annotations {
+    /// Reduces the annotations to at most `budget`, summarizing the
+    /// annotations that are left out according to `strategy`.
+    ///
+    /// With [`RollupStrategy::PerFile`] the budget is shared evenly between
+    /// files, where a file with more annotations than its share keeps its most
+    /// important annotations, ordered by severity and then type, and gets a
+    /// summary annotation stating the number of further annotations and their
+    /// highest severity. Annotations without a path are treated as one file
+    /// and summarized at the repository level. If there are more files than
+    /// the budget, only the files with the most important annotations are
+    /// kept, each represented by a summary.
+    ///
+    /// Annotations that fit within the budget are returned as they are.
+    pub fn rollup(self, budget: usize, strategy: RollupStrategy) -> Annotations {
+        self.rollup_counted(budget, strategy).0
+    }
+
+    /// Like [`rollup`](Annotations::rollup), but also returns the number of
+    /// annotations that are left out or summarized.
+    pub(crate) fn rollup_counted(
+        self,
+        budget: usize,
+        strategy: RollupStrategy,
+    ) -> (Annotations, usize) {
+        match strategy {
+            RollupStrategy::PerFile => rollup_per_file(self.annotations, budget),
+        }
+    }
+
+    /// Keeps the `n` annotations that come first when ordered by `key`,
+    /// returning them in their original order along with the number of
+    /// dropped annotations.
+    ///
+    /// Annotations with equal keys are ordered as by
+    /// [`keep_top_by_severity`](Annotations::keep_top_by_severity), so the
+    /// same annotations are kept no matter their original order.
+    pub fn keep_top<K, F>(self, n: usize, key: F) -> (Annotations, usize)
+    where
+        K: Ord,
+        F: Fn(&Annotation) -> K,
+    {
+        self.keep_top_by(n, |a, b| {
+            key(a)
+                .cmp(&key(b))
+                .then_with(|| priority(a).cmp(&priority(b)))
+        })
+    }
+
+    /// Keeps the `n` most important annotations, returning them in their
+    /// original order along with the number of dropped annotations.
+    ///
+    /// Annotations are ordered by severity, from high to low, then by type,
+    /// from vulnerabilities over bugs to code smells and annotations without a
+    /// type, and finally by path, line and contents.
+    pub fn keep_top_by_severity(self, n: usize) -> (Annotations, usize) {
+        self.keep_top_by(n, |a, b| priority(a).cmp(&priority(b)))
+    }
+
+    /// Limits the number of annotations of every file to `max`, returning the
+    /// number of annotations that were removed or summarized.
+    ///
+    /// Files with more annotations keep their most important annotations,
+    /// ordered as by [`keep_top_by_severity`](Annotations::keep_top_by_severity).
+    /// With [`OverflowBehavior::Summarize`] the summary counts towards `max`
+    /// and is added after all other annotations. Annotations without a path
+    /// are not limited.
+    pub fn cap_per_file(&mut self, max: usize, overflow: OverflowBehavior) -> usize {
+        let kept_per_file = match overflow {
+            OverflowBehavior::Drop => max,
+            OverflowBehavior::Summarize => max.saturating_sub(1),
+        };
+
+        let mut files = BTreeMap::<&str, Vec<usize>>::new();
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            if let Some(path) = &annotation.path {
+                files.entry(path).or_default().push(index);
+            }
+        }
+
+        let mut overflowing = HashSet::new();
+        let mut summaries = Vec::new();
+        for (path, mut indices) in files {
+            if indices.len() <= max {
+                continue;
+            }
+            indices.sort_by(|a, b| {
+                priority(&self.annotations[*a]).cmp(&priority(&self.annotations[*b]))
+            });
+            let rest = indices.split_off(kept_per_file);
+            if overflow == OverflowBehavior::Summarize {
+                let rest = rest
+                    .iter()
+                    .map(|index| &self.annotations[*index])
+                    .collect::<Vec<_>>();
+                summaries.push(summary(Some(path.to_owned()), &rest));
+            }
+            overflowing.extend(rest);
+        }
+
+        let mut index = 0;
+        self.annotations.retain(|_| {
+            index += 1;
+            !overflowing.contains(&(index - 1))
+        });
+        self.annotations.extend(summaries);
+        overflowing.len()
+    }
+
+    /// Replaces annotations with the same message by a single annotation,
+    /// returning the number of removed annotations.
+    ///
+    /// The remaining annotation keeps the place and location of the first
+    /// occurrence and gets the highest severity of all occurrences. Its
+    /// message states the number of further occurrences and lists their
+    /// lines, or their paths and lines with [`CollapseScope::Global`], as far
+    /// as they fit within [`MESSAGE_LIMIT`].
+    pub fn collapse_duplicates(&mut self, scope: CollapseScope) -> usize {
+        let before = self.annotations.len();
+        let mut groups = HashMap::<(Option<String>, String), usize>::new();
+        let mut collapsed = Vec::<(Annotation, Vec<String>, usize)>::new();
+
+        for annotation in self.annotations.drain(..) {
+            let path = match scope {
+                CollapseScope::PerFile => annotation.path.clone(),
+                CollapseScope::Global => None,
+            };
+            let Some(index) = groups.get(&(path.clone(), annotation.message.clone())) else {
+                groups.insert((path, annotation.message.clone()), collapsed.len());
+                collapsed.push((annotation, Vec::new(), 0));
+                continue;
+            };
+
+            let (first, locations, count) = &mut collapsed[*index];
+            first.severity = first.severity.max(annotation.severity);
+            let line = annotation.line.filter(|line| *line > 0);
+            match (scope, annotation.path, line) {
+                (CollapseScope::PerFile, _, Some(line)) => locations.push(line.to_string()),
+                (CollapseScope::Global, Some(path), Some(line)) => {
+                    locations.push(format!("{path}:{line}"));
+                }
+                (CollapseScope::Global, Some(path), None) => locations.push(path),
+                _ => {}
+            }
+            *count += 1;
+        }
+
+        self.annotations = collapsed
+            .into_iter()
+            .map(|(mut annotation, locations, count)| {
+                if count > 0 {
+                    let label = match scope {
+                        CollapseScope::PerFile => "lines",
+                        CollapseScope::Global => "locations",
+                    };
+                    annotation.message =
+                        occurrences_message(&annotation.message, count, label, &locations);
+                }
+                annotation
+            })
+            .collect();
+        before - self.annotations.len()
+    }
+
+    /// Keeps at most `per_group` annotations of every group formed by
+    /// `group`, so that every kind of problem is represented, returning the
+    /// groups that annotations were removed from, ordered by path and rule.
+    ///
+    /// Every group keeps its most important annotations, ordered as by
+    /// [`keep_top_by_severity`](Annotations::keep_top_by_severity), in their
+    /// original order. Annotations without a path or rule prefix form a group
+    /// of their own.
+    pub fn sample(&mut self, per_group: usize, group: GroupKey) -> Vec<SampledGroup> {
+        let mut groups = BTreeMap::<(Option<&str>, Option<String>), Vec<usize>>::new();
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            let path = match group {
+                GroupKey::Path | GroupKey::PathAndRule => annotation.path.as_deref(),
+                GroupKey::RulePrefix => None,
+            };
+            let rule = match group {
+                GroupKey::RulePrefix | GroupKey::PathAndRule => annotation
+                    .rule()
+                    .map(|(tool, rule)| format!("{tool}::{rule}")),
+                GroupKey::Path => None,
+            };
+            groups.entry((path, rule)).or_default().push(index);
+        }
+
+        let mut removed = HashSet::new();
+        let mut sampled = Vec::new();
+        for ((path, rule), mut indices) in groups {
+            if indices.len() <= per_group {
+                continue;
+            }
+            indices.sort_by(|a, b| {
+                priority(&self.annotations[*a]).cmp(&priority(&self.annotations[*b]))
+            });
+            let rest = indices.split_off(per_group);
+            sampled.push(SampledGroup {
+                path: path.map(str::to_owned),
+                rule,
+                removed: rest.len(),
+            });
+            removed.extend(rest);
+        }
+
+        let mut index = 0;
+        self.annotations.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+        sampled
+    }
+
+    fn keep_top_by<F>(self, n: usize, compare: F) -> (Annotations, usize)
+    where
+        F: Fn(&Annotation, &Annotation) -> Ordering,
+    {
+        let mut annotations = self.annotations;
+        if annotations.len() <= n {
+            return (Annotations::new(annotations), 0);
+        }
+
+        let mut order = (0..annotations.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| compare(&annotations[*a], &annotations[*b]));
+        let mut keep = vec![false; annotations.len()];
+        for index in &order[..n] {
+            keep[*index] = true;
+        }
+
+        let dropped = annotations.len() - n;
+        let mut keep = keep.into_iter();
+        annotations.retain(|_| keep.next().unwrap_or(false));
+        (Annotations::new(annotations), dropped)
+    }
+}
+
+fn rollup_per_file(annotations: Vec<Annotation>, budget: usize) -> (Annotations, usize) {
+    if annotations.len() <= budget {
+        return (Annotations::new(annotations), 0);
+    }
+
+    let total = annotations.len();
+    let mut files = BTreeMap::<Option<String>, Vec<Annotation>>::new();
+    for annotation in annotations {
+        files
+            .entry(annotation.path.clone())
+            .or_default()
+            .push(annotation);
+    }
+    let mut files = files.into_iter().collect::<Vec<_>>();
+    for (_, annotations) in &mut files {
+        annotations.sort_by(|a, b| priority(a).cmp(&priority(b)));
+    }
+
+    if files.len() > budget {
+        files.sort_by(|(_, a), (_, b)| {
+            priority(&a[0])
+                .cmp(&priority(&b[0]))
+                .then_with(|| b.len().cmp(&a.len()))
+        });
+        files.truncate(budget);
+    }
+
+    // Every file costs one annotation, which is a summary unless the file has
+    // a single annotation. The remaining budget is handed out one annotation
+    // at a time to the file that keeps the fewest.
+    let mut kept = files
+        .iter()
+        .map(|(_, annotations)| usize::from(annotations.len() == 1))
+        .collect::<Vec<_>>();
+    let mut used = files.len();
+    while used < budget {
+        let Some(index) = (0..files.len())
+            .filter(|index| kept[*index] < files[*index].1.len())
+            .min_by_key(|index| kept[*index])
+        else {
+            break;
+        };
+        kept[index] += 1;
+        // Summarizing a single annotation costs as much as keeping it.
+        if kept[index] + 1 == files[index].1.len() {
+            kept[index] += 1;
+        }
+        used += 1;
+    }
+
+    let mut rolled_up = Vec::with_capacity(used);
+    let mut summaries = 0;
+    for ((path, mut annotations), kept) in files.into_iter().zip(kept) {
+        let rest = annotations.split_off(kept);
+        rolled_up.extend(annotations);
+        if !rest.is_empty() {
+            summaries += 1;
+            rolled_up.push(summary(path, &rest.iter().collect::<Vec<_>>()));
+        }
+    }
+    let shown = rolled_up.len() - summaries;
+    (Annotations::new(rolled_up), total - shown)
+}
+
+/// Appends the number of further occurrences and as many of their locations
+/// as fit within [`MESSAGE_LIMIT`] to `message`, which is truncated if even
+/// the count does not fit.
+fn occurrences_message(message: &str, count: usize, label: &str, locations: &[String]) -> String {
+    let plural = if count == 1 { "" } else { "s" };
+    let head = format!(" (+{count} more occurrence{plural}");
+    let more = format!(", {ELLIPSIS}");
+
+    let mut message = message.to_owned();
+    truncate(&mut message, MESSAGE_LIMIT - head.len() - 1);
+    let mut room = MESSAGE_LIMIT - message.len() - head.len() - 1;
+    message.push_str(&head);
+
+    for (index, location) in locations.iter().enumerate() {
+        let separator = if index == 0 {
+            format!(", {label} ")
+        } else {
+            ", ".to_owned()
+        };
+        let reserved = if index + 1 < locations.len() {
+            more.len()
+        } else {
+            0
+        };
+        if separator.len() + location.len() + reserved > room {
+            if index > 0 {
+                message.push_str(&more);
+            }
+            break;
+        }
+        room -= separator.len() + location.len();
+        message.push_str(&separator);
+        message.push_str(location);
+    }
+    message.push(')');
+    message
+}
+
+/// Orders annotations from the most to the least important, falling back to
+/// their location and contents so that the order is deterministic.
+pub(crate) fn priority(annotation: &Annotation) -> impl Ord + '_ {
+    (
+        Reverse(annotation.severity),
+        annotation.annotation_type.map_or(u8::MAX, |t| t.rank()),
+        annotation.path.as_deref(),
+        annotation.line,
+        annotation.message.as_str(),
+        annotation.external_id.as_deref(),
+    )
+}
+
+/// Constructs a file-level annotation summarizing `rest`, which must not be
+/// empty.
+fn summary(path: Option<String>, rest: &[&Annotation]) -> Annotation {
+    let severity = rest
+        .iter()
+        .map(|annotation| annotation.severity)
+        .max()
+        .unwrap_or(Severity::Low);
+    let annotation_type = rest[0].annotation_type.filter(|annotation_type| {
+        rest.iter()
+            .all(|annotation| annotation.annotation_type == Some(*annotation_type))
+    });
+    let kind = annotation_type.map_or_else(String::new, |annotation_type| {
+        format!(" of type {}", annotation_type.label())
+    });
+
+    Annotation {
+        message: format!(
+            "{} further annotations{kind} (highest severity {})",
+            rest.len(),
+            severity.label()
+        ),
+        severity,
+        annotation_type,
+        path,
+        line: None,
+        link: None,
+        external_id: None,
+        #[cfg(feature = "json")]
+        extensions: BTreeMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod rollup {
+    use super::*;
+    use crate::{AnnotationBuilder, Type, MESSAGE_LIMIT};
+
+    fn annotation(path: &str, line: u32, severity: Severity) -> Annotation {
+        AnnotationBuilder::new(format!("Finding on line {line}"), severity)
+            .annotation_type(Type::CodeSmell)
+            .path(path)
+            .line(line)
+            .build()
+            .unwrap()
+    }
+
+    fn severity(index: u32) -> Severity {
+        match index % 3 {
+            0 => Severity::Low,
+            1 => Severity::Medium,
+            _ => Severity::High,
+        }
+    }
+
+    #[test]
+    fn within_budget() {
+        let annotations = Annotations::new(vec![annotation("a.rs", 1, Severity::Low)]);
+        let rolled_up = Annotations::new(vec![annotation("a.rs", 1, Severity::Low)])
+            .rollup(1, RollupStrategy::PerFile);
+        assert_eq!(annotations, rolled_up);
+    }
+
+    #[test]
+    fn single_file() {
+        let annotations = (1..=8000)
+            .map(|line| annotation("src/generated.rs", line, severity(line)))
+            .collect::<Vec<_>>();
+        let rolled_up = Annotations::new(annotations).rollup(1000, RollupStrategy::PerFile);
+        assert_eq!(1000, rolled_up.annotations.len());
+
+        let (kept, summary) = rolled_up.annotations.split_at(999);
+        assert!(kept
+            .iter()
+            .all(|annotation| annotation.severity == Severity::High));
+        assert_eq!(
+            "7001 further annotations of type Code smell (highest severity High)",
+            summary[0].message
+        );
+        assert!(summary[0].message.len() <= MESSAGE_LIMIT);
+        assert_eq!(Some("src/generated.rs"), summary[0].path.as_deref());
+        assert_eq!(None, summary[0].line);
+    }
+
+    #[test]
+    fn evenly_spread() {
+        let annotations = (0..10)
+            .flat_map(|file| {
+                (1..=50)
+                    .map(move |line| annotation(&format!("src/{file}.rs"), line, severity(line)))
+            })
+            .collect::<Vec<_>>();
+        let rolled_up = Annotations::new(annotations).rollup(100, RollupStrategy::PerFile);
+        assert_eq!(100, rolled_up.annotations.len());
+
+        for file in 0..10 {
+            let path = format!("src/{file}.rs");
+            let annotations = rolled_up
+                .annotations
+                .iter()
+                .filter(|annotation| annotation.path.as_ref() == Some(&path))
+                .collect::<Vec<_>>();
+            assert_eq!(10, annotations.len());
+            assert_eq!(
+                "41 further annotations of type Code smell (highest severity High)",
+                annotations[9].message
+            );
+        }
+    }
+
+    #[test]
+    fn uneven_files() {
+        let mut annotations = (1..=20)
+            .map(|line| annotation("big.rs", line, Severity::Low))
+            .collect::<Vec<_>>();
+        annotations.push(annotation("small.rs", 1, Severity::Low));
+        annotations.push(annotation("small.rs", 2, Severity::Medium));
+
+        let rolled_up = Annotations::new(annotations).rollup(6, RollupStrategy::PerFile);
+        let messages = rolled_up
+            .annotations
+            .iter()
+            .map(|annotation| annotation.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "Finding on line 1",
+                "Finding on line 2",
+                "Finding on line 3",
+                "17 further annotations of type Code smell (highest severity Low)",
+                "Finding on line 2",
+                "Finding on line 1",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn more_files_than_budget() {
+        let annotations = (1..=5)
+            .flat_map(|file| {
+                let path = format!("src/{file}.rs");
+                [
+                    annotation(&path, 1, severity(file)),
+                    annotation(&path, 2, Severity::Low),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let rolled_up = Annotations::new(annotations).rollup(3, RollupStrategy::PerFile);
+        let paths = rolled_up
+            .annotations
+            .iter()
+            .map(|annotation| annotation.path.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["src/2.rs", "src/5.rs", "src/1.rs"], paths);
+        assert!(rolled_up
+            .annotations
+            .iter()
+            .all(|annotation| annotation.message.starts_with("2 further")));
+    }
+}
+
+#[cfg(test)]
+mod keep_top {
+    use super::*;
+    use crate::{AnnotationBuilder, Type};
+
+    fn annotation(path: &str, line: u32, severity: Severity, annotation_type: Type) -> Annotation {
+        AnnotationBuilder::new("Message", severity)
+            .annotation_type(annotation_type)
+            .path(path)
+            .line(line)
+            .build()
+            .unwrap()
+    }
+
+    fn annotations() -> Vec<Annotation> {
+        vec![
+            annotation("b.rs", 1, Severity::Medium, Type::Bug),
+            annotation("a.rs", 9, Severity::Low, Type::Vulnerability),
+            annotation("a.rs", 3, Severity::High, Type::CodeSmell),
+            annotation("c.rs", 2, Severity::Medium, Type::Vulnerability),
+            annotation("a.rs", 5, Severity::Medium, Type::Bug),
+        ]
+    }
+
+    #[test]
+    fn by_severity() {
+        let (kept, dropped) = Annotations::new(annotations()).keep_top_by_severity(3);
+        assert_eq!(2, dropped);
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("a.rs", 3, Severity::High, Type::CodeSmell),
+                annotation("c.rs", 2, Severity::Medium, Type::Vulnerability),
+                annotation("a.rs", 5, Severity::Medium, Type::Bug),
+            ]),
+            kept
+        );
+    }
+
+    #[test]
+    fn tie_at_cutoff() {
+        // "a.rs:5" and "b.rs:1" are both medium bugs competing for the last
+        // place, which goes to the first path regardless of the input order.
+        let mut reversed = annotations();
+        reversed.reverse();
+        for annotations in [annotations(), reversed] {
+            let (kept, dropped) = Annotations::new(annotations).keep_top_by_severity(3);
+            assert_eq!(2, dropped);
+            assert!(kept
+                .annotations
+                .contains(&annotation("a.rs", 5, Severity::Medium, Type::Bug)));
+            assert!(!kept.annotations.contains(&annotation(
+                "b.rs",
+                1,
+                Severity::Medium,
+                Type::Bug
+            )));
+        }
+    }
+
+    #[test]
+    fn custom_key() {
+        let (kept, dropped) =
+            Annotations::new(annotations()).keep_top(2, |annotation| annotation.line);
+        assert_eq!(3, dropped);
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("b.rs", 1, Severity::Medium, Type::Bug),
+                annotation("c.rs", 2, Severity::Medium, Type::Vulnerability),
+            ]),
+            kept
+        );
+    }
+
+    #[test]
+    fn fewer_than_n() {
+        let (kept, dropped) = Annotations::new(annotations()).keep_top_by_severity(10);
+        assert_eq!(0, dropped);
+        assert_eq!(Annotations::new(annotations()), kept);
+    }
+}
+
+#[cfg(test)]
+mod cap_per_file {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    fn annotation(path: Option<&str>, line: u32, severity: Severity) -> Annotation {
+        let mut builder = AnnotationBuilder::new(format!("Line {line}"), severity).line(line);
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        builder.build().unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            annotation(Some("generated.rs"), 1, Severity::Low),
+            annotation(Some("generated.rs"), 2, Severity::High),
+            annotation(Some("lib.rs"), 1, Severity::Low),
+            annotation(Some("generated.rs"), 3, Severity::Low),
+            annotation(None, 1, Severity::Low),
+            annotation(Some("generated.rs"), 4, Severity::Medium),
+            annotation(None, 2, Severity::Low),
+            annotation(None, 3, Severity::Low),
+        ])
+    }
+
+    #[test]
+    fn drop() {
+        let mut annotations = annotations();
+        assert_eq!(2, annotations.cap_per_file(2, OverflowBehavior::Drop));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(Some("generated.rs"), 2, Severity::High),
+                annotation(Some("lib.rs"), 1, Severity::Low),
+                annotation(None, 1, Severity::Low),
+                annotation(Some("generated.rs"), 4, Severity::Medium),
+                annotation(None, 2, Severity::Low),
+                annotation(None, 3, Severity::Low),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn summarize() {
+        let mut annotations = annotations();
+        assert_eq!(3, annotations.cap_per_file(2, OverflowBehavior::Summarize));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(Some("generated.rs"), 2, Severity::High),
+                annotation(Some("lib.rs"), 1, Severity::Low),
+                annotation(None, 1, Severity::Low),
+                annotation(None, 2, Severity::Low),
+                annotation(None, 3, Severity::Low),
+                AnnotationBuilder::new(
+                    "3 further annotations (highest severity Medium)",
+                    Severity::Medium
+                )
+                .path("generated.rs")
+                .build()
+                .unwrap(),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn annotations_without_path_are_exempt() {
+        let mut annotations = annotations();
+        assert_eq!(3, annotations.cap_per_file(1, OverflowBehavior::Drop));
+        let without_path = annotations
+            .annotations
+            .iter()
+            .filter(|annotation| annotation.path.is_none())
+            .count();
+        assert_eq!(3, without_path);
+    }
+}
+
+#[cfg(test)]
+mod collapse_duplicates {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    fn annotation(message: &str, path: &str, line: u32, severity: Severity) -> Annotation {
+        AnnotationBuilder::new(message, severity)
+            .path(path)
+            .line(line)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn per_file() {
+        let mut annotations = Annotations::new(vec![
+            annotation("redundant clone", "a.rs", 12, Severity::Low),
+            annotation("unused import", "a.rs", 1, Severity::Low),
+            annotation("redundant clone", "a.rs", 40, Severity::Medium),
+            annotation("redundant clone", "b.rs", 3, Severity::Low),
+            annotation("redundant clone", "a.rs", 77, Severity::Low),
+        ]);
+        assert_eq!(2, annotations.collapse_duplicates(CollapseScope::PerFile));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(
+                    "redundant clone (+2 more occurrences, lines 40, 77)",
+                    "a.rs",
+                    12,
+                    Severity::Medium
+                ),
+                annotation("unused import", "a.rs", 1, Severity::Low),
+                annotation("redundant clone", "b.rs", 3, Severity::Low),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn global() {
+        let mut annotations = Annotations::new(vec![
+            annotation("redundant clone", "a.rs", 12, Severity::Low),
+            annotation("redundant clone", "b.rs", 3, Severity::High),
+            AnnotationBuilder::new("redundant clone", Severity::Low)
+                .build()
+                .unwrap(),
+        ]);
+        assert_eq!(2, annotations.collapse_duplicates(CollapseScope::Global));
+        assert_eq!(
+            Annotations::new(vec![annotation(
+                "redundant clone (+2 more occurrences, locations b.rs:3)",
+                "a.rs",
+                12,
+                Severity::High
+            )]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn fifty_duplicates() {
+        let message = "X".repeat(MESSAGE_LIMIT - 100);
+        let mut annotations = Annotations::new(
+            (1..=50)
+                .map(|line| annotation(&message, "a.rs", line * 1000, Severity::Low))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(49, annotations.collapse_duplicates(CollapseScope::PerFile));
+
+        let collapsed = &annotations.annotations[0].message;
+        assert!(collapsed.len() <= MESSAGE_LIMIT);
+        assert!(collapsed.starts_with(&message));
+        let suffix = &collapsed[message.len()..];
+        assert!(suffix.starts_with(" (+49 more occurrences, lines 2000, 3000, "));
+        assert!(suffix.ends_with(", …)"));
+    }
+
+    #[test]
+    fn long_message_is_truncated() {
+        let message = "X".repeat(MESSAGE_LIMIT);
+        let mut annotations = Annotations::new(vec![
+            annotation(&message, "a.rs", 1, Severity::Low),
+            annotation(&message, "a.rs", 2, Severity::Low),
+        ]);
+        annotations.collapse_duplicates(CollapseScope::PerFile);
+
+        let collapsed = &annotations.annotations[0].message;
+        assert_eq!(MESSAGE_LIMIT, collapsed.len());
+        assert!(collapsed.ends_with("… (+1 more occurrence)"));
+    }
+}
+
+#[cfg(test)]
+mod sample {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    fn annotation(path: &str, rule: &str, line: u32, severity: Severity) -> Annotation {
+        AnnotationBuilder::new("Message", severity)
+            .rule("clippy", rule)
+            .unwrap()
+            .path(path)
+            .line(line)
+            .build()
+            .unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            annotation("src/lib.rs", "unwrap_used", 1, Severity::Low),
+            annotation("src/lib.rs", "unwrap_used", 2, Severity::High),
+            annotation("src/lib.rs", "unwrap_used", 3, Severity::Low),
+            annotation("src/lib.rs", "needless_clone", 4, Severity::Low),
+            annotation("src/main.rs", "unwrap_used", 1, Severity::Medium),
+            annotation("src/main.rs", "todo", 2, Severity::Low),
+            annotation("src/main.rs", "todo", 3, Severity::Medium),
+        ])
+    }
+
+    fn lines(annotations: &Annotations) -> Vec<(&str, u32)> {
+        annotations
+            .annotations
+            .iter()
+            .map(|annotation| {
+                (
+                    annotation.path.as_deref().unwrap(),
+                    annotation.line.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn path_and_rule() {
+        let mut annotations = annotations();
+        let sampled = annotations.sample(1, GroupKey::PathAndRule);
+        assert_eq!(
+            vec![
+                ("src/lib.rs", 2),
+                ("src/lib.rs", 4),
+                ("src/main.rs", 1),
+                ("src/main.rs", 3)
+            ],
+            lines(&annotations)
+        );
+        assert_eq!(
+            vec![
+                SampledGroup {
+                    path: Some("src/lib.rs".to_owned()),
+                    rule: Some("clippy::unwrap_used".to_owned()),
+                    removed: 2,
+                },
+                SampledGroup {
+                    path: Some("src/main.rs".to_owned()),
+                    rule: Some("clippy::todo".to_owned()),
+                    removed: 1,
+                },
+            ],
+            sampled
+        );
+    }
+
+    #[test]
+    fn rule_prefix() {
+        let mut annotations = annotations();
+        let sampled = annotations.sample(2, GroupKey::RulePrefix);
+        assert_eq!(
+            vec![
+                ("src/lib.rs", 2),
+                ("src/lib.rs", 4),
+                ("src/main.rs", 1),
+                ("src/main.rs", 2),
+                ("src/main.rs", 3)
+            ],
+            lines(&annotations)
+        );
+        assert_eq!(1, sampled.len());
+        assert_eq!(None, sampled[0].path());
+        assert_eq!(Some("clippy::unwrap_used"), sampled[0].rule());
+        assert_eq!(2, sampled[0].removed());
+    }
+
+    #[test]
+    fn path() {
+        let mut annotations = annotations();
+        let mut reversed = annotations.clone();
+        reversed.annotations.reverse();
+
+        let sampled = annotations.sample(2, GroupKey::Path);
+        assert_eq!(
+            vec![
+                ("src/lib.rs", 1),
+                ("src/lib.rs", 2),
+                ("src/main.rs", 1),
+                ("src/main.rs", 3)
+            ],
+            lines(&annotations)
+        );
+        assert_eq!(
+            vec![2, 1],
+            sampled
+                .iter()
+                .map(SampledGroup::removed)
+                .collect::<Vec<_>>()
+        );
+
+        // The kept annotations do not depend on the original order.
+        reversed.sample(2, GroupKey::Path);
+        reversed.annotations.reverse();
+        assert_eq!(annotations, reversed);
+    }
+}
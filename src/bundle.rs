@@ -0,0 +1,202 @@
+#[cfg(feature = "json")]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotations, ANNOTATIONS_LIMIT};
+use crate::error::{Error, Result};
+#[cfg(feature = "json")]
+use crate::file::{read_json, write_json};
+use crate::report::{Report, ReportKey};
+
+/// A report together with its annotations and the key it is published under,
+/// so that they can be passed between the stages of a pipeline as one file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InsightBundle {
+    key: ReportKey,
+    report: Report,
+    annotations: Annotations,
+}
+
+/// Prefixes the name of the field in `error` with `prefix`, so that it
+/// identifies where in the bundle the error is.
+fn attribute(error: Error, prefix: &str) -> Error {
+    match error {
+        Error::FieldTooLong { name, len, limit } => Error::FieldTooLong {
+            name: format!("{prefix}.{name}"),
+            len,
+            limit,
+        },
+        Error::InvalidValue { field, reason } => Error::InvalidValue {
+            field: format!("{prefix}.{field}"),
+            reason,
+        },
+        error => error,
+    }
+}
+
+impl InsightBundle {
+    /// Constructs a bundle of `report` and its `annotations`, to be published
+    /// under `key`.
+    pub fn new(key: ReportKey, report: Report, annotations: Annotations) -> Self {
+        InsightBundle {
+            key,
+            report,
+            annotations,
+        }
+    }
+
+    /// Returns the key of the report.
+    pub fn key(&self) -> &ReportKey {
+        &self.key
+    }
+
+    /// Returns the report.
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Returns the annotations.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Splits the bundle into its key, report and annotations.
+    pub fn split(self) -> (ReportKey, Report, Annotations) {
+        (self.key, self.report, self.annotations)
+    }
+
+    /// Validates the report and the annotations.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there are more than [`ANNOTATIONS_LIMIT`]
+    /// annotations, or if any field of the report or of an annotation is
+    /// longer than Bitbucket allows. The name of the field in the error is
+    /// prefixed with where it is, e.g. `annotations[3].message`.
+    pub fn validate(&self) -> Result<()> {
+        self.report
+            .validate_fields()
+            .map_err(|error| attribute(error, "report"))?;
+
+        let len = self.annotations.annotations.len();
+        if len > ANNOTATIONS_LIMIT {
+            return Err(Error::FieldTooLong {
+                name: "annotations".to_owned(),
+                len,
+                limit: ANNOTATIONS_LIMIT,
+            });
+        }
+        for (index, annotation) in self.annotations.annotations.iter().enumerate() {
+            annotation
+                .validate_fields()
+                .map_err(|error| attribute(error, &format!("annotations[{index}]")))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a bundle from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or is not a valid bundle,
+    /// or if [`validate`](InsightBundle::validate) fails.
+    #[cfg(feature = "json")]
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<InsightBundle> {
+        let bundle = read_json::<InsightBundle>(path.as_ref())?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    /// Writes the bundle to a JSON file, replacing it atomically if it
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if [`validate`](InsightBundle::validate) fails, or if
+    /// the file cannot be written.
+    #[cfg(feature = "json")]
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.validate()?;
+        write_json(path.as_ref(), self)
+    }
+}
+
+#[cfg(test)]
+mod insight_bundle {
+    use super::*;
+    use crate::{AnnotationBuilder, ReportBuilder, ReportResult, Severity, MESSAGE_LIMIT};
+
+    fn bundle() -> InsightBundle {
+        InsightBundle::new(
+            ReportKey::new("static-analysis").unwrap(),
+            ReportBuilder::new("Static analysis")
+                .result(ReportResult::Fail)
+                .build()
+                .unwrap(),
+            Annotations::new(vec![AnnotationBuilder::new(
+                "Possible panic",
+                Severity::High,
+            )
+            .path("src/lib.rs")
+            .line(40)
+            .build()
+            .unwrap()]),
+        )
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let json = serde_json::to_value(bundle()).unwrap();
+        assert_eq!("static-analysis", json["key"]);
+        assert_eq!(bundle(), serde_json::from_value(json).unwrap());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("bundle.json");
+        bundle().to_json_file(&path).unwrap();
+        let (key, report, annotations) = InsightBundle::from_json_file(&path).unwrap().split();
+        assert_eq!("static-analysis", key.as_str());
+        assert_eq!(bundle().report, report);
+        assert_eq!(bundle().annotations, annotations);
+    }
+
+    #[test]
+    fn invalid_key() {
+        let result = serde_json::from_value::<InsightBundle>(serde_json::json!({
+            "key": "a/b",
+            "report": {"title": "Title"},
+            "annotations": {"annotations": []}
+        }));
+        assert!(result.is_err());
+        assert!(ReportKey::new("").is_err());
+    }
+
+    #[test]
+    fn nested_annotation_error() {
+        let mut bundle = bundle();
+        let mut annotation = bundle.annotations.annotations[0].clone();
+        annotation.message = "X".repeat(MESSAGE_LIMIT + 1);
+        bundle.annotations.annotations.push(annotation);
+
+        let error = bundle.validate().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::FieldTooLong { ref name, .. } if name == "annotations[1].message"
+        ));
+    }
+
+    #[test]
+    fn annotation_count() {
+        let mut bundle = bundle();
+        let annotation = bundle.annotations.annotations[0].clone();
+        bundle.annotations.annotations = vec![annotation; ANNOTATIONS_LIMIT + 1];
+        assert!(matches!(
+            bundle.validate(),
+            Err(Error::FieldTooLong { ref name, .. }) if name == "annotations"
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{Read, Write};
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+
+/// Identifies data written by [`Annotations::to_cbor`].
+const MAGIC: &[u8; 3] = b"CIA";
+
+/// The version of the cache format, which is incremented whenever the
+/// encoding of annotations changes.
+const VERSION: u8 = 1;
+
+fn invalid(message: String) -> Error {
+    Error::CacheFormat { message }
+}
+
+impl Annotations {
+    /// Writes the annotations in a compact binary format, e.g. to pass them
+    /// between the jobs of a pipeline.
+    ///
+    /// The format is a header of the bytes `CIA` and a version byte, followed
+    /// by the annotations encoded as CBOR. It is only meant for caching:
+    /// data can be read by the same version of this crate, and by later
+    /// versions as long as the version byte is unchanged. Data of any other
+    /// version is rejected by [`from_cbor`](Annotations::from_cbor).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing fails.
+    pub fn to_cbor<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer
+            .write_all(MAGIC)
+            .and_then(|()| writer.write_all(&[VERSION]))
+            .map_err(|error| invalid(error.to_string()))?;
+        ciborium::into_writer(self, writer).map_err(|error| invalid(error.to_string()))
+    }
+
+    /// Reads annotations written by [`to_cbor`](Annotations::to_cbor).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading fails, or if the data was not written by
+    /// `to_cbor`, has another version of the format or is corrupt.
+    pub fn from_cbor<R: Read>(mut reader: R) -> Result<Annotations> {
+        let mut header = [0; 4];
+        reader
+            .read_exact(&mut header)
+            .map_err(|error| invalid(format!("missing header: {error}")))?;
+        if &header[..3] != MAGIC {
+            return Err(invalid("not an annotation cache".to_owned()));
+        }
+        if header[3] != VERSION {
+            return Err(invalid(format!(
+                "unsupported version {}, expected {VERSION}",
+                header[3]
+            )));
+        }
+        ciborium::from_reader(reader).map_err(|error| invalid(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod cbor_cache {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity, Type};
+
+    fn annotations(count: u32) -> Annotations {
+        Annotations::new(
+            (0..count)
+                .map(|index| {
+                    let mut builder = AnnotationBuilder::new(
+                        format!("Finding {index} with ünïcödé"),
+                        [Severity::Low, Severity::Medium, Severity::High][index as usize % 3],
+                    )
+                    .path(format!("src/module{}.rs", index % 100));
+                    if index % 2 == 0 {
+                        builder = builder
+                            .annotation_type(Type::CodeSmell)
+                            .line(index)
+                            .link(format!("https://example.com/{index}"))
+                            .external_id(format!("id-{index}"));
+                    }
+                    builder.build().unwrap()
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let annotations = annotations(10_000);
+        let mut cbor = Vec::new();
+        annotations.to_cbor(&mut cbor).unwrap();
+        assert_eq!(annotations, Annotations::from_cbor(&cbor[..]).unwrap());
+
+        let json = serde_json::to_vec(&annotations).unwrap();
+        assert!(cbor.len() < json.len());
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let mut cbor = Vec::new();
+        annotations(3).to_cbor(&mut cbor).unwrap();
+        cbor[3] = VERSION + 1;
+        let error = Annotations::from_cbor(&cbor[..]).unwrap_err();
+        assert!(matches!(error, Error::CacheFormat { .. }));
+        assert!(
+            error.to_string().contains("unsupported version 2"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn invalid_data() {
+        let json = serde_json::to_vec(&annotations(3)).unwrap();
+        assert!(Annotations::from_cbor(&json[..]).is_err());
+        assert!(Annotations::from_cbor(&b"CI"[..]).is_err());
+
+        let mut cbor = Vec::new();
+        annotations(3).to_cbor(&mut cbor).unwrap();
+        cbor.truncate(cbor.len() - 5);
+        assert!(Annotations::from_cbor(&cbor[..]).is_err());
+    }
+}
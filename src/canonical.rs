@@ -0,0 +1,233 @@
+use serde_json::{Number, Value};
+
+use crate::annotation::Annotations;
+use crate::bundle::InsightBundle;
+use crate::error::Result;
+use crate::report::Report;
+
+/// The largest integer that every `f64` up to it represents exactly.
+const EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+fn canonical_number(number: &Number, out: &mut String) {
+    match number.as_f64() {
+        Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() <= EXACT_INTEGER => {
+            // The range check makes the conversion exact.
+            out.push_str(&(float as i64).to_string());
+        }
+        _ => out.push_str(&number.to_string()),
+    }
+}
+
+/// Writes `value` as canonical JSON: without whitespace, with the keys of
+/// objects sorted by their UTF-8 bytes, and with integral numbers written as
+/// integers.
+pub(crate) fn canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => canonical_number(number, out),
+        Value::String(string) => out.push_str(&Value::String(string.clone()).to_string()),
+        Value::Array(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                canonical(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(left, _), (right, _)| left.as_bytes().cmp(right.as_bytes()));
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                canonical(value, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Sorts the elements of the array at the JSON pointer `pointer` of `value`
+/// by their canonical serialization.
+pub(crate) fn sort_array(value: &mut Value, pointer: &str) {
+    if let Some(Value::Array(values)) = value.pointer_mut(pointer) {
+        let mut sorted = values
+            .drain(..)
+            .map(|value| {
+                let mut out = String::new();
+                canonical(&value, &mut out);
+                (out, value)
+            })
+            .collect::<Vec<_>>();
+        sorted.sort_by(|(left, _), (right, _)| left.cmp(right));
+        values.extend(sorted.into_iter().map(|(_, value)| value));
+    }
+}
+
+fn to_canonical_json(mut value: Value, sorted: Option<&str>) -> String {
+    if let Some(pointer) = sorted {
+        sort_array(&mut value, pointer);
+    }
+    let mut out = String::new();
+    canonical(&value, &mut out);
+    out
+}
+
+impl Report {
+    /// Serializes the report as canonical JSON, so that equal reports are
+    /// serialized to the same bytes, e.g. for caching or signing.
+    ///
+    /// Canonical JSON has no whitespace, the keys of objects are sorted by
+    /// their UTF-8 bytes, and numbers with an integral value are written as
+    /// integers. The data fields keep their order, since Bitbucket displays
+    /// them in that order. Bitbucket accepts any JSON, this is only for
+    /// tooling that compares payloads.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the fields are longer than Bitbucket
+    /// allows.
+    pub fn canonical_json(&self) -> Result<String> {
+        self.validate_fields()?;
+        Ok(to_canonical_json(serde_json::to_value(self)?, None))
+    }
+}
+
+impl Annotations {
+    /// Serializes the annotations as canonical JSON, like
+    /// [`Report::canonical_json`], with the annotations sorted by their
+    /// canonical serialization.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fields of any annotation are longer than
+    /// Bitbucket allows.
+    pub fn canonical_json(&self) -> Result<String> {
+        for annotation in &self.annotations {
+            annotation.validate_fields()?;
+        }
+        Ok(to_canonical_json(
+            serde_json::to_value(self)?,
+            Some("/annotations"),
+        ))
+    }
+}
+
+impl InsightBundle {
+    /// Serializes the bundle as canonical JSON, like
+    /// [`Report::canonical_json`], with the annotations sorted by their
+    /// canonical serialization.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if [`validate`](InsightBundle::validate) fails.
+    pub fn canonical_json(&self) -> Result<String> {
+        self.validate()?;
+        Ok(to_canonical_json(
+            serde_json::to_value(self)?,
+            Some("/annotations/annotations"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod canonical_json {
+    use super::*;
+    use crate::{AnnotationBuilder, ReportBuilder, ReportKey, ReportResult, Severity, Type};
+
+    fn annotations() -> Vec<crate::Annotation> {
+        vec![
+            AnnotationBuilder::new("Possible panic", Severity::High)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .line(40)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Unused import", Severity::Low)
+                .path("src/main.rs")
+                .extension("zeta", 1.0)
+                .unwrap()
+                .extension("alpha", true)
+                .unwrap()
+                .build()
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn construction_order() {
+        let mut reversed = annotations();
+        reversed.reverse();
+        let json = Annotations::new(annotations()).canonical_json().unwrap();
+        assert_eq!(json, Annotations::new(reversed).canonical_json().unwrap());
+        assert!(json.contains(r#"{"alpha":true,"message":"Unused import""#));
+
+        let first = ReportBuilder::new("Static analysis")
+            .result(ReportResult::Pass)
+            .extension("b", 2)
+            .unwrap()
+            .extension("a", 1)
+            .unwrap()
+            .reporter("clippy")
+            .build()
+            .unwrap();
+        let mut second = ReportBuilder::new("Static analysis");
+        second
+            .set_reporter("clippy")
+            .set_extension("a", 1.0)
+            .unwrap()
+            .set_extension("b", 2)
+            .unwrap()
+            .set_result(ReportResult::Pass);
+        let second = second.build().unwrap();
+        assert_eq!(
+            r#"{"a":1,"b":2,"reporter":"clippy","result":"PASS","title":"Static analysis"}"#,
+            first.canonical_json().unwrap()
+        );
+        assert_eq!(
+            first.canonical_json().unwrap(),
+            second.canonical_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn bundle() {
+        let bundle = |annotations| {
+            InsightBundle::new(
+                ReportKey::new("lint").unwrap(),
+                ReportBuilder::new("Lint").build().unwrap(),
+                Annotations::new(annotations),
+            )
+        };
+        let mut reversed = annotations();
+        reversed.reverse();
+        assert_eq!(
+            bundle(annotations()).canonical_json().unwrap(),
+            bundle(reversed).canonical_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn canonical_form() {
+        let mut out = String::new();
+        canonical(
+            &serde_json::json!({"b": [1.5, 2.0, null], "a": "\"x\""}),
+            &mut out,
+        );
+        assert_eq!(r#"{"a":"\"x\"","b":[1.5,2,null]}"#, out);
+    }
+
+    #[test]
+    fn validation() {
+        let mut report = ReportBuilder::new("Title").build().unwrap();
+        report.title = "X".repeat(crate::TITLE_LIMIT + 1);
+        assert!(report.canonical_json().is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use crate::report::{Data, Parameter};
+
+/// Describes the CI build that produced a report, to be shown as data fields.
+///
+/// The detection helpers read variables through `env`, which is usually
+/// `&|name| std::env::var(name).ok()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiInfo {
+    build_url: String,
+    build_number: u64,
+    duration: Option<Duration>,
+}
+
+impl CiInfo {
+    /// Constructs the information of a build.
+    pub fn new<T: Into<String>>(
+        build_url: T,
+        build_number: u64,
+        duration: Option<Duration>,
+    ) -> Self {
+        CiInfo {
+            build_url: build_url.into(),
+            build_number,
+            duration,
+        }
+    }
+
+    /// Detects a Jenkins build from `BUILD_URL` and `BUILD_NUMBER`.
+    ///
+    /// Returns `None` if either variable is missing or the build number is
+    /// not a number.
+    pub fn from_jenkins_env(env: &dyn Fn(&str) -> Option<String>) -> Option<CiInfo> {
+        let build_url = env("BUILD_URL")?;
+        let build_number = env("BUILD_NUMBER")?.parse().ok()?;
+        Some(CiInfo::new(build_url, build_number, None))
+    }
+
+    /// Detects a Bamboo build from `bamboo_buildResultsUrl` and
+    /// `bamboo_buildNumber`.
+    ///
+    /// Returns `None` if either variable is missing or the build number is
+    /// not a number.
+    pub fn from_bamboo_env(env: &dyn Fn(&str) -> Option<String>) -> Option<CiInfo> {
+        let build_url = env("bamboo_buildResultsUrl")?;
+        let build_number = env("bamboo_buildNumber")?.parse().ok()?;
+        Some(CiInfo::new(build_url, build_number, None))
+    }
+
+    /// Detects a GitHub Actions workflow run from `GITHUB_SERVER_URL`,
+    /// `GITHUB_REPOSITORY`, `GITHUB_RUN_ID` and `GITHUB_RUN_NUMBER`.
+    ///
+    /// Returns `None` if any variable is missing or the run number is not a
+    /// number.
+    pub fn from_github_actions_env(env: &dyn Fn(&str) -> Option<String>) -> Option<CiInfo> {
+        let server = env("GITHUB_SERVER_URL")?;
+        let repository = env("GITHUB_REPOSITORY")?;
+        let run_id = env("GITHUB_RUN_ID")?;
+        let build_number = env("GITHUB_RUN_NUMBER")?.parse().ok()?;
+        let build_url = format!(
+            "{}/{repository}/actions/runs/{run_id}",
+            server.trim_end_matches('/')
+        );
+        Some(CiInfo::new(build_url, build_number, None))
+    }
+
+    /// Sets the duration of the pipeline.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Returns the URL of the build.
+    pub fn build_url(&self) -> &str {
+        &self.build_url
+    }
+
+    /// Returns the build number.
+    pub fn build_number(&self) -> u64 {
+        self.build_number
+    }
+
+    /// Returns the duration of the pipeline, if known.
+    pub fn pipeline_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns the data fields "Build", linking to the build, "Build number"
+    /// and, if the duration is known, "Pipeline duration".
+    pub fn to_data(&self) -> Vec<Data> {
+        let mut data = vec![
+            Data {
+                title: "Build".to_owned(),
+                parameter: Parameter::Link {
+                    linktext: format!("#{}", self.build_number),
+                    href: self.build_url.clone(),
+                },
+            },
+            Data {
+                title: "Build number".to_owned(),
+                parameter: Parameter::Number(self.build_number.into()),
+            },
+        ];
+        if let Some(duration) = self.duration {
+            data.push(Data {
+                title: "Pipeline duration".to_owned(),
+                parameter: Parameter::Duration(
+                    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                ),
+            });
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod ci_detection {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(variables: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let variables = variables
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+            .collect::<HashMap<_, _>>();
+        move |name| variables.get(name).cloned()
+    }
+
+    #[test]
+    fn jenkins() {
+        let env = env(&[
+            ("BUILD_URL", "https://jenkins.example.com/job/app/42/"),
+            ("BUILD_NUMBER", "42"),
+        ]);
+        assert_eq!(
+            Some(CiInfo::new(
+                "https://jenkins.example.com/job/app/42/",
+                42,
+                None
+            )),
+            CiInfo::from_jenkins_env(&env)
+        );
+        assert_eq!(None, CiInfo::from_bamboo_env(&env));
+        assert_eq!(None, CiInfo::from_github_actions_env(&env));
+    }
+
+    #[test]
+    fn bamboo() {
+        let env = env(&[
+            (
+                "bamboo_buildResultsUrl",
+                "https://bamboo.example.com/browse/APP-BUILD-7",
+            ),
+            ("bamboo_buildNumber", "7"),
+        ]);
+        let info = CiInfo::from_bamboo_env(&env).unwrap();
+        assert_eq!(
+            "https://bamboo.example.com/browse/APP-BUILD-7",
+            info.build_url()
+        );
+        assert_eq!(7, info.build_number());
+    }
+
+    #[test]
+    fn github_actions() {
+        let env = env(&[
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "octo/app"),
+            ("GITHUB_RUN_ID", "1658821493"),
+            ("GITHUB_RUN_NUMBER", "12"),
+        ]);
+        let info = CiInfo::from_github_actions_env(&env).unwrap();
+        assert_eq!(
+            "https://github.com/octo/app/actions/runs/1658821493",
+            info.build_url()
+        );
+        assert_eq!(12, info.build_number());
+    }
+
+    #[test]
+    fn missing_or_invalid() {
+        assert_eq!(None, CiInfo::from_jenkins_env(&env(&[])));
+        let env = env(&[("BUILD_URL", "https://jenkins"), ("BUILD_NUMBER", "latest")]);
+        assert_eq!(None, CiInfo::from_jenkins_env(&env));
+    }
+
+    #[test]
+    fn data() {
+        let info = CiInfo::new("https://ci.example.com/42", 42, None);
+        assert_eq!(2, info.to_data().len());
+
+        let data = info.duration(Duration::from_secs(90)).to_data();
+        assert_eq!(
+            vec![
+                Data {
+                    title: "Build".to_owned(),
+                    parameter: Parameter::Link {
+                        linktext: "#42".to_owned(),
+                        href: "https://ci.example.com/42".to_owned(),
+                    },
+                },
+                Data {
+                    title: "Build number".to_owned(),
+                    parameter: Parameter::Number(42.into()),
+                },
+                Data {
+                    title: "Pipeline duration".to_owned(),
+                    parameter: Parameter::Duration(90_000),
+                },
+            ],
+            data
+        );
+    }
+}
@@ -0,0 +1,1094 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::annotation::{Annotation, Annotations, Severity};
+#[cfg(feature = "diff")]
+use crate::diff::ChangedLines;
+use crate::error::{Error, Result};
+use crate::paths::{path_to_string, relativize};
+#[cfg(feature = "diff")]
+use crate::report::ReportResult;
+use crate::report::{Data, Parameter};
+
+/// Line coverage of a single source file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileCoverage {
+    covered: BTreeSet<u32>,
+    uncovered: BTreeSet<u32>,
+    reported: Option<f64>,
+}
+
+impl FileCoverage {
+    /// Returns the executable lines that were run by the tests.
+    pub fn covered_lines(&self) -> &BTreeSet<u32> {
+        &self.covered
+    }
+
+    /// Returns the executable lines that were not run by the tests.
+    pub fn uncovered_lines(&self) -> &BTreeSet<u32> {
+        &self.uncovered
+    }
+
+    /// Returns the percentage of executable lines that were covered.
+    ///
+    /// If the coverage tool reported a percentage for the file it is used as
+    /// is. Otherwise it is computed from the lines, and a file without
+    /// executable lines is considered fully covered.
+    pub fn percentage(&self) -> f64 {
+        self.reported
+            .unwrap_or_else(|| percentage(self.covered.len() as u64, self.uncovered.len() as u64))
+    }
+
+    /// Returns the uncovered lines collapsed into ranges.
+    ///
+    /// Two uncovered lines belong to the same range unless a covered line lies
+    /// between them, so non-executable lines such as blank lines and comments
+    /// do not split a range.
+    pub fn uncovered_ranges(&self) -> Vec<RangeInclusive<u32>> {
+        let mut ranges = Vec::new();
+        let mut lines = self.uncovered.iter().copied();
+
+        if let Some(first) = lines.next() {
+            let (mut start, mut end) = (first, first);
+            for line in lines {
+                if self.covered.range(end + 1..line).next().is_some() {
+                    ranges.push(start..=end);
+                    start = line;
+                }
+                end = line;
+            }
+            ranges.push(start..=end);
+        }
+        ranges
+    }
+
+    fn record(&mut self, line: u32, covered: bool) {
+        if covered {
+            self.uncovered.remove(&line);
+            self.covered.insert(line);
+        } else if !self.covered.contains(&line) {
+            self.uncovered.insert(line);
+        }
+    }
+}
+
+/// The result of converting a coverage report produced by a test tool.
+///
+/// It holds the overall line coverage as well as the covered and uncovered
+/// lines of every file, keyed by their path relative to the repository root.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageInsight {
+    percentage: f64,
+    files: BTreeMap<String, FileCoverage>,
+    skipped: usize,
+}
+
+impl CoverageInsight {
+    /// Returns the overall line coverage as a percentage between 0 and 100.
+    pub fn percentage(&self) -> f64 {
+        self.percentage
+    }
+
+    /// Returns the line coverage of every file, keyed by path.
+    pub fn files(&self) -> &BTreeMap<String, FileCoverage> {
+        &self.files
+    }
+
+    /// Returns the line coverage of the file at `path`, if it was part of the
+    /// coverage report.
+    pub fn file(&self, path: &str) -> Option<&FileCoverage> {
+        self.files.get(path)
+    }
+
+    /// Returns the number of entries in the coverage report that were skipped,
+    /// e.g. generated classes without a source file.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Constructs a "Line coverage" data field for the report.
+    ///
+    /// The percentage is rounded down, so that a report with uncovered lines
+    /// never claims 100% coverage.
+    pub fn data(&self) -> Data {
+        Data {
+            title: "Line coverage".to_owned(),
+            parameter: Parameter::Percentage(self.percentage.floor() as u8),
+        }
+    }
+
+    /// Constructs one "Coverage" data field per package, i.e. per directory
+    /// containing covered files, ordered by the package path.
+    ///
+    /// The coverage of a package is the average of the coverage of its files,
+    /// weighted by their number of executable lines. Callers are responsible
+    /// for picking at most [`DATA_LIMIT`] fields in total for the report.
+    ///
+    /// [`DATA_LIMIT`]: crate::DATA_LIMIT
+    pub fn package_data(&self) -> Vec<Data> {
+        let mut packages = BTreeMap::<&str, (f64, u64)>::new();
+        for (path, file) in &self.files {
+            let package = path.rsplit_once('/').map_or(".", |(package, _)| package);
+            let lines = (file.covered.len() + file.uncovered.len()) as u64;
+            let (weighted, total) = packages.entry(package).or_default();
+            *weighted += file.percentage() * lines as f64;
+            *total += lines;
+        }
+
+        packages
+            .into_iter()
+            .map(|(package, (weighted, total))| {
+                let percentage = if total == 0 {
+                    100.0
+                } else {
+                    weighted / total as f64
+                };
+                Data {
+                    title: format!("{package} coverage"),
+                    parameter: Parameter::Percentage(percentage.floor() as u8),
+                }
+            })
+            .collect()
+    }
+
+    /// Constructs one annotation per range of uncovered lines.
+    pub fn annotations(&self) -> Annotations {
+        let annotations = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.uncovered_ranges()
+                    .into_iter()
+                    .map(move |range| uncovered_annotation(path, range))
+            })
+            .collect::<Vec<_>>();
+        Annotations::new(annotations)
+    }
+}
+
+fn percentage(covered: u64, missed: u64) -> f64 {
+    let total = covered + missed;
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+fn uncovered_annotation(path: &str, range: RangeInclusive<u32>) -> Annotation {
+    let (start, end) = range.into_inner();
+    let message = if start == end {
+        format!("Line {start} is not covered by tests")
+    } else {
+        format!("Lines {start}-{end} are not covered by tests")
+    };
+
+    Annotation {
+        message,
+        severity: Severity::Low,
+        annotation_type: None,
+        path: Some(path.to_owned()),
+        line: Some(start),
+        link: None,
+        external_id: None,
+        #[cfg(feature = "json")]
+        extensions: BTreeMap::new(),
+    }
+}
+
+#[cfg(feature = "diff")]
+impl CoverageInsight {
+    /// Constructs one annotation per range of uncovered lines that were
+    /// added or modified by the change.
+    ///
+    /// Uncovered lines that were not changed are ignored, and they split
+    /// ranges like covered lines do.
+    pub fn uncovered_changed(&self, changed: &ChangedLines) -> Annotations {
+        let annotations = self
+            .changed_files(changed)
+            .flat_map(|(path, file, lines)| {
+                let (uncovered, unchanged): (BTreeSet<u32>, BTreeSet<u32>) =
+                    file.uncovered.iter().partition(|line| lines.contains(line));
+                let changed_file = FileCoverage {
+                    covered: file.covered.union(&unchanged).copied().collect(),
+                    uncovered,
+                    reported: None,
+                };
+                changed_file
+                    .uncovered_ranges()
+                    .into_iter()
+                    .map(move |range| uncovered_annotation(path, range))
+            })
+            .collect::<Vec<_>>();
+        Annotations::new(annotations)
+    }
+
+    /// Computes the coverage of the lines added or modified by the change.
+    ///
+    /// Only changed lines that are executable according to the coverage
+    /// report are taken into account, so changed blank lines, comments and
+    /// files without coverage data do not count against it.
+    pub fn diff_coverage(&self, changed: &ChangedLines) -> DiffCoverage {
+        let mut diff_coverage = DiffCoverage::default();
+        for (_, file, lines) in self.changed_files(changed) {
+            diff_coverage.covered += file.covered.intersection(lines).count() as u64;
+            diff_coverage.uncovered += file.uncovered.intersection(lines).count() as u64;
+        }
+        diff_coverage
+    }
+
+    fn changed_files<'a>(
+        &'a self,
+        changed: &'a ChangedLines,
+    ) -> impl Iterator<Item = (&'a str, &'a FileCoverage, &'a BTreeSet<u32>)> {
+        changed.files().filter_map(|path| {
+            let file = self.files.get(path)?;
+            Some((path, file, changed.lines(path)?))
+        })
+    }
+}
+
+/// Line coverage of the executable lines added or modified by a change.
+#[cfg(feature = "diff")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiffCoverage {
+    covered: u64,
+    uncovered: u64,
+}
+
+#[cfg(feature = "diff")]
+impl DiffCoverage {
+    /// Returns the number of changed lines that were run by the tests.
+    pub fn covered(&self) -> u64 {
+        self.covered
+    }
+
+    /// Returns the number of changed executable lines that were not run by
+    /// the tests.
+    pub fn uncovered(&self) -> u64 {
+        self.uncovered
+    }
+
+    /// Returns the percentage of changed executable lines that were covered.
+    /// A change without executable lines is considered fully covered.
+    pub fn percentage(&self) -> f64 {
+        percentage(self.covered, self.uncovered)
+    }
+
+    /// Constructs a "Diff coverage" data field for the report, rounded down
+    /// like [`CoverageInsight::data`].
+    pub fn data(&self) -> Data {
+        Data {
+            title: "Diff coverage".to_owned(),
+            parameter: Parameter::Percentage(self.percentage().floor() as u8),
+        }
+    }
+
+    /// Returns [`ReportResult::Pass`] if the diff coverage is at least
+    /// `threshold` percent, and [`ReportResult::Fail`] otherwise.
+    pub fn result(&self, threshold: f64) -> ReportResult {
+        if self.percentage() >= threshold {
+            ReportResult::Pass
+        } else {
+            ReportResult::Fail
+        }
+    }
+}
+
+/// Source directories used to map the package-relative file names reported
+/// by JVM tools to paths relative to the repository root.
+#[derive(Debug, Clone, Default)]
+pub struct SourceRoots {
+    base: PathBuf,
+    roots: Vec<String>,
+}
+
+impl SourceRoots {
+    /// Constructs an empty list of source roots for the repository checked out
+    /// at `base`.
+    pub fn new<P: Into<PathBuf>>(base: P) -> Self {
+        SourceRoots {
+            base: base.into(),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Adds a source root such as `src/main/java`, relative to the repository
+    /// root.
+    pub fn root<T: Into<String>>(mut self, root: T) -> Self {
+        self.roots
+            .push(root.into().trim_end_matches('/').to_owned());
+        self
+    }
+
+    /// Adds a source root from a `Path`, turning its separators into forward
+    /// slashes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `root` is not valid UTF-8 or is absolute.
+    pub fn root_from<P: AsRef<Path>>(self, root: P) -> Result<Self> {
+        Ok(self.root(path_to_string(root.as_ref(), false)?))
+    }
+
+    /// Maps a path relative to a source root to a path relative to the
+    /// repository root.
+    ///
+    /// The first source root under which the file exists is used. If the file
+    /// does not exist under any of them the first root is used, and without
+    /// any roots the path is returned as is.
+    pub fn resolve(&self, path: &str) -> String {
+        let candidates = self
+            .roots
+            .iter()
+            .map(|root| format!("{root}/{path}"))
+            .collect::<Vec<_>>();
+
+        candidates
+            .iter()
+            .find(|candidate| self.base.join(candidate).is_file())
+            .or_else(|| candidates.first())
+            .cloned()
+            .unwrap_or_else(|| path.to_owned())
+    }
+}
+
+/// Converts a JaCoCo XML coverage report.
+///
+/// The overall line coverage is computed from the `LINE` counters of every
+/// class, and the uncovered lines of each source file are taken from its
+/// `<line>` entries that have missed but no covered instructions. Classes
+/// without a source file, which are typically generated, are skipped and
+/// counted in [`CoverageInsight::skipped`].
+///
+/// # Errors
+///
+/// Will return `Err` if the report is not well-formed XML or has malformed
+/// attributes.
+pub fn from_jacoco_xml(reader: impl BufRead, roots: &SourceRoots) -> Result<CoverageInsight> {
+    let mut reader = Reader::from_reader(reader);
+    let mut parser = JacocoParser::default();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf).map_err(jacoco_error)? {
+            Event::Start(element) => {
+                parser.element(&element, roots)?;
+                parser.stack.push(element.name().as_ref().to_owned());
+            }
+            Event::Empty(element) => {
+                parser.element(&element, roots)?;
+                // An empty class has no counters and no end tag to stop
+                // skipping at.
+                if element.name().as_ref() == "class" {
+                    parser.skipping = false;
+                }
+            }
+            Event::End(element) => {
+                parser.stack.pop();
+                match element.name().as_ref() {
+                    "class" => parser.skipping = false,
+                    "sourcefile" => parser.source = None,
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(CoverageInsight {
+        percentage: percentage(parser.covered, parser.missed),
+        files: parser.files,
+        skipped: parser.skipped,
+    })
+}
+
+#[derive(Default)]
+struct JacocoParser {
+    stack: Vec<String>,
+    package: String,
+    source: Option<String>,
+    skipping: bool,
+    covered: u64,
+    missed: u64,
+    skipped: usize,
+    files: BTreeMap<String, FileCoverage>,
+}
+
+impl JacocoParser {
+    fn element(&mut self, element: &BytesStart, roots: &SourceRoots) -> Result<()> {
+        let parent = self.stack.last().map(String::as_str);
+
+        match element.name().as_ref() {
+            "package" => self.package = attribute(element, "name")?.unwrap_or_default(),
+            "class" if attribute(element, "sourcefilename")?.is_none() => {
+                self.skipping = true;
+                self.skipped += 1;
+            }
+            "counter"
+                if parent == Some("class")
+                    && !self.skipping
+                    && attribute(element, "type")?.as_deref() == Some("LINE") =>
+            {
+                self.covered += numeric_attribute(element, "covered")?;
+                self.missed += numeric_attribute(element, "missed")?;
+            }
+            "sourcefile" => {
+                let name = attribute(element, "name")?
+                    .ok_or_else(|| jacoco_error("sourcefile without a name"))?;
+                let relative = if self.package.is_empty() {
+                    name
+                } else {
+                    format!("{}/{name}", self.package)
+                };
+                let path = roots.resolve(&relative);
+                self.files.entry(path.clone()).or_default();
+                self.source = Some(path);
+            }
+            "line" => {
+                if let Some(file) = self.source.as_ref().and_then(|s| self.files.get_mut(s)) {
+                    let line = numeric_attribute(element, "nr")?;
+                    let line = u32::try_from(line).map_err(jacoco_error)?;
+                    let missed = numeric_attribute(element, "mi")?;
+                    let covered = numeric_attribute(element, "ci")?;
+                    if covered > 0 || missed > 0 {
+                        file.record(line, covered > 0);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    match element.try_get_attribute(name).map_err(jacoco_error)? {
+        Some(attribute) => attribute
+            .normalized_value(XmlVersion::Implicit1_0)
+            .map(|value| Some(value.into_owned()))
+            .map_err(jacoco_error),
+        None => Ok(None),
+    }
+}
+
+fn numeric_attribute(element: &BytesStart, name: &str) -> Result<u64> {
+    attribute(element, name)?
+        .unwrap_or_else(|| "0".to_owned())
+        .parse()
+        .map_err(|_| jacoco_error(format!("attribute '{name}' is not a number")))
+}
+
+fn jacoco_error<E: ToString>(error: E) -> Error {
+    Error::parse("JaCoCo", error.to_string())
+}
+
+/// Converts an Istanbul (nyc) `coverage-final.json` report.
+///
+/// A line is executable if a statement starts on it, and covered if any of
+/// the statements starting on it was hit. Uncovered lines are thus the lines
+/// where every statement has zero hits. The absolute paths used as keys are
+/// made relative to `root`; paths outside of `root` are kept as they are.
+///
+/// By convention a file without any statements counts as 100% covered, and
+/// it does not affect the overall coverage.
+///
+/// # Errors
+///
+/// Will return `Err` if `value` does not have the structure of an Istanbul
+/// coverage report.
+pub fn from_istanbul_json(value: &Value, root: &Path) -> Result<CoverageInsight> {
+    let report = BTreeMap::<String, IstanbulFile>::deserialize(value).map_err(istanbul_error)?;
+    let mut files = BTreeMap::new();
+
+    for (key, file) in report {
+        let mut coverage = FileCoverage::default();
+        for (id, location) in file.statement_map {
+            let hits = file
+                .s
+                .get(&id)
+                .ok_or_else(|| istanbul_error(format!("no hit count for statement {id}")))?;
+            coverage.record(location.start.line, *hits > 0);
+        }
+        files.insert(relativize(&key, root), coverage);
+    }
+
+    let (covered, missed) = files.values().fold((0, 0), |(covered, missed), file| {
+        (
+            covered + file.covered.len() as u64,
+            missed + file.uncovered.len() as u64,
+        )
+    });
+
+    Ok(CoverageInsight {
+        percentage: percentage(covered, missed),
+        files,
+        skipped: 0,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IstanbulFile {
+    statement_map: BTreeMap<String, IstanbulLocation>,
+    s: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+struct IstanbulLocation {
+    start: IstanbulPosition,
+}
+
+#[derive(Deserialize)]
+struct IstanbulPosition {
+    line: u32,
+}
+
+fn istanbul_error<E: ToString>(error: E) -> Error {
+    Error::parse("Istanbul", error.to_string())
+}
+
+/// Converts a coverage.py JSON report, as produced by `coverage json`.
+///
+/// The overall coverage is taken from `totals.percent_covered` and the
+/// coverage of each file from its `summary.percent_covered`. The
+/// `missing_lines` of each file are its uncovered lines, while
+/// `excluded_lines` are not executable and never count as uncovered.
+///
+/// The paths in the report are relative to the directory `coverage` was run
+/// in, which normally is the repository root. If `root` is given, absolute
+/// paths below it are made relative to it.
+///
+/// # Errors
+///
+/// Will return `Err` if `value` does not have the structure of a coverage.py
+/// JSON report.
+pub fn from_coverage_py_json(value: &Value, root: Option<&Path>) -> Result<CoverageInsight> {
+    let report = CoveragePyReport::deserialize(value).map_err(coverage_py_error)?;
+    let mut files = BTreeMap::new();
+
+    for (path, file) in report.files {
+        let excluded = file.excluded_lines.into_iter().collect::<BTreeSet<_>>();
+        let mut coverage = FileCoverage {
+            reported: Some(file.summary.percent_covered),
+            ..FileCoverage::default()
+        };
+        for line in file.executed_lines {
+            coverage.record(line, true);
+        }
+        for line in file.missing_lines {
+            if !excluded.contains(&line) {
+                coverage.record(line, false);
+            }
+        }
+
+        let path = match root {
+            Some(root) => relativize(&path, root),
+            None => path,
+        };
+        files.insert(path, coverage);
+    }
+
+    Ok(CoverageInsight {
+        percentage: report.totals.percent_covered,
+        files,
+        skipped: 0,
+    })
+}
+
+#[derive(Deserialize)]
+struct CoveragePyReport {
+    files: BTreeMap<String, CoveragePyFile>,
+    totals: CoveragePySummary,
+}
+
+#[derive(Deserialize)]
+struct CoveragePyFile {
+    executed_lines: Vec<u32>,
+    missing_lines: Vec<u32>,
+    #[serde(default)]
+    excluded_lines: Vec<u32>,
+    summary: CoveragePySummary,
+}
+
+#[derive(Deserialize)]
+struct CoveragePySummary {
+    percent_covered: f64,
+}
+
+fn coverage_py_error<E: ToString>(error: E) -> Error {
+    Error::parse("coverage.py", error.to_string())
+}
+
+#[cfg(test)]
+mod jacoco {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<!DOCTYPE report PUBLIC "-//JACOCO//DTD Report 1.1//EN" "report.dtd">
+<report name="example">
+  <sessioninfo id="host-1" start="1582841968000" dump="1582841969000"/>
+  <package name="com/example">
+    <class name="com/example/App" sourcefilename="App.java">
+      <method name="main" desc="([Ljava/lang/String;)V" line="5">
+        <counter type="INSTRUCTION" missed="4" covered="6"/>
+        <counter type="LINE" missed="2" covered="3"/>
+      </method>
+      <counter type="INSTRUCTION" missed="4" covered="6"/>
+      <counter type="LINE" missed="2" covered="3"/>
+    </class>
+    <class name="com/example/Util" sourcefilename="Util.kt">
+      <counter type="LINE" missed="1" covered="1"/>
+    </class>
+    <class name="com/example/App_Generated">
+      <counter type="LINE" missed="10" covered="0"/>
+    </class>
+    <sourcefile name="App.java">
+      <line nr="5" mi="0" ci="3" mb="0" cb="0"/>
+      <line nr="6" mi="0" ci="2" mb="0" cb="0"/>
+      <line nr="7" mi="2" ci="0" mb="0" cb="0"/>
+      <line nr="9" mi="1" ci="0" mb="0" cb="0"/>
+      <line nr="10" mi="0" ci="1" mb="0" cb="0"/>
+      <counter type="LINE" missed="2" covered="3"/>
+    </sourcefile>
+    <sourcefile name="Util.kt">
+      <line nr="3" mi="0" ci="2" mb="0" cb="0"/>
+      <line nr="4" mi="3" ci="0" mb="0" cb="0"/>
+      <counter type="LINE" missed="1" covered="1"/>
+    </sourcefile>
+    <counter type="LINE" missed="13" covered="4"/>
+  </package>
+  <counter type="LINE" missed="13" covered="4"/>
+</report>"#;
+
+    fn convert() -> CoverageInsight {
+        let repository = tempfile::tempdir().unwrap();
+        for file in [
+            "src/main/java/com/example/App.java",
+            "src/main/kotlin/com/example/Util.kt",
+        ] {
+            let path = repository.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let roots = SourceRoots::new(repository.path())
+            .root("src/main/java")
+            .root("src/main/kotlin/");
+
+        from_jacoco_xml(REPORT.as_bytes(), &roots).unwrap()
+    }
+
+    #[test]
+    fn line_coverage() {
+        let insight = convert();
+        assert_eq!(4.0 * 100.0 / 7.0, insight.percentage());
+        assert_eq!(Parameter::Percentage(57), insight.data().parameter);
+    }
+
+    #[test]
+    fn generated_classes_are_skipped() {
+        assert_eq!(1, convert().skipped());
+    }
+
+    #[test]
+    fn source_roots() {
+        let insight = convert();
+        let paths = insight.files().keys().collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "src/main/java/com/example/App.java",
+                "src/main/kotlin/com/example/Util.kt"
+            ],
+            paths
+        );
+    }
+
+    #[test]
+    fn missing_source_file_uses_first_root() {
+        let roots = SourceRoots::new("/nonexistent").root("src/main/java");
+        assert_eq!("src/main/java/a/B.java", roots.resolve("a/B.java"));
+        assert_eq!("a/B.java", SourceRoots::default().resolve("a/B.java"));
+    }
+
+    #[test]
+    fn source_roots_from_paths() {
+        let roots = SourceRoots::new("/nonexistent")
+            .root_from(Path::new("src").join("main").join("java"))
+            .unwrap();
+        assert_eq!("src/main/java/a/B.java", roots.resolve("a/B.java"));
+        assert!(SourceRoots::default().root_from("/src/main/java").is_err());
+    }
+
+    #[test]
+    fn uncovered_annotations() {
+        let expected = json!({"annotations": [
+            {
+                "message": "Lines 7-9 are not covered by tests",
+                "severity": "LOW",
+                "path": "src/main/java/com/example/App.java",
+                "line": 7
+            },
+            {
+                "message": "Line 4 is not covered by tests",
+                "severity": "LOW",
+                "path": "src/main/kotlin/com/example/Util.kt",
+                "line": 4
+            }
+        ]});
+        let actual = serde_json::to_value(convert().annotations()).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn empty_generated_class() {
+        let report = r#"<report>
+  <package name="p">
+    <class name="p/Gen"/>
+    <class name="p/App" sourcefilename="App.java">
+      <counter type="LINE" missed="5" covered="5"/>
+    </class>
+  </package>
+</report>"#;
+        let insight = from_jacoco_xml(report.as_bytes(), &SourceRoots::default()).unwrap();
+        assert_eq!(50.0, insight.percentage());
+        assert_eq!(1, insight.skipped());
+    }
+
+    #[test]
+    fn malformed_report() {
+        let report = r#"<report><package name="a"><sourcefile name="B.java"><line nr="x"/>"#;
+        let result = from_jacoco_xml(report.as_bytes(), &SourceRoots::default());
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+}
+
+#[cfg(test)]
+mod istanbul {
+    use super::*;
+    use serde_json::json;
+
+    fn location(line: u32) -> Value {
+        json!({"start": {"line": line, "column": 0}, "end": {"line": line, "column": 20}})
+    }
+
+    fn report() -> Value {
+        json!({
+            "/home/ci/app/src/index.js": {
+                "path": "/home/ci/app/src/index.js",
+                "statementMap": {
+                    "0": location(1),
+                    "1": location(2),
+                    "2": location(3),
+                    "3": location(3),
+                    "4": location(5),
+                    "5": location(6),
+                    "6": location(6)
+                },
+                "fnMap": {},
+                "branchMap": {},
+                "s": {"0": 1, "1": 0, "2": 0, "3": 0, "4": 4, "5": 0, "6": 2},
+                "f": {},
+                "b": {}
+            },
+            "/home/ci/app/src/empty.js": {
+                "path": "/home/ci/app/src/empty.js",
+                "statementMap": {},
+                "fnMap": {},
+                "branchMap": {},
+                "s": {},
+                "f": {},
+                "b": {}
+            }
+        })
+    }
+
+    fn convert() -> CoverageInsight {
+        from_istanbul_json(&report(), Path::new("/home/ci/app")).unwrap()
+    }
+
+    #[test]
+    fn line_coverage() {
+        let insight = convert();
+        assert_eq!(60.0, insight.percentage());
+        assert_eq!(60.0, insight.file("src/index.js").unwrap().percentage());
+    }
+
+    #[test]
+    fn empty_file_is_covered() {
+        let insight = convert();
+        let file = insight.file("src/empty.js").unwrap();
+        assert_eq!(100.0, file.percentage());
+        assert!(file.uncovered_lines().is_empty());
+    }
+
+    #[test]
+    fn statements_are_merged_per_line() {
+        let insight = convert();
+        let file = insight.file("src/index.js").unwrap();
+        assert_eq!(&BTreeSet::from([1, 5, 6]), file.covered_lines());
+        assert_eq!(&BTreeSet::from([2, 3]), file.uncovered_lines());
+    }
+
+    #[test]
+    fn uncovered_annotations() {
+        let expected = json!({"annotations": [
+            {
+                "message": "Lines 2-3 are not covered by tests",
+                "severity": "LOW",
+                "path": "src/index.js",
+                "line": 2
+            }
+        ]});
+        let actual = serde_json::to_value(convert().annotations()).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn path_outside_root() {
+        let insight = from_istanbul_json(&report(), Path::new("/elsewhere")).unwrap();
+        assert!(insight.file("/home/ci/app/src/index.js").is_some());
+    }
+
+    #[test]
+    fn missing_hit_count() {
+        let report = json!({"/a.js": {"statementMap": {"0": location(1)}, "s": {}}});
+        let result = from_istanbul_json(&report, Path::new("/"));
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+}
+
+#[cfg(test)]
+mod coverage_py {
+    use super::*;
+    use serde_json::json;
+
+    fn report() -> Value {
+        json!({
+            "meta": {"version": "7.4.0", "branch_coverage": false, "show_contexts": false},
+            "files": {
+                "app/models.py": {
+                    "executed_lines": [1, 2, 3, 8],
+                    "summary": {
+                        "covered_lines": 4,
+                        "num_statements": 7,
+                        "percent_covered": 57.142857142857146,
+                        "missing_lines": 3,
+                        "excluded_lines": 2
+                    },
+                    "missing_lines": [4, 5, 10],
+                    "excluded_lines": [6, 7]
+                },
+                "app/views.py": {
+                    "executed_lines": [1, 2, 3],
+                    "summary": {
+                        "covered_lines": 3,
+                        "num_statements": 3,
+                        "percent_covered": 100.0,
+                        "missing_lines": 0,
+                        "excluded_lines": 0
+                    },
+                    "missing_lines": [],
+                    "excluded_lines": []
+                },
+                "manage.py": {
+                    "executed_lines": [1],
+                    "summary": {
+                        "covered_lines": 1,
+                        "num_statements": 1,
+                        "percent_covered": 100.0,
+                        "missing_lines": 0,
+                        "excluded_lines": 0
+                    },
+                    "missing_lines": [],
+                    "excluded_lines": []
+                }
+            },
+            "totals": {
+                "covered_lines": 8,
+                "num_statements": 11,
+                "percent_covered": 72.72727272727273,
+                "missing_lines": 3,
+                "excluded_lines": 2
+            }
+        })
+    }
+
+    #[test]
+    fn line_coverage() {
+        let insight = from_coverage_py_json(&report(), None).unwrap();
+        assert_eq!(72.72727272727273, insight.percentage());
+        assert_eq!(Parameter::Percentage(72), insight.data().parameter);
+    }
+
+    #[test]
+    fn excluded_lines_are_not_uncovered() {
+        let insight = from_coverage_py_json(&report(), None).unwrap();
+        let file = insight.file("app/models.py").unwrap();
+        assert_eq!(&BTreeSet::from([4, 5, 10]), file.uncovered_lines());
+        assert_eq!(vec![4..=5, 10..=10], file.uncovered_ranges());
+    }
+
+    #[test]
+    fn fully_covered_file() {
+        let insight = from_coverage_py_json(&report(), None).unwrap();
+        let file = insight.file("app/views.py").unwrap();
+        assert_eq!(100.0, file.percentage());
+        assert!(file.uncovered_ranges().is_empty());
+    }
+
+    #[test]
+    fn uncovered_annotations() {
+        let expected = json!({"annotations": [
+            {
+                "message": "Lines 4-5 are not covered by tests",
+                "severity": "LOW",
+                "path": "app/models.py",
+                "line": 4
+            },
+            {
+                "message": "Line 10 is not covered by tests",
+                "severity": "LOW",
+                "path": "app/models.py",
+                "line": 10
+            }
+        ]});
+        let insight = from_coverage_py_json(&report(), None).unwrap();
+        let actual = serde_json::to_value(insight.annotations()).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn package_data() {
+        let expected = json!([
+            {"title": ". coverage", "type": "PERCENTAGE", "value": 100},
+            {"title": "app coverage", "type": "PERCENTAGE", "value": 70}
+        ]);
+        let insight = from_coverage_py_json(&report(), None).unwrap();
+        let actual = serde_json::to_value(insight.package_data()).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn root_override() {
+        let mut report = report();
+        let files = report["files"].as_object_mut().unwrap();
+        let file = files.remove("manage.py").unwrap();
+        files.insert("/builds/project/manage.py".to_owned(), file);
+
+        let root = Path::new("/builds/project");
+        let insight = from_coverage_py_json(&report, Some(root)).unwrap();
+        assert!(insight.file("manage.py").is_some());
+    }
+}
+
+#[cfg(all(test, feature = "diff"))]
+mod diff_coverage {
+    use super::*;
+    use serde_json::json;
+
+    fn insight() -> CoverageInsight {
+        let files = [
+            ("src/app.py", vec![1, 2, 5, 6], vec![3, 4, 7, 8]),
+            ("src/old.py", vec![], vec![1, 2]),
+        ];
+        let files = files
+            .into_iter()
+            .map(|(path, covered, uncovered)| {
+                let file = FileCoverage {
+                    covered: covered.into_iter().collect(),
+                    uncovered: uncovered.into_iter().collect(),
+                    reported: None,
+                };
+                (path.to_owned(), file)
+            })
+            .collect();
+
+        CoverageInsight {
+            percentage: 40.0,
+            files,
+            skipped: 0,
+        }
+    }
+
+    // Changes lines 2-4 and 6-8 of src/app.py, where 6 is covered and 7-8 are
+    // uncovered, as well as the blank line 9, which is not executable.
+    const DIFF: &str = "\
++++ b/src/app.py
+@@ -1,4 +1,5 @@
+ a
+-b
++B
++C
++D
+-e
+ f
+@@ -6,1 +6,4 @@
+-g
++G
++H
++I
++
++++ b/src/unknown.py
+@@ -0,0 +1 @@
++new
+";
+
+    fn changed() -> ChangedLines {
+        ChangedLines::from_unified_diff(DIFF.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn percentage() {
+        let diff_coverage = insight().diff_coverage(&changed());
+        assert_eq!((2, 4), (diff_coverage.covered(), diff_coverage.uncovered()));
+        assert_eq!(2.0 * 100.0 / 6.0, diff_coverage.percentage());
+        assert_eq!(Parameter::Percentage(33), diff_coverage.data().parameter);
+    }
+
+    #[test]
+    fn non_executable_lines_do_not_count() {
+        let diff = "+++ b/src/app.py\n@@ -9,0 +9,2 @@\n+\n+# comment\n";
+        let changed = ChangedLines::from_unified_diff(diff.as_bytes()).unwrap();
+        let diff_coverage = insight().diff_coverage(&changed);
+        assert_eq!(DiffCoverage::default(), diff_coverage);
+        assert_eq!(100.0, diff_coverage.percentage());
+    }
+
+    #[test]
+    fn threshold() {
+        let diff_coverage = insight().diff_coverage(&changed());
+        assert_eq!(ReportResult::Pass, diff_coverage.result(33.0));
+        assert_eq!(ReportResult::Fail, diff_coverage.result(34.0));
+    }
+
+    #[test]
+    fn uncovered_changed_annotations() {
+        let expected = json!({"annotations": [
+            {
+                "message": "Lines 3-4 are not covered by tests",
+                "severity": "LOW",
+                "path": "src/app.py",
+                "line": 3
+            },
+            {
+                "message": "Lines 7-8 are not covered by tests",
+                "severity": "LOW",
+                "path": "src/app.py",
+                "line": 7
+            }
+        ]});
+        let actual = serde_json::to_value(insight().uncovered_changed(&changed())).unwrap();
+        assert_eq!(expected, actual);
+    }
+}
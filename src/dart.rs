@@ -0,0 +1,204 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::annotation::{Annotations, Severity};
+use crate::error::{Error, Result};
+use crate::finding::{Finding, FindingMapper, SeverityMap};
+
+/// Base URL of the documentation of the Dart analyzer's diagnostics.
+pub const DART_DIAGNOSTICS_URL: &str = "https://dart.dev/tools/diagnostic-messages";
+
+/// Base URL of the documentation of the Dart linter rules.
+pub const DART_LINTER_RULES_URL: &str = "https://dart.dev/tools/linter-rules";
+
+/// The annotations converted from `dart analyze --format=machine` output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DartAnalysis {
+    annotations: Annotations,
+    skipped: usize,
+}
+
+impl DartAnalysis {
+    /// Returns the converted annotations.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Returns the number of malformed lines that were skipped.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Consumes the analysis, returning the converted annotations.
+    pub fn into_annotations(self) -> Annotations {
+        self.annotations
+    }
+}
+
+/// Converts the output of `dart analyze --format=machine`.
+///
+/// Every line is a record of the form
+/// `SEVERITY|TYPE|CODE|PATH|LINE|COL|LENGTH|MESSAGE`, where pipes, backslashes
+/// and newlines within fields are escaped with a backslash. `ERROR` becomes
+/// [`Severity::High`], `WARNING` becomes [`Severity::Medium`] and anything
+/// else [`Severity::Low`]. The diagnostic code is used as the external ID and
+/// each annotation links to its documentation on dart.dev. Paths are made
+/// relative to `root`, and `severity_map` can override the severities.
+///
+/// Lines that do not have the expected fields are skipped and counted in
+/// [`DartAnalysis::skipped`].
+///
+/// # Errors
+///
+/// Will return `Err` if reading from `reader` fails, or if a diagnostic does
+/// not fit within the limits of an [`Annotation`].
+///
+/// [`Annotation`]: crate::Annotation
+pub fn from_dart_analyze_machine(
+    reader: impl BufRead,
+    root: &Path,
+    severity_map: Option<&SeverityMap>,
+) -> Result<DartAnalysis> {
+    let mut findings = Vec::new();
+    let mut skipped = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line =
+            line.map_err(|error| Error::parse_source("dart analyze", error).at_line(index + 1))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_record(&line) {
+            Some(finding) => findings.push(finding),
+            None => skipped += 1,
+        }
+    }
+
+    let mapper = FindingMapper::new()
+        .severity_map(severity_map.cloned().unwrap_or_default())
+        .root(root);
+    Ok(DartAnalysis {
+        annotations: mapper.map_all(findings)?,
+        skipped,
+    })
+}
+
+fn parse_record(record: &str) -> Option<Finding> {
+    let fields = split_fields(record);
+    let [level, diagnostic_type, code, path, line, _column, _length, message] =
+        <[String; 8]>::try_from(fields).ok()?;
+    let line = line.parse::<u32>().ok()?;
+
+    let severity = match level.as_str() {
+        "ERROR" => Severity::High,
+        "WARNING" => Severity::Medium,
+        _ => Severity::Low,
+    };
+    let code = code.to_lowercase();
+    let link = if diagnostic_type == "LINT" {
+        format!("{DART_LINTER_RULES_URL}/{code}")
+    } else {
+        format!("{DART_DIAGNOSTICS_URL}#{code}")
+    };
+
+    Some(Finding {
+        rule_id: Some(code.clone()),
+        path: Some(path),
+        line: Some(line),
+        link: Some(link),
+        severity: Some(severity),
+        fingerprint: Some(code),
+        ..Finding::new("dart analyze", message, level)
+    })
+}
+
+/// Splits a record on unescaped pipes, unescaping the fields.
+fn split_fields(record: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => field.push('\n'),
+                Some(escaped) => field.push(escaped),
+                None => field.push('\\'),
+            },
+            '|' => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod conversion {
+    use super::*;
+    use serde_json::json;
+
+    const OUTPUT: &str = r#"ERROR|COMPILE_TIME_ERROR|UNDEFINED_IDENTIFIER|/home/ci/app/lib/main.dart|12|5|3|Undefined name 'foo'.
+INFO|LINT|AVOID_PRINT|/home/ci/app/lib/main.dart|20|3|5|Don't invoke 'print' in production code.
+this line is not a diagnostic
+
+WARNING|STATIC_WARNING|DEAD_CODE|/home/ci/app/lib/src/parser.dart|7|1|10|Dead code in 'a\|b' pattern with a \\ backslash.
+"#;
+
+    fn convert() -> DartAnalysis {
+        from_dart_analyze_machine(OUTPUT.as_bytes(), Path::new("/home/ci/app"), None).unwrap()
+    }
+
+    #[test]
+    fn diagnostics() {
+        let expected = json!({"annotations": [
+            {
+                "message": "[dart_analyze::undefined_identifier] Undefined name 'foo'.",
+                "severity": "HIGH",
+                "path": "lib/main.dart",
+                "line": 12,
+                "link": "https://dart.dev/tools/diagnostic-messages#undefined_identifier",
+                "externalId": "undefined_identifier"
+            },
+            {
+                "message": "[dart_analyze::avoid_print] Don't invoke 'print' in production code.",
+                "severity": "LOW",
+                "path": "lib/main.dart",
+                "line": 20,
+                "link": "https://dart.dev/tools/linter-rules/avoid_print",
+                "externalId": "avoid_print"
+            },
+            {
+                "message": "[dart_analyze::dead_code] Dead code in 'a|b' pattern with a \\ backslash.",
+                "severity": "MEDIUM",
+                "path": "lib/src/parser.dart",
+                "line": 7,
+                "link": "https://dart.dev/tools/diagnostic-messages#dead_code",
+                "externalId": "dead_code"
+            }
+        ]});
+        assert_eq!(
+            expected,
+            serde_json::to_value(convert().annotations()).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        assert_eq!(1, convert().skipped());
+    }
+
+    #[test]
+    fn escaped_fields() {
+        let fields = split_fields(r"a\|b|c\\|d\ne");
+        assert_eq!(vec!["a|b", r"c\", "d\ne"], fields);
+    }
+
+    #[test]
+    fn invalid_line_number() {
+        let output = "ERROR|SYNTACTIC_ERROR|EXPECTED_TOKEN|/a.dart|x|1|1|Expected ';'.";
+        let analysis = from_dart_analyze_machine(output.as_bytes(), Path::new("/"), None).unwrap();
+        assert_eq!(1, analysis.skipped());
+        assert_eq!(Annotations::new(vec![]), analysis.into_annotations());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::report::{Data, Parameter};
+
+/// Converts milliseconds since the Unix epoch for [`Parameter::Date`], which
+/// cannot represent dates before the epoch. They saturate to the epoch, so
+/// that the conversions from `chrono` and `time` dates are infallible.
+fn saturating_millis(millis: i128) -> u64 {
+    u64::try_from(millis.max(0)).unwrap_or(u64::MAX)
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Parameter {
+    fn from(date: chrono::DateTime<Tz>) -> Self {
+        Parameter::Date(saturating_millis(date.timestamp_millis().into()))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Parameter {
+    fn from(date: time::OffsetDateTime) -> Self {
+        Parameter::Date(saturating_millis(date.unix_timestamp_nanos() / 1_000_000))
+    }
+}
+
+impl Data {
+    /// Constructs a [`Parameter::Date`] data field from a `chrono` date,
+    /// which saturates to the Unix epoch if it is before it.
+    #[cfg(feature = "chrono")]
+    pub fn date_chrono<T, Tz>(title: T, date: chrono::DateTime<Tz>) -> Data
+    where
+        T: Into<String>,
+        Tz: chrono::TimeZone,
+    {
+        Data::new(title, date.into())
+    }
+
+    /// Constructs a [`Parameter::Date`] data field from a `time` date, which
+    /// saturates to the Unix epoch if it is before it.
+    #[cfg(feature = "time")]
+    pub fn date_time<T: Into<String>>(title: T, date: time::OffsetDateTime) -> Data {
+        Data::new(title, date.into())
+    }
+}
+
+#[cfg(all(test, feature = "chrono"))]
+mod chrono_dates {
+    use chrono::{DateTime, FixedOffset, Utc};
+
+    use super::*;
+
+    #[test]
+    fn conversion() {
+        let date = DateTime::<Utc>::from_timestamp_millis(1_582_841_968_123).unwrap();
+        assert_eq!(Parameter::Date(1_582_841_968_123), Parameter::from(date));
+
+        let offset = date.with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        assert_eq!(
+            Data::new("Analyzed", Parameter::Date(1_582_841_968_123)),
+            Data::date_chrono("Analyzed", offset)
+        );
+    }
+
+    #[test]
+    fn before_epoch() {
+        let date = DateTime::<Utc>::from_timestamp_millis(-1).unwrap();
+        assert_eq!(Parameter::Date(0), Parameter::from(date));
+    }
+}
+
+#[cfg(all(test, feature = "time"))]
+mod time_dates {
+    use time::{Duration, OffsetDateTime, UtcOffset};
+
+    use super::*;
+
+    #[test]
+    fn conversion() {
+        let date = OffsetDateTime::UNIX_EPOCH + Duration::milliseconds(1_582_841_968_123);
+        assert_eq!(Parameter::Date(1_582_841_968_123), Parameter::from(date));
+
+        let offset = date.to_offset(UtcOffset::from_hms(1, 0, 0).unwrap());
+        assert_eq!(
+            Data::new("Analyzed", Parameter::Date(1_582_841_968_123)),
+            Data::date_time("Analyzed", offset)
+        );
+    }
+
+    #[test]
+    fn before_epoch() {
+        let date = OffsetDateTime::UNIX_EPOCH - Duration::milliseconds(1);
+        assert_eq!(Parameter::Date(0), Parameter::from(date));
+    }
+}
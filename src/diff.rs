@@ -0,0 +1,821 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+use crate::logging::log_debug;
+
+/// The lines added or modified by a change, per file.
+///
+/// Bitbucket only displays annotations on lines that were changed in a pull
+/// request, so annotations elsewhere can be dropped before they are posted
+/// with [`Annotations::retain_changed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedLines {
+    files: BTreeMap<String, BTreeSet<u32>>,
+    keep_file_level: bool,
+    quiet: bool,
+}
+
+impl Default for ChangedLines {
+    fn default() -> Self {
+        ChangedLines {
+            files: BTreeMap::new(),
+            keep_file_level: true,
+            quiet: false,
+        }
+    }
+}
+
+impl ChangedLines {
+    /// Constructs an empty set of changed lines.
+    pub fn new() -> Self {
+        ChangedLines::default()
+    }
+
+    /// Parses the changed lines from a unified diff, as produced by
+    /// `git diff`.
+    ///
+    /// The line numbers of added lines are recorded under the new path of
+    /// each file, so renamed files are found under their new name. Deleted
+    /// files are not recorded. Any number of context lines is supported,
+    /// including none as produced by `git diff --unified=0`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading from `reader` fails, if a hunk header is
+    /// malformed or not preceded by a file header, or if a hunk has fewer or
+    /// other lines than its header states.
+    pub fn from_unified_diff(reader: impl BufRead) -> Result<ChangedLines> {
+        let mut changed = ChangedLines::new();
+        let mut file: Option<String> = None;
+        let mut in_file = false;
+        let mut new_line = 0;
+        let (mut old_remaining, mut new_remaining) = (0u32, 0u32);
+        let mut last = 0;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| Error::parse_source("diff", error))?;
+            last = index + 1;
+
+            if old_remaining > 0 || new_remaining > 0 {
+                match line.chars().next() {
+                    Some('+') => {
+                        if let Some(lines) = file.as_ref().and_then(|f| changed.files.get_mut(f)) {
+                            lines.insert(new_line);
+                        }
+                        new_line += 1;
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                    Some('-') => old_remaining = old_remaining.saturating_sub(1),
+                    Some('\\') => {}
+                    // Some tools strip the space of empty context lines.
+                    Some(' ') | None => {
+                        new_line += 1;
+                        old_remaining = old_remaining.saturating_sub(1);
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                    Some(_) => {
+                        return Err(diff_error(format!(
+                            "unexpected line in hunk, expected {}",
+                            more_lines(old_remaining.max(new_remaining))
+                        ))
+                        .at_line(index + 1));
+                    }
+                }
+                continue;
+            }
+
+            if let Some(path) = line.strip_prefix("rename to ") {
+                let path = unquote(path);
+                changed.files.entry(path.clone()).or_default();
+                file = Some(path);
+                in_file = true;
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                file = header_path(path, "b/");
+                if let Some(path) = &file {
+                    changed.files.entry(path.clone()).or_default();
+                }
+                in_file = true;
+            } else if line.starts_with("diff ") {
+                file = None;
+                in_file = false;
+            } else if let Some(header) = line.strip_prefix("@@ ") {
+                if !in_file {
+                    return Err(
+                        diff_error("hunk without a file header".to_owned()).at_line(index + 1)
+                    );
+                }
+                let (old, new) = parse_hunk_header(header).ok_or_else(|| {
+                    diff_error("malformed hunk header".to_owned()).at_line(index + 1)
+                })?;
+                (old_remaining, new_line, new_remaining) = (old.1, new.0, new.1);
+            }
+        }
+        if old_remaining > 0 || new_remaining > 0 {
+            return Err(diff_error(format!(
+                "diff ends within a hunk, expected {}",
+                more_lines(old_remaining.max(new_remaining))
+            ))
+            .at_line(last));
+        }
+        Ok(changed)
+    }
+
+    /// Records `lines` of the file at `path` as changed, e.g. a range like
+    /// `10..=20`. The file is part of the change even if `lines` is empty.
+    pub fn insert<P, L>(&mut self, path: P, lines: L) -> &mut Self
+    where
+        P: Into<String>,
+        L: IntoIterator<Item = u32>,
+    {
+        self.files.entry(path.into()).or_default().extend(lines);
+        self
+    }
+
+    /// Sets whether file-level annotations, i.e. annotations without a line
+    /// or on line 0, are kept for files that are part of the change. This is
+    /// the default.
+    pub fn file_level_annotations(mut self, keep: bool) -> Self {
+        self.keep_file_level = keep;
+        self
+    }
+
+    /// Silences the debug events logged for every annotation removed by
+    /// [`Annotations::retain_changed`], if the `log` feature is enabled.
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Returns an iterator over the paths of the changed files.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Returns the changed lines of the file at `path`, if it is part of the
+    /// change.
+    pub fn lines(&self, path: &str) -> Option<&BTreeSet<u32>> {
+        self.files.get(path)
+    }
+
+    /// Returns `true` if the file at `path` is part of the change.
+    pub fn contains_file(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Returns `true` if `line` of the file at `path` was changed.
+    pub fn contains(&self, path: &str, line: u32) -> bool {
+        self.files
+            .get(path)
+            .is_some_and(|lines| lines.contains(&line))
+    }
+}
+
+impl Annotations {
+    /// Retains only the annotations that Bitbucket will display for the
+    /// change, returning the number of removed annotations.
+    ///
+    /// Annotations on a line are kept if that line was changed. File-level
+    /// annotations are kept if the file is part of the change, unless
+    /// disabled through [`ChangedLines::file_level_annotations`].
+    /// Annotations without a path are always kept.
+    pub fn retain_changed(&mut self, changed: &ChangedLines) -> usize {
+        self.take_unchanged(changed).annotations.len()
+    }
+
+    /// Like [`retain_changed`](Annotations::retain_changed), but returns the
+    /// removed annotations, e.g. to summarize them in the details of the
+    /// report.
+    pub fn take_unchanged(&mut self, changed: &ChangedLines) -> Annotations {
+        let (kept, removed) =
+            std::mem::take(&mut self.annotations)
+                .into_iter()
+                .partition(|annotation| {
+                    let keep = match (&annotation.path, annotation.line) {
+                        (None, _) => true,
+                        (Some(path), None | Some(0)) => {
+                            changed.keep_file_level && changed.contains_file(path)
+                        }
+                        (Some(path), Some(line)) => changed.contains(path, line),
+                    };
+                    if !keep {
+                        log_debug!(
+                            changed.quiet,
+                            "dropped annotation outside the change on {}:{}: {}",
+                            annotation.path.as_deref().unwrap_or_default(),
+                            annotation.line.unwrap_or_default(),
+                            annotation.message
+                        );
+                    }
+                    keep
+                });
+        self.annotations = kept;
+        Annotations::new(removed)
+    }
+
+    /// Moves annotations from the lines of an older version of their files
+    /// to the lines of the newer version described by `map`, returning the
+    /// number of removed annotations.
+    ///
+    /// Annotations on deleted lines are handled according to `policy`, while
+    /// annotations on deleted files are always removed. Annotations on files
+    /// that are not part of the diff, file-level annotations and annotations
+    /// without a path are left as they are, apart from following renames.
+    pub fn remap_lines(&mut self, map: &LineMap, policy: RemapPolicy) -> usize {
+        let before = self.annotations.len();
+        self.annotations.retain_mut(|annotation| {
+            let Some(file) = annotation
+                .path
+                .as_ref()
+                .and_then(|path| map.files.get(path))
+            else {
+                return true;
+            };
+            let Some(new_path) = &file.new_path else {
+                return false;
+            };
+
+            if let Some(line) = annotation.line.filter(|line| *line > 0) {
+                annotation.line = match (file.target(line), policy) {
+                    (Target::Line(line), _) => Some(line),
+                    (Target::Deleted { .. }, RemapPolicy::Drop) => return false,
+                    (Target::Deleted { .. }, RemapPolicy::FileLevel) => None,
+                    (Target::Deleted { next }, RemapPolicy::Keep) => Some(next.max(1)),
+                };
+            }
+            annotation.path = Some(new_path.clone());
+            true
+        });
+        before - self.annotations.len()
+    }
+}
+
+/// Where a line of the old version of a file ended up in the new version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Line(u32),
+    Deleted { next: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileLineMap {
+    new_path: Option<String>,
+    lines: BTreeMap<u32, Target>,
+    offsets: BTreeMap<u32, i64>,
+}
+
+impl FileLineMap {
+    fn target(&self, line: u32) -> Target {
+        if let Some(target) = self.lines.get(&line) {
+            return *target;
+        }
+        let offset = self
+            .offsets
+            .range(..=line)
+            .next_back()
+            .map_or(0, |(_, offset)| *offset);
+        Target::Line((i64::from(line) + offset) as u32)
+    }
+}
+
+/// Maps line numbers of an older version of files to a newer version.
+///
+/// Analyses that run against an older commit than the head of a pull request
+/// report line numbers that may have shifted since. A diff between the two
+/// commits describes how to move the annotations to the lines they were
+/// meant for with [`Annotations::remap_lines`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineMap {
+    files: BTreeMap<String, FileLineMap>,
+}
+
+impl LineMap {
+    /// Parses a unified diff from the analyzed commit to the new commit, as
+    /// produced by `git diff`.
+    ///
+    /// Files are identified by their old path, and renames are recorded so
+    /// that annotations can be moved to the new path.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading from `reader` fails or if a hunk header is
+    /// malformed.
+    pub fn from_unified_diff(reader: impl BufRead) -> Result<LineMap> {
+        let mut map = LineMap::default();
+        let mut old_path = None;
+        let mut file: Option<&mut FileLineMap> = None;
+        let (mut old_line, mut new_line) = (0u32, 0u32);
+        let (mut old_remaining, mut new_remaining) = (0u32, 0u32);
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| Error::parse_source("diff", error))?;
+
+            if old_remaining > 0 || new_remaining > 0 {
+                match line.chars().next() {
+                    Some('+') => {
+                        new_line += 1;
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                    Some('-') => {
+                        if let Some(file) = file.as_deref_mut() {
+                            file.lines
+                                .insert(old_line, Target::Deleted { next: new_line });
+                        }
+                        old_line += 1;
+                        old_remaining = old_remaining.saturating_sub(1);
+                    }
+                    Some('\\') => {}
+                    _ => {
+                        if let Some(file) = file.as_deref_mut() {
+                            file.lines.insert(old_line, Target::Line(new_line));
+                        }
+                        old_line += 1;
+                        new_line += 1;
+                        old_remaining = old_remaining.saturating_sub(1);
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                }
+                if old_remaining == 0 && new_remaining == 0 {
+                    if let Some(file) = file.as_deref_mut() {
+                        file.offsets
+                            .insert(old_line, i64::from(new_line) - i64::from(old_line));
+                    }
+                }
+                continue;
+            }
+
+            if line.starts_with("diff ") {
+                old_path = None;
+                file = None;
+            } else if let Some(path) = line.strip_prefix("rename from ") {
+                old_path = Some(unquote(path));
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                file = map.file(&old_path, Some(unquote(path)));
+            } else if let Some(path) = line.strip_prefix("--- ") {
+                old_path = header_path(path, "a/");
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                file = map.file(&old_path, header_path(path, "b/"));
+            } else if let Some(header) = line.strip_prefix("@@ ") {
+                let (old, new) = parse_hunk_header(header).ok_or_else(|| {
+                    diff_error("malformed hunk header".to_owned()).at_line(index + 1)
+                })?;
+                // A range without lines starts after the given line.
+                old_line = if old.1 == 0 { old.0 + 1 } else { old.0 };
+                new_line = if new.1 == 0 { new.0 + 1 } else { new.0 };
+                (old_remaining, new_remaining) = (old.1, new.1);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Returns the new path of the file at the old `path`, or `None` if the
+    /// file was deleted. Files that are not part of the diff keep their path.
+    pub fn path<'a>(&'a self, path: &'a str) -> Option<&'a str> {
+        match self.files.get(path) {
+            Some(file) => file.new_path.as_deref(),
+            None => Some(path),
+        }
+    }
+
+    /// Returns the new number of `line` of the file at the old `path`, or
+    /// `None` if the line or file was deleted.
+    pub fn line(&self, path: &str, line: u32) -> Option<u32> {
+        match self.files.get(path) {
+            Some(file) if file.new_path.is_some() => match file.target(line) {
+                Target::Line(line) => Some(line),
+                Target::Deleted { .. } => None,
+            },
+            Some(_) => None,
+            None => Some(line),
+        }
+    }
+
+    fn file(
+        &mut self,
+        old_path: &Option<String>,
+        new_path: Option<String>,
+    ) -> Option<&mut FileLineMap> {
+        let file = self
+            .files
+            .entry(old_path.clone()?)
+            .or_insert_with(|| FileLineMap {
+                new_path: None,
+                lines: BTreeMap::new(),
+                offsets: BTreeMap::new(),
+            });
+        file.new_path = new_path;
+        Some(file)
+    }
+}
+
+/// Decides what happens to annotations on deleted lines when remapping them
+/// with [`Annotations::remap_lines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemapPolicy {
+    /// Removes the annotation.
+    Drop,
+
+    /// Turns the annotation into a file-level annotation.
+    FileLevel,
+
+    /// Moves the annotation to the line that now follows the deleted lines.
+    Keep,
+}
+
+/// Returns the path of a `---` or `+++` header without its `a/` or `b/`
+/// prefix, or `None` for `/dev/null`.
+fn header_path(header: &str, prefix: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or(header);
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = unquote(path);
+    Some(path.strip_prefix(prefix).unwrap_or(&path).to_owned())
+}
+
+/// Removes the C-style quoting git applies to paths with special characters.
+fn unquote(path: &str) -> String {
+    let Some(quoted) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_owned();
+    };
+
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.bytes().peekable();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = u32::from(digit - b'0');
+                for _ in 0..2 {
+                    if let Some(digit @ b'0'..=b'7') = chars.peek().copied() {
+                        value = value * 8 + u32::from(digit - b'0');
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses `-a,b +c,d @@`, returning the start line and line count of the old
+/// and new ranges.
+fn parse_hunk_header(header: &str) -> Option<((u32, u32), (u32, u32))> {
+    let mut ranges = header.split_whitespace();
+    let old = parse_range(ranges.next()?.strip_prefix('-')?)?;
+    let new = parse_range(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn more_lines(count: u32) -> String {
+    format!("{count} more {}", if count == 1 { "line" } else { "lines" })
+}
+
+fn diff_error(message: String) -> Error {
+    Error::parse("diff", message)
+}
+
+#[cfg(test)]
+mod changed_lines {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 3b18e51..a9c2f4e 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,5 @@
+ mod annotation;
++mod diff;
+ mod error;
+-mod report;
++mod reports;
+ mod validation;
+@@ -20,1 +21,2 @@ pub use crate::error::*;
+ pub use crate::report::*;
++pub use crate::diff::*;
+diff --git a/src/old_name.rs b/src/new_name.rs
+similarity index 90%
+rename from src/old_name.rs
+rename to src/new_name.rs
+index 1111111..2222222 100644
+--- a/src/old_name.rs
++++ b/src/new_name.rs
+@@ -10,0 +11,2 @@
++fn added() {}
++fn also_added() {}
+diff --git a/README.md b/README.md
+deleted file mode 100644
+index 3333333..0000000
+--- a/README.md
++++ /dev/null
+@@ -1,2 +0,0 @@
+-# Title
+-Text
+";
+
+    fn annotation(path: Option<&str>, line: Option<u32>) -> crate::Annotation {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low);
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        if let Some(line) = line {
+            builder = builder.line(line);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let changed = ChangedLines::from_unified_diff(DIFF.as_bytes()).unwrap();
+        assert_eq!(
+            vec!["src/lib.rs", "src/new_name.rs"],
+            changed.files().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            &BTreeSet::from([2, 4, 22]),
+            changed.lines("src/lib.rs").unwrap()
+        );
+        assert_eq!(
+            &BTreeSet::from([11, 12]),
+            changed.lines("src/new_name.rs").unwrap()
+        );
+    }
+
+    #[test]
+    fn retain_changed() {
+        let changed = ChangedLines::from_unified_diff(DIFF.as_bytes()).unwrap();
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("src/lib.rs"), Some(2)),
+            annotation(Some("src/lib.rs"), Some(3)),
+            annotation(Some("src/new_name.rs"), Some(12)),
+            annotation(Some("src/old_name.rs"), Some(12)),
+            annotation(Some("src/new_name.rs"), None),
+            annotation(Some("src/unchanged.rs"), Some(0)),
+            annotation(None, None),
+        ]);
+
+        assert_eq!(3, annotations.retain_changed(&changed));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(Some("src/lib.rs"), Some(2)),
+                annotation(Some("src/new_name.rs"), Some(12)),
+                annotation(Some("src/new_name.rs"), None),
+                annotation(None, None),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn insert() {
+        let mut changed = ChangedLines::new();
+        changed
+            .insert("src/lib.rs", 10..=12)
+            .insert("src/lib.rs", [20])
+            .insert("src/main.rs", []);
+        assert_eq!(
+            &BTreeSet::from([10, 11, 12, 20]),
+            changed.lines("src/lib.rs").unwrap()
+        );
+        assert!(changed.contains_file("src/main.rs"));
+        assert!(!changed.contains("src/main.rs", 1));
+
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("src/lib.rs"), Some(11)),
+            annotation(Some("src/lib.rs"), Some(13)),
+            annotation(Some("src/main.rs"), None),
+            annotation(Some("src/main.rs"), Some(1)),
+            annotation(Some("src/other.rs"), None),
+        ]);
+        let removed = annotations.take_unchanged(&changed);
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(Some("src/lib.rs"), Some(11)),
+                annotation(Some("src/main.rs"), None),
+            ]),
+            annotations
+        );
+        assert_eq!(
+            Annotations::new(vec![
+                annotation(Some("src/lib.rs"), Some(13)),
+                annotation(Some("src/main.rs"), Some(1)),
+                annotation(Some("src/other.rs"), None),
+            ]),
+            removed
+        );
+    }
+
+    #[test]
+    fn without_file_level_annotations() {
+        let changed = ChangedLines::from_unified_diff(DIFF.as_bytes())
+            .unwrap()
+            .file_level_annotations(false);
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("src/new_name.rs"), None),
+            annotation(Some("src/new_name.rs"), Some(0)),
+        ]);
+        assert_eq!(2, annotations.retain_changed(&changed));
+    }
+
+    #[test]
+    fn quoted_path() {
+        let diff = "+++ \"b/caf\\303\\251 menu.txt\"\n@@ -0,0 +1 @@\n+line\n";
+        let changed = ChangedLines::from_unified_diff(diff.as_bytes()).unwrap();
+        assert!(changed.contains("café menu.txt", 1));
+    }
+
+    #[test]
+    fn new_file_with_spaces() {
+        let diff = "\
+diff --git a/docs/user guide.md b/docs/user guide.md
+new file mode 100644
+index 0000000..4444444
+--- /dev/null
++++ b/docs/user guide.md
+@@ -0,0 +1,2 @@
++# Guide
++Text
+";
+        let changed = ChangedLines::from_unified_diff(diff.as_bytes()).unwrap();
+        assert_eq!(
+            &BTreeSet::from([1, 2]),
+            changed.lines("docs/user guide.md").unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_hunk_header() {
+        let diff = "+++ b/a.txt\n@@ -1,x +1 @@\n";
+        let result = ChangedLines::from_unified_diff(diff.as_bytes());
+        assert!(matches!(result, Err(Error::Parse { line: Some(2), .. })));
+    }
+
+    #[test]
+    fn malformed_hunks() {
+        let parse = |diff: &str| match ChangedLines::from_unified_diff(diff.as_bytes()) {
+            Err(Error::Parse { line, message, .. }) => (line, message),
+            other => panic!("unexpected result {other:?}"),
+        };
+        assert_eq!(
+            (
+                Some(3),
+                "diff ends within a hunk, expected 1 more line".to_owned()
+            ),
+            parse("+++ b/a.txt\n@@ -1,2 +1,2 @@\n context\n")
+        );
+        assert_eq!(
+            (
+                Some(3),
+                "unexpected line in hunk, expected 2 more lines".to_owned()
+            ),
+            parse("+++ b/a.txt\n@@ -1,2 +1,2 @@\n@@ -5 +5 @@\n")
+        );
+        assert_eq!(
+            (Some(2), "hunk without a file header".to_owned()),
+            parse("diff --git a/a.txt b/a.txt\n@@ -1 +1 @@\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod line_map {
+    use super::*;
+    use crate::{Annotation, AnnotationBuilder, Severity};
+
+    // Inserts two lines above line 3, deletes line 6, replaces line 9 and
+    // renames the file.
+    const DIFF: &str = "\
+diff --git a/src/old.rs b/src/new.rs
+similarity index 80%
+rename from src/old.rs
+rename to src/new.rs
+--- a/src/old.rs
++++ b/src/new.rs
+@@ -2,1 +2,3 @@
+ b
++inserted
++inserted
+@@ -5,6 +7,5 @@
+ e
+-f
+ g
+ h
+-i
++I
+ j
+diff --git a/README.md b/README.md
+deleted file mode 100644
+--- a/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Title
+";
+
+    fn annotation(path: &str, line: Option<u32>) -> Annotation {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low).path(path);
+        if let Some(line) = line {
+            builder = builder.line(line);
+        }
+        builder.build().unwrap()
+    }
+
+    fn map() -> LineMap {
+        LineMap::from_unified_diff(DIFF.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn lines() {
+        let map = map();
+        let lines = (1..=12)
+            .map(|line| map.line("src/old.rs", line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(1),
+                Some(2),
+                Some(5),
+                Some(6),
+                Some(7),
+                None,
+                Some(8),
+                Some(9),
+                None,
+                Some(11),
+                Some(12),
+                Some(13)
+            ],
+            lines
+        );
+        assert_eq!(Some("src/new.rs"), map.path("src/old.rs"));
+        assert_eq!(None, map.path("README.md"));
+        assert_eq!(Some(4), map.line("src/other.rs", 4));
+    }
+
+    #[test]
+    fn remap() {
+        let mut annotations = Annotations::new(vec![
+            annotation("src/old.rs", Some(3)),
+            annotation("src/old.rs", Some(6)),
+            annotation("src/old.rs", Some(10)),
+            annotation("src/old.rs", None),
+            annotation("src/other.rs", Some(6)),
+            annotation("README.md", Some(1)),
+        ]);
+        assert_eq!(2, annotations.remap_lines(&map(), RemapPolicy::Drop));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("src/new.rs", Some(5)),
+                annotation("src/new.rs", Some(11)),
+                annotation("src/new.rs", None),
+                annotation("src/other.rs", Some(6)),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn deleted_lines() {
+        let remap = |policy| {
+            let mut annotations = Annotations::new(vec![
+                annotation("src/old.rs", Some(6)),
+                annotation("src/old.rs", Some(9)),
+            ]);
+            annotations.remap_lines(&map(), policy);
+            annotations
+        };
+
+        assert_eq!(Annotations::new(vec![]), remap(RemapPolicy::Drop));
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("src/new.rs", None),
+                annotation("src/new.rs", None),
+            ]),
+            remap(RemapPolicy::FileLevel)
+        );
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("src/new.rs", Some(8)),
+                annotation("src/new.rs", Some(10)),
+            ]),
+            remap(RemapPolicy::Keep)
+        );
+    }
+}
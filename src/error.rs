@@ -1,20 +1,127 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 /// Provides descriptive errors when the serialization of a `Report` or
 /// `Annotation` fails.
 #[derive(Debug, Error)]
 pub enum Error {
+    #[cfg(feature = "cache")]
+    #[error("invalid annotation cache: {message}")]
+    CacheFormat { message: String },
     #[error("field '{name}' too long, its length {len} is longer than the allowed limit {limit}")]
     FieldTooLong {
         name: String,
         len: usize,
         limit: usize,
     },
+    #[cfg(any(feature = "glob", feature = "regex"))]
+    #[error("invalid pattern '{pattern}': {message}")]
+    InvalidPattern { pattern: String, message: String },
+    #[error("invalid template '{template}': {message}")]
+    InvalidTemplate { template: String, message: String },
+    #[error("invalid value for '{field}': {reason}")]
+    InvalidValue { field: String, reason: String },
+    #[error("I/O error on '{}'", .path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("input exceeds the limit of {limit} {unit}")]
+    LimitExceeded { limit: u64, unit: String },
+    #[error("path '{}' is outside of the repository at '{}'", .path.display(), .root.display())]
+    OutsideRepository { path: PathBuf, root: PathBuf },
+    #[error("failed to parse {tool} output{}: {message}", .line.map(|line| format!(" at line {line}")).unwrap_or_default())]
+    Parse {
+        tool: String,
+        line: Option<usize>,
+        message: String,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[cfg(feature = "git")]
+    #[error("no repository found at or above '{}'", .path.display())]
+    RepositoryNotFound { path: PathBuf },
+    #[error("pipeline stage '{stage}' failed")]
+    Stage { stage: String, source: Box<Error> },
+    #[cfg(feature = "json")]
     #[error("serialization error")]
     SerdeError(#[from] serde_json::Error),
+    #[cfg(feature = "yaml")]
+    #[error("YAML serialization error")]
+    YamlError(#[from] serde_yaml::Error),
+}
+
+impl Error {
+    /// Constructs an [`Error::Parse`] for the output of `tool`, e.g. for a
+    /// converter outside of this crate.
+    pub fn parse<T: Into<String>, M: Into<String>>(tool: T, message: M) -> Error {
+        Error::Parse {
+            tool: tool.into(),
+            line: None,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Constructs an [`Error::Parse`] for the output of `tool` caused by
+    /// `source`, whose message it repeats.
+    pub fn parse_source<T, E>(tool: T, source: E) -> Error
+    where
+        T: Into<String>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Error::Parse {
+            tool: tool.into(),
+            line: None,
+            message: source.to_string(),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Sets the line of the input, counting from 1, at which an
+    /// [`Error::Parse`] occurred. Other errors are returned unchanged.
+    pub fn at_line(mut self, at: usize) -> Error {
+        if let Error::Parse { line, .. } = &mut self {
+            *line = Some(at);
+        }
+        self
+    }
 }
 
 /// Shorthand for [`Result`] type.
 ///
 /// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod parse_errors {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "failed to parse clippy output: unexpected end",
+            Error::parse("clippy", "unexpected end").to_string()
+        );
+        assert_eq!(
+            "failed to parse clippy output at line 812: unexpected end",
+            Error::parse("clippy", "unexpected end")
+                .at_line(812)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn source() {
+        let io = std::io::Error::other("disk on fire");
+        let error = Error::parse_source("clippy", io).at_line(3);
+        assert_eq!(
+            "failed to parse clippy output at line 3: disk on fire",
+            error.to_string()
+        );
+        assert_eq!("disk on fire", error.source().unwrap().to_string());
+        assert!(Error::parse("clippy", "message").source().is_none());
+    }
+}
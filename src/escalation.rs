@@ -0,0 +1,376 @@
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+
+use crate::annotation::{Annotation, Annotations, Severity, MESSAGE_LIMIT};
+use crate::finding::glob_matches;
+use crate::validation::truncate;
+
+/// Decides what happens when an [`EscalationRule`] fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscalationAction {
+    /// Raises the severity of the matched annotations.
+    Escalate,
+
+    /// Adds a repository-level annotation stating the number of matched
+    /// annotations, which are left as they are.
+    Summarize,
+}
+
+/// A rule that escalates annotations once at least a threshold of them match,
+/// e.g. to make fifty `unwrap` lints a Medium problem while one is Low.
+///
+/// An annotation matches if it matches all the criteria that are set. Rule
+/// and path patterns may contain `*`, matching any number of characters
+/// including `/`, and `?`, matching a single character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscalationRule {
+    name: String,
+    threshold: usize,
+    target: Severity,
+    action: EscalationAction,
+    rule: Option<String>,
+    path: Option<String>,
+    severity: Option<Severity>,
+}
+
+impl EscalationRule {
+    /// Constructs a rule named `name` that escalates the matched annotations
+    /// to `target` once there are at least `threshold` of them.
+    pub fn new<T: Into<String>>(name: T, threshold: usize, target: Severity) -> Self {
+        EscalationRule {
+            name: name.into(),
+            threshold,
+            target,
+            action: EscalationAction::Escalate,
+            rule: None,
+            path: None,
+            severity: None,
+        }
+    }
+
+    /// Sets what happens when the rule fires.
+    pub fn action(mut self, action: EscalationAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Only matches annotations whose rule prefix, written as `tool::rule`,
+    /// matches `pattern`. See [`AnnotationBuilder::rule`].
+    ///
+    /// [`AnnotationBuilder::rule`]: crate::AnnotationBuilder::rule
+    pub fn rule<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.rule = Some(pattern.into());
+        self
+    }
+
+    /// Only matches annotations whose path matches `pattern`.
+    ///
+    /// Unlike the patterns of `PathFilter` and `SeverityOverrides`, `*` also
+    /// matches `/`, and the pattern must match the whole path, so a directory
+    /// does not match the files below it. For example, `src/*.rs` matches
+    /// `src/a/b.rs` and `*.pb.go` matches at any depth, while `vendor/`
+    /// matches nothing, unlike `vendor/*`.
+    pub fn path<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.path = Some(pattern.into());
+        self
+    }
+
+    /// Only matches annotations of `severity`.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    fn matches(&self, annotation: &Annotation) -> bool {
+        let rule = self.rule.as_deref().is_none_or(|pattern| {
+            annotation
+                .rule()
+                .is_some_and(|(tool, rule)| glob_matches(pattern, &format!("{tool}::{rule}")))
+        });
+        let path = self.path.as_deref().is_none_or(|pattern| {
+            annotation
+                .path
+                .as_deref()
+                .is_some_and(|path| glob_matches(pattern, path))
+        });
+        let severity = self
+            .severity
+            .is_none_or(|severity| annotation.severity == severity);
+        rule && path && severity
+    }
+}
+
+/// An ordered list of [`EscalationRule`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EscalationRules {
+    rules: Vec<EscalationRule>,
+}
+
+impl EscalationRules {
+    /// Constructs an empty list of rules.
+    pub fn new() -> Self {
+        EscalationRules::default()
+    }
+
+    /// Appends `rule`, which is applied after the rules before it.
+    pub fn rule(mut self, rule: EscalationRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+/// A rule that fired when applying [`EscalationRules`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiredEscalation {
+    name: String,
+    matched: usize,
+    target: Severity,
+}
+
+impl FiredEscalation {
+    /// Returns the name of the rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of annotations the rule matched.
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    /// Returns the severity the rule escalated to.
+    pub fn target(&self) -> Severity {
+        self.target
+    }
+}
+
+/// The outcome of [`Annotations::apply_escalations`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EscalationOutcome {
+    fired: Vec<FiredEscalation>,
+}
+
+impl EscalationOutcome {
+    /// Returns the rules that fired, in the order they were applied.
+    pub fn fired(&self) -> &[FiredEscalation] {
+        &self.fired
+    }
+
+    /// Describes the rules that fired, one per line, e.g. for the details of
+    /// a report.
+    pub fn describe(&self) -> String {
+        self.fired
+            .iter()
+            .map(|fired| {
+                format!(
+                    "{}: {} annotations escalated to {}",
+                    fired.name,
+                    fired.matched,
+                    fired.target.label()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Annotations {
+    /// Applies `rules` in the order they were declared, each to the
+    /// annotations as left by the rules before it.
+    ///
+    /// A rule fires if at least its threshold of annotations match it. Then
+    /// it either raises the severity of the matched annotations to its
+    /// target, leaving more severe annotations as they are, or adds a
+    /// repository-level annotation of the target severity.
+    pub fn apply_escalations(&mut self, rules: &EscalationRules) -> EscalationOutcome {
+        let mut outcome = EscalationOutcome::default();
+        for rule in &rules.rules {
+            let matched = self
+                .annotations
+                .iter()
+                .filter(|annotation| rule.matches(annotation))
+                .count();
+            if matched == 0 || matched < rule.threshold {
+                continue;
+            }
+
+            match rule.action {
+                EscalationAction::Escalate => {
+                    for annotation in &mut self.annotations {
+                        if rule.matches(annotation) && rule.target > annotation.severity {
+                            annotation.severity = rule.target;
+                        }
+                    }
+                }
+                EscalationAction::Summarize => {
+                    let mut message =
+                        format!("{}: {matched} annotations match this policy", rule.name);
+                    truncate(&mut message, MESSAGE_LIMIT);
+                    self.annotations.push(Annotation {
+                        message,
+                        severity: rule.target,
+                        annotation_type: None,
+                        path: None,
+                        line: None,
+                        link: None,
+                        external_id: None,
+                        #[cfg(feature = "json")]
+                        extensions: BTreeMap::new(),
+                    });
+                }
+            }
+            outcome.fired.push(FiredEscalation {
+                name: rule.name.clone(),
+                matched,
+                target: rule.target,
+            });
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod escalation_rules {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    fn annotations() -> Annotations {
+        let mut annotations = (0..5)
+            .map(|line| {
+                AnnotationBuilder::new("Called unwrap", Severity::Low)
+                    .rule("clippy", "unwrap_used")
+                    .unwrap()
+                    .path("src/lib.rs")
+                    .line(line + 1)
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        annotations.push(
+            AnnotationBuilder::new("Redundant clone", Severity::Low)
+                .rule("clippy", "redundant_clone")
+                .unwrap()
+                .path("src/main.rs")
+                .build()
+                .unwrap(),
+        );
+        Annotations::new(annotations)
+    }
+
+    fn severities(annotations: &Annotations) -> Vec<Severity> {
+        annotations
+            .annotations
+            .iter()
+            .map(|annotation| annotation.severity)
+            .collect()
+    }
+
+    #[test]
+    fn fires() {
+        let mut annotations = annotations();
+        let rules = EscalationRules::new().rule(
+            EscalationRule::new("Too many unwraps", 5, Severity::Medium)
+                .rule("clippy::unwrap_*")
+                .path("src/*"),
+        );
+        let outcome = annotations.apply_escalations(&rules);
+        assert_eq!(1, outcome.fired().len());
+        assert_eq!(5, outcome.fired()[0].matched());
+        assert_eq!(
+            "Too many unwraps: 5 annotations escalated to Medium",
+            outcome.describe()
+        );
+        assert_eq!(
+            vec![
+                Severity::Medium,
+                Severity::Medium,
+                Severity::Medium,
+                Severity::Medium,
+                Severity::Medium,
+                Severity::Low
+            ],
+            severities(&annotations)
+        );
+    }
+
+    #[test]
+    fn does_not_fire() {
+        let expected = annotations();
+        let mut annotations = annotations();
+        let rules = EscalationRules::new()
+            .rule(
+                EscalationRule::new("Too many unwraps", 6, Severity::Medium).rule("*::unwrap_used"),
+            )
+            .rule(EscalationRule::new("Any high", 1, Severity::High).severity(Severity::High));
+        let outcome = annotations.apply_escalations(&rules);
+        assert!(outcome.fired().is_empty());
+        assert_eq!("", outcome.describe());
+        assert_eq!(expected, annotations);
+    }
+
+    #[test]
+    fn declaration_order() {
+        let mut annotations = annotations();
+        let rules = EscalationRules::new()
+            .rule(EscalationRule::new("Lints", 6, Severity::Medium).rule("clippy::*"))
+            .rule(
+                EscalationRule::new("Medium lints", 6, Severity::High)
+                    .severity(Severity::Medium)
+                    .action(EscalationAction::Summarize),
+            );
+        let outcome = annotations.apply_escalations(&rules);
+        let names = outcome
+            .fired()
+            .iter()
+            .map(FiredEscalation::name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Lints", "Medium lints"], names);
+
+        let summary = annotations.annotations.last().unwrap();
+        assert_eq!(None, summary.path);
+        assert_eq!(Severity::High, summary.severity);
+        assert_eq!(
+            "Medium lints: 6 annotations match this policy",
+            summary.message
+        );
+    }
+
+    #[test]
+    fn nested_paths() {
+        let nested = |path: &str| {
+            AnnotationBuilder::new("Called unwrap", Severity::Low)
+                .path(path)
+                .build()
+                .unwrap()
+        };
+        let rule = EscalationRule::new("Sources", 1, Severity::Medium).path("src/*.rs");
+        assert!(rule.matches(&nested("src/lib.rs")));
+        assert!(rule.matches(&nested("src/a/b.rs")));
+        assert!(!rule.matches(&nested("tests/src/lib.rs")));
+
+        let rule = EscalationRule::new("Vendored", 1, Severity::Medium).path("vendor/");
+        assert!(!rule.matches(&nested("vendor/a.rs")));
+        let rule = EscalationRule::new("Vendored", 1, Severity::Medium).path("vendor/*");
+        assert!(rule.matches(&nested("vendor/a/b.rs")));
+        let rule = EscalationRule::new("Generated", 1, Severity::Medium).path("*.pb.go");
+        assert!(rule.matches(&nested("api/v1/service.pb.go")));
+    }
+
+    #[test]
+    fn later_severity_filter() {
+        let mut annotations = annotations();
+        let rules = EscalationRules::new().rule(
+            EscalationRule::new("Too many unwraps", 3, Severity::Medium)
+                .rule("clippy::unwrap_used"),
+        );
+        annotations.apply_escalations(&rules);
+        annotations
+            .annotations
+            .retain(|annotation| annotation.severity >= Severity::Medium);
+        assert_eq!(5, annotations.annotations.len());
+        assert!(annotations
+            .annotations
+            .iter()
+            .all(|annotation| annotation.rule() == Some(("clippy", "unwrap_used"))));
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::annotation::{Annotation, Annotations, EXTERNAL_ID_LIMIT};
+use crate::error::{Error, Result};
+use crate::finding::fingerprint;
+
+/// Length of the hashes that replace external IDs that are too long.
+const HASH_LEN: usize = 16;
+
+/// A prefix of external IDs, such as `clippy/`, that identifies the tool that
+/// produced an annotation, e.g. to delete the annotations of one tool only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalIdNamespace {
+    prefix: String,
+}
+
+impl ExternalIdNamespace {
+    /// Constructs the namespace of `tool`, whose IDs start with `tool/`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `tool` is empty, contains a `/`, or is too long
+    /// for a hash to fit into an external ID after it.
+    pub fn new(tool: &str) -> Result<ExternalIdNamespace> {
+        let reason = if tool.is_empty() {
+            Some("is empty".to_owned())
+        } else if tool.contains('/') {
+            Some("contains '/'".to_owned())
+        } else if tool.len() + 1 + HASH_LEN > EXTERNAL_ID_LIMIT {
+            Some(format!(
+                "is longer than {}",
+                EXTERNAL_ID_LIMIT - 1 - HASH_LEN
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(Error::InvalidValue {
+                field: "namespace".to_owned(),
+                reason: format!("'{tool}' {reason}"),
+            });
+        }
+        Ok(ExternalIdNamespace {
+            prefix: format!("{tool}/"),
+        })
+    }
+
+    /// Returns the tool of the namespace.
+    pub fn tool(&self) -> &str {
+        &self.prefix[..self.prefix.len() - 1]
+    }
+
+    /// Returns `id` in the namespace.
+    ///
+    /// IDs that are already in the namespace are returned as they are. If
+    /// the ID would be longer than [`EXTERNAL_ID_LIMIT`], the part after the
+    /// prefix is replaced by a hash of `id`, so that the result is still
+    /// unique in practice.
+    pub fn id(&self, id: &str) -> String {
+        if id.starts_with(&self.prefix) && id.len() <= EXTERNAL_ID_LIMIT {
+            return id.to_owned();
+        }
+        if self.prefix.len() + id.len() <= EXTERNAL_ID_LIMIT {
+            format!("{}{id}", self.prefix)
+        } else {
+            format!("{}{}", self.prefix, fingerprint(&[id]))
+        }
+    }
+}
+
+/// Hashes the location and message of an annotation, see
+/// [`Annotation::stable_id`].
+pub(crate) fn stable_id(
+    salt: &str,
+    path: Option<&str>,
+    line: Option<u32>,
+    message: &str,
+) -> String {
+    let line = line.unwrap_or(0).to_string();
+    fingerprint(&[salt, path.unwrap_or_default(), &line, message])
+}
+
+impl Annotation {
+    /// Returns an ID derived from the path, line and message of the
+    /// annotation and `salt`, e.g. the name of the tool, to use as its
+    /// external ID.
+    ///
+    /// The ID is the 64-bit FNV-1a hash of these fields as 16 hexadecimal
+    /// digits, so it is the same across runs and machines and always fits
+    /// within [`EXTERNAL_ID_LIMIT`]. Annotations without a line and on line 0
+    /// have the same ID.
+    pub fn stable_id(&self, salt: &str) -> String {
+        stable_id(salt, self.path.as_deref(), self.line, &self.message)
+    }
+}
+
+impl Annotations {
+    /// Indexes the annotations by their external ID, e.g. to reconcile them
+    /// with the annotations of a report in Bitbucket.
+    ///
+    /// Annotations without an external ID are left out. If several
+    /// annotations have the same ID, the first one is indexed; use
+    /// [`duplicate_external_ids`](Annotations::duplicate_external_ids) to find
+    /// them.
+    pub fn by_external_id(&self) -> HashMap<&str, &Annotation> {
+        let mut index = HashMap::new();
+        for annotation in &self.annotations {
+            if let Some(id) = annotation.external_id.as_deref() {
+                index.entry(id).or_insert(annotation);
+            }
+        }
+        index
+    }
+
+    /// Returns the first annotation with the external ID `id`.
+    pub fn find_external_id(&self, id: &str) -> Option<&Annotation> {
+        self.annotations
+            .iter()
+            .find(|annotation| annotation.external_id.as_deref() == Some(id))
+    }
+
+    /// Moves the external IDs of the annotations into `namespace`, see
+    /// [`ExternalIdNamespace::id`], returning the number of changed IDs.
+    ///
+    /// Annotations without an external ID are left as they are, and calling
+    /// this again with the same namespace changes nothing.
+    pub fn namespace_external_ids(&mut self, namespace: &ExternalIdNamespace) -> usize {
+        let mut changed = 0;
+        for external_id in self
+            .annotations
+            .iter_mut()
+            .filter_map(|annotation| annotation.external_id.as_mut())
+        {
+            let id = namespace.id(external_id);
+            if id != *external_id {
+                *external_id = id;
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Groups the annotations by the namespace of their external ID, i.e. the
+    /// part before the first `/`, e.g. to delete the annotations of one tool.
+    ///
+    /// Annotations without an external ID or whose ID has no namespace are
+    /// grouped under the empty string.
+    pub fn partition_by_namespace(&self) -> BTreeMap<&str, Vec<&Annotation>> {
+        let mut partitions = BTreeMap::<&str, Vec<&Annotation>>::new();
+        for annotation in &self.annotations {
+            let namespace = annotation
+                .external_id
+                .as_deref()
+                .and_then(|id| id.split_once('/'))
+                .map_or("", |(namespace, _)| namespace);
+            partitions.entry(namespace).or_default().push(annotation);
+        }
+        partitions
+    }
+
+    /// Returns the external IDs that more than one annotation has, each once,
+    /// in the order they first occur.
+    pub fn duplicate_external_ids(&self) -> Vec<&str> {
+        let mut counts = HashMap::new();
+        let mut duplicates = Vec::new();
+        for id in self
+            .annotations
+            .iter()
+            .filter_map(|annotation| annotation.external_id.as_deref())
+        {
+            match counts.entry(id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(1);
+                }
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() += 1;
+                    if *entry.get() == 2 {
+                        duplicates.push(id);
+                    }
+                }
+            }
+        }
+        duplicates
+    }
+}
+
+#[cfg(test)]
+mod external_ids {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation(message: &str, id: Option<&str>) -> Annotation {
+        let mut builder = AnnotationBuilder::new(message, Severity::Low);
+        if let Some(id) = id {
+            builder.set_external_id(id);
+        }
+        builder.build().unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            annotation("First", Some("a")),
+            annotation("Second", Some("b")),
+            annotation("Without ID", None),
+            annotation("Third", Some("a")),
+            annotation("Fourth", Some("c")),
+            annotation("Fifth", Some("a")),
+        ])
+    }
+
+    #[test]
+    fn lookup() {
+        let annotations = annotations();
+        let index = annotations.by_external_id();
+        assert_eq!(3, index.len());
+        assert_eq!("Second", index["b"].message);
+        assert_eq!(
+            Some("Fourth"),
+            annotations
+                .find_external_id("c")
+                .map(|annotation| annotation.message.as_str())
+        );
+        assert_eq!(None, annotations.find_external_id("missing"));
+    }
+
+    #[test]
+    fn duplicates() {
+        let annotations = annotations();
+        assert_eq!("First", annotations.by_external_id()["a"].message);
+        assert_eq!("First", annotations.find_external_id("a").unwrap().message);
+        assert_eq!(vec!["a"], annotations.duplicate_external_ids());
+
+        let unique = Annotations::new(vec![
+            annotation("First", Some("a")),
+            annotation("Second", None),
+            annotation("Third", None),
+        ]);
+        assert!(unique.duplicate_external_ids().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod stable_ids {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn builder(line: u32) -> AnnotationBuilder {
+        AnnotationBuilder::new("Possible panic", Severity::High)
+            .path("src/lib.rs")
+            .line(line)
+    }
+
+    #[test]
+    fn generated() {
+        let first = builder(40).generate_external_id("clippy").build().unwrap();
+        let second = builder(40).generate_external_id("clippy").build().unwrap();
+        assert_eq!(first.external_id, second.external_id);
+        assert_eq!(Some(first.stable_id("clippy")), first.external_id);
+        // The ID is independent of the platform.
+        assert_eq!(Some("ded4067915137cfd"), first.external_id.as_deref());
+
+        let moved = builder(41).generate_external_id("clippy").build().unwrap();
+        assert_ne!(first.external_id, moved.external_id);
+        let other_tool = builder(40).generate_external_id("semgrep").build().unwrap();
+        assert_ne!(first.external_id, other_tool.external_id);
+
+        let id = first.external_id.unwrap();
+        assert_eq!(16, id.len());
+        assert!(id.len() <= EXTERNAL_ID_LIMIT);
+    }
+
+    #[test]
+    fn explicit_id_is_kept() {
+        let annotation = builder(40)
+            .generate_external_id("clippy")
+            .external_id("CLIPPY-1")
+            .build()
+            .unwrap();
+        assert_eq!(Some("CLIPPY-1"), annotation.external_id.as_deref());
+    }
+}
+
+#[cfg(test)]
+mod namespaces {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation(id: Option<&str>) -> Annotation {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low);
+        if let Some(id) = id {
+            builder = builder.external_id(id);
+        }
+        builder.build().unwrap()
+    }
+
+    fn ids(annotations: &Annotations) -> Vec<Option<&str>> {
+        annotations
+            .annotations
+            .iter()
+            .map(|annotation| annotation.external_id.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn idempotent() {
+        let clippy = ExternalIdNamespace::new("clippy").unwrap();
+        assert_eq!("clippy", clippy.tool());
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("8f3a")),
+            annotation(Some("clippy/77c1")),
+            annotation(Some("semgrep/77c1")),
+            annotation(None),
+        ]);
+        assert_eq!(2, annotations.namespace_external_ids(&clippy));
+        assert_eq!(
+            vec![
+                Some("clippy/8f3a"),
+                Some("clippy/77c1"),
+                Some("clippy/semgrep/77c1"),
+                None
+            ],
+            ids(&annotations)
+        );
+
+        let namespaced = annotations.clone();
+        assert_eq!(0, annotations.namespace_external_ids(&clippy));
+        assert_eq!(namespaced, annotations);
+    }
+
+    #[test]
+    fn overflow() {
+        let namespace = ExternalIdNamespace::new("semgrep").unwrap();
+        let fits = "X".repeat(EXTERNAL_ID_LIMIT - "semgrep/".len());
+        assert_eq!(format!("semgrep/{fits}"), namespace.id(&fits));
+
+        let long = "X".repeat(EXTERNAL_ID_LIMIT);
+        let mut annotations = Annotations::new(vec![annotation(Some(&long))]);
+        assert_eq!(1, annotations.namespace_external_ids(&namespace));
+        let id = annotations.annotations[0].external_id.clone().unwrap();
+        assert_eq!(format!("semgrep/{}", fingerprint(&[&long])), id);
+        assert!(annotations.annotations[0].validate_fields().is_ok());
+
+        assert_eq!(0, annotations.namespace_external_ids(&namespace));
+        assert_eq!(
+            Some(id.as_str()),
+            annotations.annotations[0].external_id.as_deref()
+        );
+    }
+
+    #[test]
+    fn invalid_tool() {
+        assert!(ExternalIdNamespace::new("").is_err());
+        assert!(ExternalIdNamespace::new("a/b").is_err());
+        assert!(ExternalIdNamespace::new(&"x".repeat(EXTERNAL_ID_LIMIT)).is_err());
+    }
+
+    #[test]
+    fn partition() {
+        let annotations = Annotations::new(vec![
+            annotation(Some("clippy/1")),
+            annotation(Some("semgrep/1")),
+            annotation(Some("clippy/2")),
+            annotation(Some("plain")),
+            annotation(None),
+        ]);
+        let partitions = annotations.partition_by_namespace();
+        assert_eq!(
+            vec!["", "clippy", "semgrep"],
+            partitions.keys().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(2, partitions["clippy"].len());
+        assert_eq!(1, partitions["semgrep"].len());
+        assert_eq!(2, partitions[""].len());
+    }
+}
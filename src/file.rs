@@ -0,0 +1,172 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+use crate::report::Report;
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path).map_err(io_error(path))?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// Writes `value` as JSON to a temporary file next to `path`, which is then
+/// renamed to `path`, so that `path` is never left partially written.
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| {
+            io_error(path)(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "path has no file name",
+            ))
+        })?
+        .to_owned();
+    name.push(format!(".{}.tmp", std::process::id()));
+    let temporary = path.with_file_name(name);
+
+    let result = File::create(&temporary)
+        .map_err(io_error(&temporary))
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, value)?;
+            let file = writer
+                .into_inner()
+                .map_err(|error| io_error(&temporary)(error.into_error()))?;
+            file.sync_all().map_err(io_error(&temporary))
+        })
+        .and_then(|()| fs::rename(&temporary, path).map_err(io_error(path)));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+impl Report {
+    /// Reads a report from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or is not a valid report,
+    /// or if any of the fields are longer than Bitbucket allows.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Report> {
+        let report = read_json::<Report>(path.as_ref())?;
+        report.validate_fields()?;
+        Ok(report)
+    }
+
+    /// Writes the report to a JSON file, replacing it atomically if it
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the fields are longer than Bitbucket
+    /// allows, or if the file cannot be written.
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.validate_fields()?;
+        write_json(path.as_ref(), self)
+    }
+}
+
+impl Annotations {
+    /// Reads annotations from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or does not contain valid
+    /// annotations, or if the fields of any annotation are longer than
+    /// Bitbucket allows.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Annotations> {
+        let annotations = read_json::<Annotations>(path.as_ref())?;
+        for annotation in &annotations.annotations {
+            annotation.validate_fields()?;
+        }
+        Ok(annotations)
+    }
+
+    /// Writes the annotations to a JSON file, replacing it atomically if it
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fields of any annotation are longer than
+    /// Bitbucket allows, or if the file cannot be written.
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        for annotation in &self.annotations {
+            annotation.validate_fields()?;
+        }
+        write_json(path.as_ref(), self)
+    }
+}
+
+#[cfg(test)]
+mod json_files {
+    use super::*;
+    use crate::{AnnotationBuilder, ReportBuilder, ReportResult, Severity};
+    use std::path::PathBuf;
+
+    fn error_path(error: &Error) -> Option<&PathBuf> {
+        match error {
+            Error::Io { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let path = directory.path().join("report.json");
+        let report = ReportBuilder::new("Static analysis")
+            .result(ReportResult::Pass)
+            .build()
+            .unwrap();
+        report.to_json_file(&path).unwrap();
+        assert_eq!(report, Report::from_json_file(&path).unwrap());
+
+        let path = directory.path().join("annotations.json");
+        let annotations =
+            Annotations::new(vec![AnnotationBuilder::new("Unused import", Severity::Low)
+                .path("src/lib.rs")
+                .build()
+                .unwrap()]);
+        annotations.to_json_file(&path).unwrap();
+        annotations.to_json_file(&path).unwrap();
+        assert_eq!(annotations, Annotations::from_json_file(&path).unwrap());
+
+        assert_eq!(2, fs::read_dir(directory.path()).unwrap().count());
+    }
+
+    #[test]
+    fn missing_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("missing.json");
+        let error = Report::from_json_file(&path).unwrap_err();
+        assert_eq!(Some(&path), error_path(&error));
+        assert!(error.to_string().contains("missing.json"), "{error}");
+
+        let path = directory.path().join("missing").join("annotations.json");
+        let error = Annotations::new(vec![]).to_json_file(&path).unwrap_err();
+        assert!(error_path(&error).is_some());
+    }
+
+    #[test]
+    fn malformed_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("annotations.json");
+        fs::write(&path, "{\"annotations\": [").unwrap();
+        let result = Annotations::from_json_file(&path);
+        assert!(matches!(result, Err(Error::SerdeError(_))));
+    }
+}
@@ -0,0 +1,645 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotation, AnnotationBuilder, Annotations, Severity, Type};
+use crate::error::{Error, Result};
+use crate::paths::relativize;
+#[cfg(feature = "regex")]
+use crate::redact::Redactor;
+use crate::rule::rule_prefix;
+
+/// A finding reported by an analysis tool, before any policy such as
+/// severity mapping or path relativization has been applied.
+///
+/// Converters produce findings, and a [`FindingMapper`] turns them into
+/// annotations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The name of the tool that reported the finding.
+    pub tool: String,
+
+    /// The ID of the rule or check that produced the finding.
+    pub rule_id: Option<String>,
+
+    /// The message as reported by the tool.
+    pub message: String,
+
+    /// The level of the finding as reported by the tool, e.g. `warning`.
+    pub level: String,
+
+    /// The path of the file the finding applies to, as reported by the tool.
+    pub path: Option<String>,
+
+    /// The line the finding applies to.
+    pub line: Option<u32>,
+
+    /// A URL with more information about the finding.
+    pub link: Option<String>,
+
+    /// The severity derived from the tool's own severity scale, if any.
+    pub severity: Option<Severity>,
+
+    /// The type of the finding, if known.
+    pub finding_type: Option<Type>,
+
+    /// A stable identifier of the finding provided by the tool.
+    pub fingerprint: Option<String>,
+}
+
+impl Finding {
+    /// Constructs a finding reported by `tool` with a message and level.
+    pub fn new<T, M, L>(tool: T, message: M, level: L) -> Self
+    where
+        T: Into<String>,
+        M: Into<String>,
+        L: Into<String>,
+    {
+        Finding {
+            tool: tool.into(),
+            rule_id: None,
+            message: message.into(),
+            level: level.into(),
+            path: None,
+            line: None,
+            link: None,
+            severity: None,
+            finding_type: None,
+            fingerprint: None,
+        }
+    }
+}
+
+/// A collection of findings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Findings {
+    findings: Vec<Finding>,
+}
+
+impl Findings {
+    pub fn new<T: Into<Vec<Finding>>>(findings: T) -> Self {
+        Findings {
+            findings: findings.into(),
+        }
+    }
+
+    /// Adds a finding to the collection.
+    pub fn push(&mut self, finding: Finding) {
+        self.findings.push(finding);
+    }
+
+    /// Returns an iterator over the findings.
+    pub fn iter(&self) -> std::slice::Iter<'_, Finding> {
+        self.findings.iter()
+    }
+
+    /// Returns the number of findings.
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Returns `true` if there are no findings.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl FromIterator<Finding> for Findings {
+    fn from_iter<I: IntoIterator<Item = Finding>>(iter: I) -> Self {
+        Findings::new(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl IntoIterator for Findings {
+    type Item = Finding;
+    type IntoIter = std::vec::IntoIter<Finding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.findings.into_iter()
+    }
+}
+
+impl TryFrom<Findings> for Annotations {
+    type Error = Error;
+
+    /// Converts the findings using the default [`FindingMapper`].
+    fn try_from(value: Findings) -> std::result::Result<Self, Self::Error> {
+        FindingMapper::new().map_all(value)
+    }
+}
+
+/// Decides the severity of findings.
+///
+/// The severity of a finding is resolved in the following order:
+///
+/// 1. the severity configured for its exact rule ID,
+/// 2. the severity of the most specific glob pattern matching its rule ID,
+/// 3. the severity configured for its tool,
+/// 4. the severity derived by the converter from the tool's own scale,
+/// 5. the default severity of the map.
+///
+/// Rule patterns may contain `*`, matching any number of characters, and
+/// `?`, matching a single character. When several patterns match, the one
+/// with the most literal characters is the most specific, and between equally
+/// specific patterns the alphabetically first one wins.
+///
+/// A map can be deserialized from a configuration file, e.g. in JSON:
+///
+/// ```json
+/// {
+///     "default": "LOW",
+///     "tools": {"Brakeman": "HIGH"},
+///     "rules": {"E*": "HIGH", "E501": "LOW"}
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SeverityMap {
+    default: Severity,
+    tools: BTreeMap<String, Severity>,
+    rules: BTreeMap<String, Severity>,
+}
+
+impl Default for SeverityMap {
+    /// Constructs a map with the default severity [`Severity::Medium`].
+    fn default() -> Self {
+        SeverityMap::new(Severity::Medium)
+    }
+}
+
+impl SeverityMap {
+    /// Constructs a map without rules, using `default` for findings that have
+    /// no severity.
+    pub fn new(default: Severity) -> Self {
+        SeverityMap {
+            default,
+            tools: BTreeMap::new(),
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Constructs a builder for a map with the default severity
+    /// [`Severity::Medium`].
+    pub fn builder() -> SeverityMapBuilder {
+        SeverityMapBuilder {
+            map: SeverityMap::default(),
+        }
+    }
+
+    /// Returns the severity of `finding`.
+    pub fn resolve(&self, finding: &Finding) -> Severity {
+        finding
+            .rule_id
+            .as_deref()
+            .and_then(|rule_id| self.rule(rule_id))
+            .or_else(|| self.tools.get(&finding.tool).copied())
+            .or(finding.severity)
+            .unwrap_or(self.default)
+    }
+
+    fn rule(&self, rule_id: &str) -> Option<Severity> {
+        if let Some(severity) = self.rules.get(rule_id) {
+            return Some(*severity);
+        }
+
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| is_glob(pattern) && glob_matches(pattern, rule_id))
+            .max_by(|(a, _), (b, _)| literals(a).cmp(&literals(b)).then_with(|| b.cmp(a)))
+            .map(|(_, severity)| *severity)
+    }
+}
+
+/// Builds a [`SeverityMap`].
+#[derive(Debug, Clone)]
+pub struct SeverityMapBuilder {
+    map: SeverityMap,
+}
+
+impl SeverityMapBuilder {
+    /// Sets the severity of findings that get no severity otherwise.
+    pub fn default(mut self, severity: Severity) -> Self {
+        self.map.default = severity;
+        self
+    }
+
+    /// Sets the severity of all findings of `tool`, overriding the severities
+    /// derived by its converter.
+    pub fn tool<T: Into<String>>(mut self, tool: T, severity: Severity) -> Self {
+        self.map.tools.insert(tool.into(), severity);
+        self
+    }
+
+    /// Sets the severity of the findings whose rule ID is `pattern`, or
+    /// matches it if it is a glob pattern.
+    pub fn rule<T: Into<String>>(mut self, pattern: T, severity: Severity) -> Self {
+        self.map.rules.insert(pattern.into(), severity);
+        self
+    }
+
+    /// Creates the map.
+    pub fn build(self) -> SeverityMap {
+        self.map
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+fn literals(pattern: &str) -> usize {
+    pattern.chars().filter(|c| !matches!(c, '*' | '?')).count()
+}
+
+/// Matches `text` against a glob pattern supporting `*` and `?`.
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Turns findings into annotations, applying the policy shared by all
+/// converters.
+///
+/// The mapper decides the severity through a [`SeverityMap`], makes paths
+/// relative to the repository root, renders the message through a template
+/// and truncates it to [`MESSAGE_LIMIT`], and optionally fingerprints findings
+/// that have none to give their annotations a stable external ID.
+///
+/// [`MESSAGE_LIMIT`]: crate::MESSAGE_LIMIT
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FindingMapper {
+    severity_map: SeverityMap,
+    root: Option<PathBuf>,
+    template: Option<String>,
+    fingerprints: bool,
+    #[cfg(feature = "regex")]
+    redactor: Option<Redactor>,
+}
+
+impl FindingMapper {
+    /// Constructs a mapper with the default severity map, no root, the
+    /// default message template and fingerprinting disabled.
+    pub fn new() -> Self {
+        FindingMapper::default()
+    }
+
+    /// Sets the severity map.
+    pub fn severity_map(mut self, severity_map: SeverityMap) -> Self {
+        self.severity_map = severity_map;
+        self
+    }
+
+    /// Sets the root that absolute paths are made relative to.
+    ///
+    /// Paths outside of `root` are kept as they are.
+    pub fn root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Sets the template used to render annotation messages.
+    ///
+    /// The placeholders `{tool}`, `{rule}`, `{level}` and `{message}` are
+    /// replaced by the respective fields of the finding, and a missing rule ID
+    /// is replaced by an empty string. Without a template, the message is
+    /// prefixed with the tool and the rule ID as by
+    /// [`AnnotationBuilder::rule`], i.e. `[{tool}::{rule}] {message}`, if the
+    /// finding has a rule ID, so that [`Annotation::rule`] can read them.
+    /// Characters that are not allowed in the prefix are replaced by `_`.
+    pub fn message_template<T: Into<String>>(mut self, template: T) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Sets whether findings without a fingerprint get one computed from their
+    /// tool, rule ID, path and message.
+    ///
+    /// The fingerprint ignores the line, so it remains stable when code moves.
+    pub fn fingerprints(mut self, enabled: bool) -> Self {
+        self.fingerprints = enabled;
+        self
+    }
+
+    /// Sets the redactor that masks secrets in messages and links, before
+    /// messages are truncated.
+    #[cfg(feature = "regex")]
+    pub fn redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Turns a finding into an annotation.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fingerprint of the finding is longer than
+    /// [`EXTERNAL_ID_LIMIT`].
+    ///
+    /// [`EXTERNAL_ID_LIMIT`]: crate::EXTERNAL_ID_LIMIT
+    pub fn map(&self, finding: Finding) -> Result<Annotation> {
+        let severity = self.severity_map.resolve(&finding);
+        let message = self.render_message(&finding);
+        let path = match (&self.root, finding.path) {
+            (Some(root), Some(path)) => Some(relativize(&path, root)),
+            (_, path) => path,
+        };
+        let fingerprint = match finding.fingerprint {
+            Some(fingerprint) => Some(fingerprint),
+            None if self.fingerprints => Some(fingerprint(&[
+                &finding.tool,
+                finding.rule_id.as_deref().unwrap_or_default(),
+                path.as_deref().unwrap_or_default(),
+                &finding.message,
+            ])),
+            None => None,
+        };
+
+        let mut builder = AnnotationBuilder::new(message, severity).truncate_message();
+        if let Some(finding_type) = finding.finding_type {
+            builder = builder.annotation_type(finding_type);
+        }
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        if let Some(line) = finding.line {
+            builder = builder.line(line);
+        }
+        if let Some(link) = finding.link {
+            builder = builder.link(link);
+        }
+        if let Some(fingerprint) = fingerprint {
+            builder = builder.external_id(fingerprint);
+        }
+        #[cfg(feature = "regex")]
+        if let Some(redactor) = &self.redactor {
+            builder = builder.redact(redactor);
+        }
+        builder.build()
+    }
+
+    /// Turns all findings into annotations.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the findings cannot be turned into an
+    /// annotation, see [`map`](FindingMapper::map).
+    pub fn map_all<I: IntoIterator<Item = Finding>>(&self, findings: I) -> Result<Annotations> {
+        let annotations = findings
+            .into_iter()
+            .map(|finding| self.map(finding))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Annotations::new(annotations))
+    }
+
+    fn render_message(&self, finding: &Finding) -> String {
+        let rule = finding.rule_id.as_deref().unwrap_or_default();
+        let Some(template) = &self.template else {
+            return match rule_prefix(&finding.tool, rule) {
+                Some(prefix) => format!("{prefix}{}", finding.message),
+                None => finding.message.clone(),
+            };
+        };
+
+        let mut rendered = String::with_capacity(template.len() + finding.message.len());
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find('}').map_or(rest.len(), |end| end + 1);
+            match &rest[..end] {
+                "{tool}" => rendered.push_str(&finding.tool),
+                "{rule}" => rendered.push_str(rule),
+                "{level}" => rendered.push_str(&finding.level),
+                "{message}" => rendered.push_str(&finding.message),
+                other => rendered.push_str(other),
+            }
+            rest = &rest[end..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+/// Computes a 64-bit FNV-1a hash of `parts` as a hexadecimal string.
+///
+/// The parts are separated by a zero byte so that moving characters between
+/// adjacent parts changes the hash.
+pub(crate) fn fingerprint(parts: &[&str]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = parts
+        .iter()
+        .flat_map(|part| part.bytes().chain(std::iter::once(0)))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod mapping {
+    use super::*;
+    use serde_json::json;
+
+    fn finding() -> Finding {
+        Finding {
+            rule_id: Some("needless_clone".to_owned()),
+            path: Some("/builds/app/src/lib.rs".to_owned()),
+            line: Some(42),
+            link: Some("https://rust-lang.github.io/rust-clippy/".to_owned()),
+            severity: Some(Severity::Low),
+            finding_type: Some(Type::CodeSmell),
+            ..Finding::new("clippy", "redundant clone", "warning")
+        }
+    }
+
+    #[test]
+    fn default_mapper() {
+        let expected = json!({
+            "message": "[clippy::needless_clone] redundant clone",
+            "severity": "LOW",
+            "type": "CODE_SMELL",
+            "path": "/builds/app/src/lib.rs",
+            "line": 42,
+            "link": "https://rust-lang.github.io/rust-clippy/"
+        });
+        let annotation = FindingMapper::new().map(finding()).unwrap();
+        assert_eq!(expected, serde_json::to_value(annotation).unwrap());
+    }
+
+    #[test]
+    fn configured_mapper() {
+        let mapper = FindingMapper::new()
+            .severity_map(
+                SeverityMap::builder()
+                    .default(Severity::Low)
+                    .rule("needless_clone", Severity::High)
+                    .build(),
+            )
+            .root("/builds/app")
+            .message_template("[{tool}::{rule}] {message} ({level})")
+            .fingerprints(true);
+        let expected = json!({
+            "message": "[clippy::needless_clone] redundant clone (warning)",
+            "severity": "HIGH",
+            "type": "CODE_SMELL",
+            "path": "src/lib.rs",
+            "line": 42,
+            "link": "https://rust-lang.github.io/rust-clippy/",
+            "externalId": fingerprint(&["clippy", "needless_clone", "src/lib.rs", "redundant clone"])
+        });
+        let annotation = mapper.map(finding()).unwrap();
+        assert_eq!(expected, serde_json::to_value(annotation).unwrap());
+    }
+
+    fn rule(rule_id: &str) -> Finding {
+        Finding {
+            rule_id: Some(rule_id.to_owned()),
+            ..finding()
+        }
+    }
+
+    #[test]
+    fn severity_resolution() {
+        let map = SeverityMap::new(Severity::High);
+        assert_eq!(Severity::Low, map.resolve(&finding()));
+
+        let unrated = Finding {
+            severity: None,
+            ..finding()
+        };
+        assert_eq!(Severity::High, map.resolve(&unrated));
+    }
+
+    #[test]
+    fn severity_resolution_order() {
+        let map = SeverityMap::builder()
+            .default(Severity::High)
+            .tool("clippy", Severity::Medium)
+            .rule("needless_*", Severity::Low)
+            .rule("needless_clone", Severity::High)
+            .build();
+        assert_eq!(Severity::High, map.resolve(&rule("needless_clone")));
+        assert_eq!(Severity::Low, map.resolve(&rule("needless_return")));
+        assert_eq!(Severity::Medium, map.resolve(&rule("unwrap_used")));
+
+        let other_tool = Finding {
+            tool: "rustc".to_owned(),
+            ..rule("dead_code")
+        };
+        assert_eq!(Severity::Low, map.resolve(&other_tool));
+        let unrated = Finding {
+            severity: None,
+            ..other_tool
+        };
+        assert_eq!(Severity::High, map.resolve(&unrated));
+    }
+
+    #[test]
+    fn ambiguous_glob() {
+        let map = SeverityMap::builder()
+            .rule("E*", Severity::High)
+            .rule("E5*", Severity::Low)
+            .rule("E?0?", Severity::High)
+            .rule("*01", Severity::Medium)
+            .build();
+        assert_eq!(Severity::High, map.resolve(&rule("E1")));
+        assert_eq!(Severity::High, map.resolve(&rule("E203")));
+        // "E5*", "E?0?" and "*01" have two literal characters each, so the
+        // alphabetically first pattern decides.
+        assert_eq!(Severity::Medium, map.resolve(&rule("E501")));
+        assert_eq!(Severity::Low, map.resolve(&rule("E503")));
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(glob_matches("a?c", "abc"));
+        assert!(!glob_matches("a?c", "ac"));
+        assert!(!glob_matches("a*b", "acbd"));
+    }
+
+    #[test]
+    fn severity_map_from_config() {
+        let config = json!({
+            "default": "LOW",
+            "tools": {"Brakeman": "HIGH"},
+            "rules": {"E*": "HIGH"}
+        });
+        let expected = SeverityMap::builder()
+            .default(Severity::Low)
+            .tool("Brakeman", Severity::High)
+            .rule("E*", Severity::High)
+            .build();
+        let map: SeverityMap = serde_json::from_value(config.clone()).unwrap();
+        assert_eq!(expected, map);
+        assert_eq!(config, serde_json::to_value(&map).unwrap());
+
+        let partial: SeverityMap = serde_json::from_value(json!({"rules": {}})).unwrap();
+        assert_eq!(SeverityMap::default(), partial);
+    }
+
+    #[test]
+    fn fingerprint_ignores_line() {
+        let mapper = FindingMapper::new().fingerprints(true);
+        let moved = Finding {
+            line: Some(7),
+            ..finding()
+        };
+        let first = mapper.map(finding()).unwrap();
+        let second = mapper.map(moved).unwrap();
+        assert!(first.external_id.is_some());
+        assert_eq!(first.external_id, second.external_id);
+    }
+
+    #[test]
+    fn tool_fingerprint_is_kept() {
+        let finding = Finding {
+            fingerprint: Some("abc123".to_owned()),
+            ..finding()
+        };
+        let annotation = FindingMapper::new().fingerprints(true).map(finding);
+        assert_eq!(Some("abc123"), annotation.unwrap().external_id.as_deref());
+    }
+
+    #[test]
+    fn template_placeholders() {
+        let mapper = FindingMapper::new().message_template("{unknown} {message} {");
+        let annotation = mapper.map(finding()).unwrap();
+        assert_eq!("{unknown} redundant clone {", annotation.message);
+    }
+
+    #[test]
+    fn findings_into_annotations() {
+        let findings = Findings::from_iter([finding(), finding()]);
+        let annotations = Annotations::try_from(findings).unwrap();
+        assert_eq!(2, annotations.annotations.len());
+    }
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::annotation::{Annotation, Annotations};
+use crate::canonical::{canonical, sort_array};
+use crate::report::Report;
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    // Reports and annotations only have string keys, so this cannot fail.
+    serde_json::to_value(value).expect("payload is serializable")
+}
+
+fn digest(value: &Value) -> String {
+    let mut out = String::new();
+    canonical(value, &mut out);
+    Sha256::digest(out.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+impl Report {
+    /// Returns a fingerprint of the content of the report, e.g. to skip
+    /// publishing a report that has not changed.
+    ///
+    /// The fingerprint is the lowercase hex SHA-256 digest of a canonical JSON
+    /// serialization of the report, which is built by these rules:
+    ///
+    /// - object keys are sorted by their UTF-8 bytes, and fields that are not
+    ///   set are omitted,
+    /// - there is no whitespace between tokens, and strings are escaped as by
+    ///   `serde_json`,
+    /// - numbers with an integral value are written as integers, so `2.0` and
+    ///   `2` are the same, other numbers in their shortest round-trip form,
+    /// - the data fields are sorted by their canonical serialization.
+    ///
+    /// Sorting the data fields deliberately ignores their order, even though
+    /// Bitbucket displays them in that order and
+    /// [`canonical_json`](Report::canonical_json) keeps it, so that a report
+    /// whose only change is the order of its data fields counts as unchanged
+    /// and is not published again.
+    ///
+    /// The fingerprint therefore only depends on the content of the report,
+    /// not on how it was built or on the platform. Changing these rules
+    /// changes the fingerprints, which is a breaking change.
+    pub fn fingerprint(&self) -> String {
+        let mut value = to_value(self);
+        sort_array(&mut value, "/data");
+        digest(&value)
+    }
+}
+
+impl Annotations {
+    /// Returns a fingerprint of the annotations, which does not depend on
+    /// their order.
+    ///
+    /// It is computed by the rules of [`Report::fingerprint`], with the
+    /// annotations sorted by their canonical serialization.
+    pub fn fingerprint(&self) -> String {
+        let mut value = to_value(self);
+        sort_array(&mut value, "/annotations");
+        digest(&value)
+    }
+}
+
+/// Returns a fingerprint of `annotation` that does not depend on its line or
+/// link, which often contains the line, so that it survives unrelated edits
+/// of the file.
+///
+/// It is computed by the rules of [`Report::fingerprint`].
+pub(crate) fn location_independent(annotation: &Annotation) -> String {
+    let mut value = to_value(annotation);
+    if let Value::Object(fields) = &mut value {
+        fields.remove("line");
+        fields.remove("link");
+    }
+    digest(&value)
+}
+
+#[cfg(test)]
+mod fingerprints {
+    use super::*;
+    use crate::{
+        Annotation, AnnotationBuilder, Data, Number, Parameter, ReportBuilder, ReportResult,
+        Severity, Type,
+    };
+
+    fn report(data: Vec<Data>) -> Report {
+        ReportBuilder::new("Static analysis")
+            .result(ReportResult::Pass)
+            .reporter("clippy")
+            .data(data)
+            .build()
+            .unwrap()
+    }
+
+    fn data() -> Vec<Data> {
+        vec![
+            Data {
+                title: "Warnings".to_owned(),
+                parameter: Parameter::Number(3.into()),
+            },
+            Data {
+                title: "Safe to merge?".to_owned(),
+                parameter: Parameter::Boolean(true),
+            },
+        ]
+    }
+
+    fn annotations() -> Vec<Annotation> {
+        vec![
+            AnnotationBuilder::new("Possible panic", Severity::High)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .line(40)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Unused import", Severity::Low)
+                .path("src/main.rs")
+                .build()
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn pinned() {
+        assert_eq!(
+            "4de716c72c2b3416e8cbe42d4e0a69442894d8ae425dbe377e2586b03ac516b5",
+            report(data()).fingerprint()
+        );
+        assert_eq!(
+            "bb163624489eca6314c9fe153da75a053cc47f473d22258628695b842fe38b1b",
+            Annotations::new(annotations()).fingerprint()
+        );
+    }
+
+    #[test]
+    fn annotation_order() {
+        let mut reversed = annotations();
+        reversed.reverse();
+        assert_eq!(
+            Annotations::new(annotations()).fingerprint(),
+            Annotations::new(reversed).fingerprint()
+        );
+    }
+
+    #[test]
+    fn canonical_form() {
+        let mut reversed = data();
+        reversed.reverse();
+        assert_eq!(report(data()).fingerprint(), report(reversed).fingerprint());
+
+        let float = report(vec![Data {
+            title: "Warnings".to_owned(),
+            parameter: Parameter::Number(Number::from_f64(3.0).unwrap()),
+        }]);
+        let integer = report(vec![Data {
+            title: "Warnings".to_owned(),
+            parameter: Parameter::Number(3.into()),
+        }]);
+        assert_eq!(float.fingerprint(), integer.fingerprint());
+    }
+
+    #[test]
+    fn data_order() {
+        let mut reordered = data();
+        reordered.swap(0, 1);
+        let (original, reordered) = (report(data()), report(reordered));
+        assert_ne!(
+            original.canonical_json().unwrap(),
+            reordered.canonical_json().unwrap()
+        );
+        assert_eq!(original.fingerprint(), reordered.fingerprint());
+    }
+
+    #[test]
+    fn content_changes() {
+        let mut changed = annotations();
+        changed[1] = AnnotationBuilder::new("Unused import", Severity::Medium)
+            .path("src/main.rs")
+            .build()
+            .unwrap();
+        assert_ne!(
+            Annotations::new(annotations()).fingerprint(),
+            Annotations::new(changed).fingerprint()
+        );
+    }
+}
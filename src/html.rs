@@ -0,0 +1,304 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::annotation::{Annotation, Annotations, Severity};
+use crate::render::{render_date, render_duration};
+use crate::report::{Parameter, Report, ReportResult};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #172b4d; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #dfe1e6; padding: 4px 8px; text-align: left; vertical-align: top; }
+th[data-sort] { cursor: pointer; }
+.badge { border-radius: 3px; padding: 1px 6px; color: #fff; font-size: 0.85em; }
+.high { background: #de350b; }
+.medium { background: #ff991f; }
+.low { background: #0065ff; }
+.pass { color: #00875a; }
+.fail { color: #de350b; }
+";
+
+// Sorts the rows of a table by the clicked column, toggling the order on
+// every click. Kept free of '<' and '&' so the page stays well-formed XML.
+const SCRIPT: &str = r#"
+document.querySelectorAll("th[data-sort]").forEach(function (header) {
+  header.addEventListener("click", function () {
+    var body = header.closest("table").tBodies[0];
+    var index = header.cellIndex;
+    var ascending = header.dataset.order !== "asc";
+    header.dataset.order = ascending ? "asc" : "desc";
+    var value = function (row) {
+      var cell = row.cells[index];
+      return cell.dataset.value || cell.textContent;
+    };
+    Array.from(body.rows).sort(function (a, b) {
+      var order = header.dataset.sort === "number"
+        ? Number(value(a)) - Number(value(b))
+        : value(a).localeCompare(value(b));
+      return ascending ? order : -order;
+    }).forEach(function (row) {
+      body.appendChild(row);
+    });
+  });
+});
+"#;
+
+/// Renders a report and its annotations as a self-contained HTML page, e.g.
+/// as a build artifact when Bitbucket cannot be reached.
+///
+/// The page shows the report with its data fields, the number of annotations
+/// per severity, and a table of annotations for each file that can be sorted
+/// by clicking a column header. Styles and the script for sorting are inline,
+/// so the page does not load any external resources.
+///
+/// Every string taken from the report or annotations is HTML-escaped, and
+/// links are only rendered if they are HTTP or HTTPS URLs. The page is
+/// well-formed XML as well.
+pub fn render_html(report: &Report, annotations: &Annotations) -> String {
+    let title = escape(&report.title);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n\
+         <title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+
+    if let Some(details) = &report.details {
+        writeln!(html, "<p class=\"details\">{}</p>", escape(details)).unwrap();
+    }
+    if let Some(result) = &report.result {
+        let (class, result) = match result {
+            ReportResult::Pass => ("pass", "Passed"),
+            ReportResult::Fail => ("fail", "Failed"),
+        };
+        writeln!(html, "<p class=\"result {class}\">{result}</p>").unwrap();
+    }
+    if let Some(data) = report.data.as_deref().filter(|data| !data.is_empty()) {
+        html.push_str("<table class=\"data\">\n<tbody>\n");
+        for data in data {
+            writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(&data.title),
+                render_parameter(&data.parameter)
+            )
+            .unwrap();
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    let annotations = &annotations.annotations;
+    html.push_str("<p class=\"summary\">");
+    for severity in [Severity::High, Severity::Medium, Severity::Low] {
+        let count = annotations
+            .iter()
+            .filter(|annotation| annotation.severity == severity)
+            .count();
+        write!(
+            html,
+            "{} ",
+            badge(severity, &format!("{count} {}", severity.label()))
+        )
+        .unwrap();
+    }
+    html.truncate(html.trim_end().len());
+    html.push_str("</p>\n");
+
+    let mut files = BTreeMap::<Option<&str>, Vec<&Annotation>>::new();
+    for annotation in annotations {
+        files
+            .entry(annotation.path.as_deref())
+            .or_default()
+            .push(annotation);
+    }
+
+    for (path, mut group) in files {
+        group.sort_by_key(|annotation| (Reverse(annotation.severity), annotation.line));
+        match path {
+            Some(path) => writeln!(html, "<h2><code>{}</code></h2>", escape(path)).unwrap(),
+            None => html.push_str("<h2>Repository</h2>\n"),
+        }
+        html.push_str(
+            "<table class=\"annotations\">\n<thead>\n<tr><th data-sort=\"number\">Severity</th>\
+             <th data-sort=\"number\">Line</th><th data-sort=\"text\">Message</th></tr>\n\
+             </thead>\n<tbody>\n",
+        );
+        for annotation in group {
+            let line = annotation
+                .line
+                .filter(|line| *line > 0)
+                .map_or_else(String::new, |line| line.to_string());
+            let mut message = escape(&annotation.message);
+            if let Some(link) = annotation.link.as_deref().filter(|link| is_http(link)) {
+                write!(message, " <a href=\"{}\">Details</a>", escape(link)).unwrap();
+            }
+            writeln!(
+                html,
+                "<tr><td data-value=\"{}\">{}</td><td data-value=\"{line}\">{line}</td><td>{message}</td></tr>",
+                annotation.severity as u8,
+                badge(annotation.severity, annotation.severity.label()),
+            )
+            .unwrap();
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    write!(html, "<script>{SCRIPT}</script>\n</body>\n</html>\n").unwrap();
+    html
+}
+
+fn badge(severity: Severity, text: &str) -> String {
+    let class = match severity {
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+    };
+    format!("<span class=\"badge {class}\">{}</span>", escape(text))
+}
+
+fn render_parameter(parameter: &Parameter) -> String {
+    match parameter {
+        Parameter::Boolean(true) => "Yes".to_owned(),
+        Parameter::Boolean(false) => "No".to_owned(),
+        Parameter::Date(millis) => render_date(*millis),
+        Parameter::Duration(millis) => render_duration(*millis),
+        Parameter::Link { linktext, href } if is_http(href) => {
+            format!("<a href=\"{}\">{}</a>", escape(href), escape(linktext))
+        }
+        Parameter::Link { linktext, .. } => escape(linktext),
+        Parameter::Number(number) => number.to_string(),
+        Parameter::Percentage(percentage) => format!("{percentage}%"),
+        Parameter::Text(text) => escape(text),
+    }
+}
+
+fn is_http(link: &str) -> bool {
+    let link = link.to_ascii_lowercase();
+    link.starts_with("https://") || link.starts_with("http://")
+}
+
+/// Escapes text for use in element content as well as quoted attributes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod rendering {
+    use super::*;
+    use crate::{AnnotationBuilder, Data, ReportBuilder};
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    fn report() -> Report {
+        ReportBuilder::new("Static analysis")
+            .details("Findings of \"lint\"")
+            .result(ReportResult::Pass)
+            .data(vec![
+                Data {
+                    title: "Line coverage".to_owned(),
+                    parameter: Parameter::Percentage(57),
+                },
+                Data {
+                    title: "Pipeline".to_owned(),
+                    parameter: Parameter::Link {
+                        linktext: "Build #12".to_owned(),
+                        href: "https://ci.example.com/12?a=1&b=2".to_owned(),
+                    },
+                },
+            ])
+            .build()
+            .unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            AnnotationBuilder::new("Unused import", Severity::Low)
+                .path("src/lib.rs")
+                .line(3)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("<script>alert('x')</script>", Severity::High)
+                .path("src/lib.rs")
+                .line(40)
+                .link("javascript:alert(1)")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Build is slow", Severity::Medium)
+                .link("https://ci.example.com/12")
+                .build()
+                .unwrap(),
+        ])
+    }
+
+    /// Returns the names of the elements in document order, failing if the
+    /// document is not well-formed.
+    fn elements(html: &str) -> Vec<String> {
+        let mut reader = Reader::from_str(html);
+        reader.config_mut().check_end_names = true;
+        let mut stack = Vec::new();
+        let mut elements = Vec::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) => {
+                    let name = element.name().as_ref().to_owned();
+                    stack.push(name.clone());
+                    elements.push(name);
+                }
+                Event::Empty(element) => elements.push(element.name().as_ref().to_owned()),
+                Event::End(element) => {
+                    assert_eq!(stack.pop().as_deref(), Some(element.name().as_ref()));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        assert!(stack.is_empty());
+        elements
+    }
+
+    #[test]
+    fn well_formed() {
+        let html = render_html(&report(), &annotations());
+        let elements = elements(&html);
+        assert_eq!(Some("html"), elements.first().map(String::as_str));
+        assert_eq!(2, elements.iter().filter(|name| *name == "h2").count());
+        assert_eq!(1, elements.iter().filter(|name| *name == "script").count());
+    }
+
+    #[test]
+    fn escaping() {
+        let html = render_html(&report(), &annotations());
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</td>"));
+        assert!(html.contains("Findings of &quot;lint&quot;"));
+        assert!(html.contains("<a href=\"https://ci.example.com/12?a=1&amp;b=2\">Build #12</a>"));
+    }
+
+    #[test]
+    fn links() {
+        let html = render_html(&report(), &annotations());
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("Build is slow <a href=\"https://ci.example.com/12\">Details</a>"));
+    }
+
+    #[test]
+    fn grouped_by_file() {
+        let html = render_html(&report(), &annotations());
+        let repository = html.find("<h2>Repository</h2>").unwrap();
+        let file = html.find("<h2><code>src/lib.rs</code></h2>").unwrap();
+        let high = html.find("&lt;script&gt;").unwrap();
+        let low = html.find("Unused import").unwrap();
+        assert!(repository < file && file < high && high < low);
+        assert!(html.contains("<span class=\"badge high\">1 High</span>"));
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+use crate::finding::{Finding, FindingMapper, SeverityMap};
+
+/// Base URL of the documentation of kube-linter's built-in checks.
+pub const KUBE_LINTER_DOCS_URL: &str = "https://docs.kubelinter.io/#/generated/checks";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterOutput {
+    reports: Option<Vec<KubeLinterReport>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterReport {
+    diagnostic: KubeLinterDiagnostic,
+    check: String,
+    #[serde(default)]
+    remediation: String,
+    object: KubeLinterObject,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterDiagnostic {
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterObject {
+    metadata: KubeLinterMetadata,
+    k8s_object: KubeLinterK8sObject,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterMetadata {
+    file_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterK8sObject {
+    #[serde(default)]
+    namespace: String,
+    name: String,
+    group_version_kind: KubeLinterGroupVersionKind,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KubeLinterGroupVersionKind {
+    kind: String,
+}
+
+/// Converts the JSON output of `kube-linter lint --format json`.
+///
+/// kube-linter does not report line numbers, so every annotation is placed on
+/// the manifest file as a whole. The message names the check, the object the
+/// diagnostic applies to, so that objects in multi-document YAML files can be
+/// told apart, and the remediation. It is truncated to fit within
+/// [`MESSAGE_LIMIT`]. Each annotation links to the documentation of its check.
+///
+/// kube-linter does not rate its diagnostics either, so annotations get the
+/// default severity of `severity_map`, which is [`Severity::Medium`] unless
+/// configured otherwise. Rules in the map apply to check names.
+///
+/// [`Severity::Medium`]: crate::Severity::Medium
+/// [`MESSAGE_LIMIT`]: crate::MESSAGE_LIMIT
+///
+/// # Errors
+///
+/// Will return `Err` if `value` is not kube-linter JSON output.
+pub fn from_kube_linter_json(
+    value: &Value,
+    severity_map: Option<&SeverityMap>,
+) -> Result<Annotations> {
+    let output = KubeLinterOutput::deserialize(value)
+        .map_err(|error| Error::parse_source("kube-linter", error))?;
+
+    let findings = output
+        .reports
+        .unwrap_or_default()
+        .into_iter()
+        .map(|report| {
+            let object = report.object.k8s_object;
+            let name = if object.namespace.is_empty() {
+                object.name
+            } else {
+                format!("{}/{}", object.namespace, object.name)
+            };
+            let mut message = format!(
+                "{} {}: {}",
+                object.group_version_kind.kind, name, report.diagnostic.message
+            );
+            if !report.remediation.is_empty() {
+                message = format!("{message} ({})", report.remediation);
+            }
+
+            Finding {
+                link: Some(format!("{KUBE_LINTER_DOCS_URL}?id={}", report.check)),
+                rule_id: Some(report.check),
+                path: Some(report.object.metadata.file_path),
+                ..Finding::new("kube-linter", message, "")
+            }
+        });
+
+    FindingMapper::new()
+        .severity_map(severity_map.cloned().unwrap_or_default())
+        .map_all(findings)
+}
+
+#[cfg(test)]
+mod conversion {
+    use super::*;
+    use crate::{Severity, MESSAGE_LIMIT};
+    use serde_json::json;
+
+    fn report(check: &str, name: &str, message: &str, remediation: &str) -> Value {
+        json!({
+            "Diagnostic": {"Message": message},
+            "Check": check,
+            "Remediation": remediation,
+            "Object": {
+                "Metadata": {"FilePath": "deploy/app.yaml", "Raw": null},
+                "K8sObject": {
+                    "Namespace": "payments",
+                    "Name": name,
+                    "GroupVersionKind": {"Group": "apps", "Version": "v1", "Kind": "Deployment"}
+                }
+            }
+        })
+    }
+
+    fn output() -> Value {
+        json!({
+            "Checks": [],
+            "Reports": [
+                report(
+                    "no-read-only-root-fs",
+                    "api",
+                    "container \"api\" does not have a read-only root file system",
+                    "Set readOnlyRootFilesystem to true in the container securityContext."
+                ),
+                report(
+                    "run-as-non-root",
+                    "worker",
+                    "container \"worker\" is not set to runAsNonRoot",
+                    "Set runAsUser to a non-zero number and runAsNonRoot to true."
+                )
+            ],
+            "Summary": {"ChecksStatus": "Failed"}
+        })
+    }
+
+    #[test]
+    fn objects_in_one_file() {
+        let expected = json!({"annotations": [
+            {
+                "message": "[kube-linter::no-read-only-root-fs] Deployment payments/api: container \"api\" does not have a read-only root file system (Set readOnlyRootFilesystem to true in the container securityContext.)",
+                "severity": "MEDIUM",
+                "path": "deploy/app.yaml",
+                "link": "https://docs.kubelinter.io/#/generated/checks?id=no-read-only-root-fs"
+            },
+            {
+                "message": "[kube-linter::run-as-non-root] Deployment payments/worker: container \"worker\" is not set to runAsNonRoot (Set runAsUser to a non-zero number and runAsNonRoot to true.)",
+                "severity": "MEDIUM",
+                "path": "deploy/app.yaml",
+                "link": "https://docs.kubelinter.io/#/generated/checks?id=run-as-non-root"
+            }
+        ]});
+        let annotations = from_kube_linter_json(&output(), None).unwrap();
+        assert_eq!(expected, serde_json::to_value(annotations).unwrap());
+    }
+
+    #[test]
+    fn severity_override() {
+        let severity_map = SeverityMap::builder()
+            .rule("run-as-*", Severity::High)
+            .build();
+        let annotations = from_kube_linter_json(&output(), Some(&severity_map)).unwrap();
+        assert_eq!(Severity::Medium, annotations.annotations[0].severity);
+        assert_eq!(Severity::High, annotations.annotations[1].severity);
+    }
+
+    #[test]
+    fn long_remediation_is_truncated() {
+        let remediation = "X".repeat(MESSAGE_LIMIT);
+        let output = json!({"Reports": [report("check", "api", "message", &remediation)]});
+        let annotations = from_kube_linter_json(&output, None).unwrap();
+        assert_eq!(MESSAGE_LIMIT, annotations.annotations[0].message.len());
+    }
+
+    #[test]
+    fn no_reports() {
+        let annotations = from_kube_linter_json(&json!({"Reports": null}), None);
+        assert_eq!(Annotations::new(vec![]), annotations.unwrap());
+    }
+}
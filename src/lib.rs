@@ -1,8 +1,136 @@
 mod annotation;
+mod annotation_diff;
+mod annotation_ref;
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "fingerprint")]
+mod baseline;
+#[cfg(feature = "brakeman")]
+mod brakeman;
+mod budget;
+mod bundle;
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "json")]
+mod canonical;
+mod ci;
+#[cfg(feature = "coverage")]
+mod coverage;
+#[cfg(feature = "dart")]
+mod dart;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod date;
+#[cfg(feature = "diff")]
+mod diff;
 mod error;
+mod escalation;
+mod external_id;
+#[cfg(feature = "json")]
+mod file;
+mod finding;
+#[cfg(feature = "fingerprint")]
+mod fingerprint;
+#[cfg(feature = "html")]
+mod html;
+#[cfg(feature = "kubelinter")]
+mod kube_linter;
+mod link;
+mod logging;
+#[cfg(feature = "markdown")]
+mod markdown;
+#[cfg(feature = "json")]
+mod ndjson;
+mod number;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "glob")]
+mod path_filter;
+mod paths;
+mod pipeline;
+mod preset;
+mod processing;
+#[cfg(feature = "json")]
+mod reader;
+#[cfg(feature = "regex")]
+mod redact;
+mod render;
 mod report;
+mod report_diff;
+#[cfg(feature = "json")]
+mod response;
+mod result_policy;
+mod rule;
+#[cfg(feature = "glob")]
+mod severity_override;
+#[cfg(feature = "sonar")]
+mod sonar;
+mod stats;
+#[cfg(feature = "json")]
+mod stream;
+#[cfg(feature = "swiftlint")]
+mod swiftlint;
+mod timer;
+mod trend;
+mod typestate;
+mod validated;
 mod validation;
+#[cfg(feature = "yaml")]
+mod yaml;
 
 pub use crate::annotation::*;
+pub use crate::annotation_diff::*;
+pub use crate::annotation_ref::*;
+#[cfg(feature = "proptest")]
+pub use crate::arbitrary::*;
+#[cfg(feature = "fingerprint")]
+pub use crate::baseline::*;
+#[cfg(feature = "brakeman")]
+pub use crate::brakeman::*;
+pub use crate::budget::*;
+pub use crate::bundle::*;
+pub use crate::ci::*;
+#[cfg(feature = "coverage")]
+pub use crate::coverage::*;
+#[cfg(feature = "dart")]
+pub use crate::dart::*;
+#[cfg(feature = "diff")]
+pub use crate::diff::*;
 pub use crate::error::*;
+pub use crate::escalation::*;
+pub use crate::external_id::*;
+pub use crate::finding::*;
+#[cfg(feature = "html")]
+pub use crate::html::*;
+#[cfg(feature = "kubelinter")]
+pub use crate::kube_linter::*;
+pub use crate::link::*;
+#[cfg(feature = "markdown")]
+pub use crate::markdown::*;
+pub use crate::number::*;
+#[cfg(feature = "glob")]
+pub use crate::path_filter::*;
+pub use crate::paths::*;
+pub use crate::pipeline::*;
+pub use crate::preset::*;
+pub use crate::processing::*;
+#[cfg(feature = "json")]
+pub use crate::reader::*;
+#[cfg(feature = "regex")]
+pub use crate::redact::*;
 pub use crate::report::*;
+pub use crate::report_diff::*;
+#[cfg(feature = "json")]
+pub use crate::response::*;
+pub use crate::result_policy::*;
+#[cfg(feature = "glob")]
+pub use crate::severity_override::*;
+#[cfg(feature = "sonar")]
+pub use crate::sonar::*;
+pub use crate::stats::*;
+#[cfg(feature = "json")]
+pub use crate::stream::*;
+#[cfg(feature = "swiftlint")]
+pub use crate::swiftlint::*;
+pub use crate::timer::*;
+pub use crate::typestate::*;
+pub use crate::validated::*;
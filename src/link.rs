@@ -0,0 +1,265 @@
+use std::fmt::Write;
+
+use crate::annotation::{Annotation, Annotations};
+use crate::error::{Error, Result};
+
+/// What to do when an annotation lacks a value for a placeholder of a
+/// [`LinkTemplate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingValue {
+    /// Leaves the link of the annotation unchanged.
+    Skip,
+    /// Replaces the placeholder with an empty string.
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Path,
+    Line,
+    ExternalId,
+    Rule,
+}
+
+/// A URL template that links annotations to their finding in an external
+/// tool, such as `https://scanner.example.com/findings?id={external_id}` or
+/// `https://src.example.com/{path}#L{line}`.
+///
+/// The placeholders are:
+///
+/// - `{path}`, the path of the annotated file.
+/// - `{line}`, the annotated line, which is missing for file-level
+///   annotations.
+/// - `{external_id}`, the external ID of the annotation.
+/// - `{rule}`, the rule of the prefix read by [`Annotation::rule`], as in
+///   `[tool::rule] message`, which is how [`FindingMapper`] renders messages
+///   by default.
+///
+/// Substituted values are percent-encoded, except for the slashes of paths.
+///
+/// [`FindingMapper`]: crate::FindingMapper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkTemplate {
+    segments: Vec<Segment>,
+    missing: MissingValue,
+    overwrite: bool,
+}
+
+impl LinkTemplate {
+    /// Parses a template, which by default skips annotations that lack a
+    /// value for a placeholder and does not overwrite existing links.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `template` contains an unknown or unterminated
+    /// placeholder.
+    pub fn new(template: &str) -> Result<LinkTemplate> {
+        let invalid = |message: String| Error::InvalidTemplate {
+            template: template.to_owned(),
+            message,
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_owned()));
+            }
+            rest = &rest[start..];
+            let end = rest
+                .find('}')
+                .ok_or_else(|| invalid(format!("unterminated placeholder '{rest}'")))?;
+            segments.push(match &rest[..=end] {
+                "{path}" => Segment::Path,
+                "{line}" => Segment::Line,
+                "{external_id}" => Segment::ExternalId,
+                "{rule}" => Segment::Rule,
+                other => return Err(invalid(format!("unknown placeholder '{other}'"))),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_owned()));
+        }
+
+        Ok(LinkTemplate {
+            segments,
+            missing: MissingValue::Skip,
+            overwrite: false,
+        })
+    }
+
+    /// Sets what to do when an annotation lacks a value for a placeholder.
+    pub fn missing(mut self, missing: MissingValue) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Sets whether existing links are replaced.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Renders the link of `annotation`, or returns `None` if a value is
+    /// missing and missing values are skipped.
+    pub fn render(&self, annotation: &Annotation) -> Option<String> {
+        let mut link = String::new();
+        for segment in &self.segments {
+            let value = match segment {
+                Segment::Literal(literal) => {
+                    link.push_str(literal);
+                    continue;
+                }
+                Segment::Path => annotation.path.as_deref().map(|path| encode(path, "/")),
+                Segment::Line => annotation
+                    .line
+                    .filter(|line| *line > 0)
+                    .map(|line| line.to_string()),
+                Segment::ExternalId => annotation.external_id.as_deref().map(|id| encode(id, "")),
+                Segment::Rule => annotation.rule().map(|(_, rule)| encode(rule, "")),
+            };
+            match (value, self.missing) {
+                (Some(value), _) => link.push_str(&value),
+                (None, MissingValue::Empty) => {}
+                (None, MissingValue::Skip) => return None,
+            }
+        }
+        Some(link)
+    }
+}
+
+impl Annotations {
+    /// Sets the links of the annotations from `template`, returning the number
+    /// of links that were set.
+    ///
+    /// Annotations that already have a link keep it unless the template
+    /// overwrites links.
+    pub fn apply_link_template(&mut self, template: &LinkTemplate) -> usize {
+        let mut applied = 0;
+        for annotation in &mut self.annotations {
+            if annotation.link.is_some() && !template.overwrite {
+                continue;
+            }
+            if let Some(link) = template.render(annotation) {
+                annotation.link = Some(link);
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+/// Percent-encodes all bytes of `value` except unreserved characters and
+/// those in `keep`.
+fn encode(value: &str, keep: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~".contains(&byte)
+            || keep.as_bytes().contains(&byte)
+        {
+            encoded.push(char::from(byte));
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod link_template {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation() -> Annotation {
+        AnnotationBuilder::new("[clippy::unwrap_used] Used unwrap", Severity::Low)
+            .path("src/my file.rs")
+            .line(12)
+            .external_id("a/b c")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn encoding() {
+        let template = LinkTemplate::new("https://src.example.com/{path}#L{line}").unwrap();
+        assert_eq!(
+            Some("https://src.example.com/src/my%20file.rs#L12".to_owned()),
+            template.render(&annotation())
+        );
+
+        let template =
+            LinkTemplate::new("https://scanner.example.com/?id={external_id}&rule={rule}").unwrap();
+        assert_eq!(
+            Some("https://scanner.example.com/?id=a%2Fb%20c&rule=unwrap_used".to_owned()),
+            template.render(&annotation())
+        );
+    }
+
+    #[test]
+    fn missing_values() {
+        let file_level = AnnotationBuilder::new("Unused import", Severity::Low)
+            .path("src/lib.rs")
+            .build()
+            .unwrap();
+        let template = LinkTemplate::new("https://src.example.com/{path}#L{line}").unwrap();
+        assert_eq!(None, template.render(&file_level));
+
+        let template = template.missing(MissingValue::Empty);
+        assert_eq!(
+            Some("https://src.example.com/src/lib.rs#L".to_owned()),
+            template.render(&file_level)
+        );
+
+        let template = LinkTemplate::new("https://rules.example.com/{rule}").unwrap();
+        assert_eq!(None, template.render(&file_level));
+    }
+
+    #[test]
+    fn apply() {
+        let linked = AnnotationBuilder::new("Linked", Severity::Low)
+            .path("src/lib.rs")
+            .link("https://example.com/")
+            .build()
+            .unwrap();
+        let unlinked = AnnotationBuilder::new("Unlinked", Severity::Low)
+            .build()
+            .unwrap();
+        let mut annotations = Annotations::new(vec![annotation(), linked, unlinked]);
+
+        let template = LinkTemplate::new("https://src.example.com/{path}").unwrap();
+        assert_eq!(1, annotations.apply_link_template(&template));
+        let links = |annotations: &Annotations| {
+            annotations
+                .annotations
+                .iter()
+                .map(|annotation| annotation.link.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                Some("https://src.example.com/src/my%20file.rs".to_owned()),
+                Some("https://example.com/".to_owned()),
+                None
+            ],
+            links(&annotations)
+        );
+
+        let template = template.overwrite(true);
+        assert_eq!(2, annotations.apply_link_template(&template));
+        assert_eq!(
+            Some("https://src.example.com/src/lib.rs".to_owned()),
+            links(&annotations)[1]
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in ["https://example.com/{file}", "https://example.com/{path"] {
+            let result = LinkTemplate::new(template);
+            assert!(matches!(result, Err(Error::InvalidTemplate { .. })));
+        }
+    }
+}
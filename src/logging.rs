@@ -0,0 +1,162 @@
+/// The target of every event logged by this crate.
+#[cfg(feature = "log")]
+pub(crate) const TARGET: &str = "code_insights";
+
+/// Logs a warning with `log::warn!` unless `$quiet` is `true`. Without the
+/// `log` feature, nothing is logged and the arguments are not evaluated.
+#[cfg(feature = "log")]
+macro_rules! log_warn {
+    ($quiet:expr, $($arg:tt)+) => {
+        if !$quiet {
+            log::warn!(target: $crate::logging::TARGET, $($arg)+);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_warn {
+    ($quiet:expr, $($arg:tt)+) => {
+        let _ = $quiet;
+    };
+}
+
+pub(crate) use log_warn;
+
+/// Logs a debug event with `log::debug!` unless `$quiet` is `true`, for the
+/// filters.
+#[cfg(all(feature = "log", any(feature = "diff", feature = "glob")))]
+macro_rules! log_debug {
+    ($quiet:expr, $($arg:tt)+) => {
+        if !$quiet {
+            log::debug!(target: $crate::logging::TARGET, $($arg)+);
+        }
+    };
+}
+
+#[cfg(all(not(feature = "log"), any(feature = "diff", feature = "glob")))]
+macro_rules! log_debug {
+    ($quiet:expr, $($arg:tt)+) => {
+        let _ = $quiet;
+    };
+}
+
+#[cfg(any(feature = "diff", feature = "glob"))]
+pub(crate) use log_debug;
+
+#[cfg(all(test, feature = "log"))]
+mod captured_warnings {
+    use std::cell::RefCell;
+    use std::sync::Once;
+
+    use log::{Level, Log, Metadata, Record};
+
+    use crate::{AnnotationBuilder, Data, Parameter, ReportBuilder, Severity, MESSAGE_LIMIT};
+
+    thread_local! {
+        static RECORDS: RefCell<Vec<(Level, String)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Captures the events of the current thread, since tests run in
+    /// parallel but there is only one logger.
+    struct CapturingLogger;
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target() == super::TARGET
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                RECORDS.with(|records| {
+                    records
+                        .borrow_mut()
+                        .push((record.level(), record.args().to_string()))
+                });
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Runs `f` and returns the events it logged.
+    fn capture(f: impl FnOnce()) -> Vec<(Level, String)> {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&CapturingLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        RECORDS.with(|records| records.borrow_mut().clear());
+        f();
+        RECORDS.with(|records| records.take())
+    }
+
+    #[test]
+    fn truncated_message() {
+        let message = "X".repeat(MESSAGE_LIMIT + 1);
+        let records = capture(|| {
+            AnnotationBuilder::new(message.clone(), Severity::Low)
+                .truncate_message()
+                .build()
+                .unwrap();
+        });
+        assert_eq!(
+            vec![(
+                Level::Warn,
+                "truncated annotation message to 2000 bytes".to_owned()
+            )],
+            records
+        );
+
+        let records = capture(|| {
+            AnnotationBuilder::new(message, Severity::Low)
+                .truncate_message()
+                .quiet()
+                .build()
+                .unwrap();
+        });
+        assert!(records.is_empty(), "{records:?}");
+    }
+
+    #[test]
+    fn line_without_path() {
+        let records = capture(|| {
+            AnnotationBuilder::new("Message", Severity::Low)
+                .line(3)
+                .build()
+                .unwrap();
+        });
+        assert_eq!(1, records.len());
+        assert_eq!(Level::Warn, records[0].0);
+    }
+
+    #[test]
+    fn date_in_seconds() {
+        let data = |value| {
+            vec![Data {
+                title: "Built".to_owned(),
+                parameter: Parameter::Date(value),
+            }]
+        };
+        let records = capture(|| {
+            ReportBuilder::new("Title")
+                .data(data(1_582_841_968))
+                .build()
+                .unwrap();
+            ReportBuilder::new("Title")
+                .data(data(1_582_841_968_000))
+                .build()
+                .unwrap();
+        });
+        assert_eq!(1, records.len());
+        assert!(records[0].1.contains("'Built'"), "{records:?}");
+
+        let records = capture(|| {
+            ReportBuilder::new("Title")
+                .data(data(1_582_841_968))
+                .quiet()
+                .build()
+                .unwrap();
+        });
+        assert!(records.is_empty(), "{records:?}");
+    }
+}
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::annotation::{Annotation, Annotations, Severity};
+use crate::render::{render_date, render_duration};
+use crate::report::{Parameter, Report, ReportResult};
+
+/// Options for [`render_markdown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    max_rows: usize,
+    max_rows_per_file: usize,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            max_rows: 50,
+            max_rows_per_file: 10,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Constructs the default options, which list at most 50 findings in
+    /// total and at most 10 per file.
+    pub fn new() -> Self {
+        MarkdownOptions::default()
+    }
+
+    /// Sets the maximum number of findings listed in total.
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    /// Sets the maximum number of findings listed per file.
+    pub fn max_rows_per_file(mut self, max_rows_per_file: usize) -> Self {
+        self.max_rows_per_file = max_rows_per_file;
+        self
+    }
+}
+
+/// Renders a report and its annotations as GitHub Flavored Markdown, e.g. for
+/// a pull request comment or a build log.
+///
+/// The summary consists of the title and details of the report, its result, a
+/// table of its data fields and the number of annotations per severity. It is
+/// followed by a table of findings for each file, most severe first, where
+/// each location is formatted as inline code. Findings beyond the limits set
+/// in `options` are left out, and a notice states how many.
+///
+/// Text from the report and annotations is escaped, so that pipes and angle
+/// brackets cannot break the tables or inject HTML.
+pub fn render_markdown(
+    report: &Report,
+    annotations: &Annotations,
+    options: &MarkdownOptions,
+) -> String {
+    let mut markdown = format!("## {}\n", escape(&report.title));
+
+    if let Some(details) = &report.details {
+        write!(markdown, "\n{}\n", escape(details)).unwrap();
+    }
+    if let Some(result) = &report.result {
+        let result = match result {
+            ReportResult::Pass => "Passed",
+            ReportResult::Fail => "Failed",
+        };
+        write!(markdown, "\n**Result:** {result}\n").unwrap();
+    }
+    if let Some(data) = report.data.as_deref().filter(|data| !data.is_empty()) {
+        markdown.push_str("\n| Data | Value |\n| --- | --- |\n");
+        for data in data {
+            let value = render_parameter(&data.parameter);
+            writeln!(markdown, "| {} | {value} |", escape(&data.title)).unwrap();
+        }
+    }
+
+    let annotations = &annotations.annotations;
+    let count = |severity| {
+        annotations
+            .iter()
+            .filter(|annotation| annotation.severity == severity)
+            .count()
+    };
+    write!(
+        markdown,
+        "\n**Annotations:** {} high, {} medium, {} low\n",
+        count(Severity::High),
+        count(Severity::Medium),
+        count(Severity::Low)
+    )
+    .unwrap();
+
+    let mut files = BTreeMap::<Option<&str>, Vec<&Annotation>>::new();
+    for annotation in annotations {
+        files
+            .entry(annotation.path.as_deref())
+            .or_default()
+            .push(annotation);
+    }
+
+    let mut rows = 0;
+    for (path, mut findings) in files {
+        if rows == options.max_rows {
+            break;
+        }
+        findings.sort_by_key(|annotation| (Reverse(annotation.severity), annotation.line));
+        findings.truncate(options.max_rows_per_file.min(options.max_rows - rows));
+        rows += findings.len();
+
+        match path {
+            Some(path) => write!(markdown, "\n### `{}`\n", escape_code(path)).unwrap(),
+            None => markdown.push_str("\n### Repository\n"),
+        }
+        markdown.push_str("\n| Severity | Location | Message |\n| --- | --- | --- |\n");
+        for annotation in findings {
+            let severity = annotation.severity.label();
+            let location = match (path, annotation.line.filter(|line| *line > 0)) {
+                (Some(path), Some(line)) => format!("`{}:{line}`", escape_code(path)),
+                (Some(path), None) => format!("`{}`", escape_code(path)),
+                (None, _) => String::new(),
+            };
+            writeln!(
+                markdown,
+                "| {severity} | {location} | {} |",
+                escape(&annotation.message)
+            )
+            .unwrap();
+        }
+    }
+
+    let omitted = annotations.len() - rows;
+    if omitted > 0 {
+        write!(markdown, "\n_{omitted} more findings not shown._\n").unwrap();
+    }
+    markdown
+}
+
+fn render_parameter(parameter: &Parameter) -> String {
+    match parameter {
+        Parameter::Boolean(true) => "Yes".to_owned(),
+        Parameter::Boolean(false) => "No".to_owned(),
+        Parameter::Date(millis) => render_date(*millis),
+        Parameter::Duration(millis) => render_duration(*millis),
+        Parameter::Link { linktext, href } => {
+            format!("[{}]({})", escape(linktext), href.replace(' ', "%20"))
+        }
+        Parameter::Number(number) => number.to_string(),
+        Parameter::Percentage(percentage) => format!("{percentage}%"),
+        Parameter::Text(text) => escape(text),
+    }
+}
+
+/// Escapes text so that it is rendered literally inside a table cell.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '|' => escaped.push_str("\\|"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes text inside inline code, where only pipes and backticks would break
+/// the table.
+fn escape_code(text: &str) -> String {
+    text.replace('|', "\\|").replace('`', "'")
+}
+
+#[cfg(test)]
+mod rendering {
+    use super::*;
+    use crate::{AnnotationBuilder, Data, ReportBuilder};
+
+    fn report() -> Report {
+        ReportBuilder::new("Static analysis")
+            .details("Findings of <lint>")
+            .result(ReportResult::Fail)
+            .data(vec![
+                Data {
+                    title: "Line coverage".to_owned(),
+                    parameter: Parameter::Percentage(57),
+                },
+                Data {
+                    title: "Started".to_owned(),
+                    parameter: Parameter::Date(1582841968000),
+                },
+                Data {
+                    title: "Duration".to_owned(),
+                    parameter: Parameter::Duration(125_000),
+                },
+            ])
+            .build()
+            .unwrap()
+    }
+
+    fn annotation(message: &str, severity: Severity, path: &str, line: u32) -> Annotation {
+        AnnotationBuilder::new(message, severity)
+            .path(path)
+            .line(line)
+            .build()
+            .unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            annotation("Unused import", Severity::Low, "src/lib.rs", 3),
+            annotation("Possible panic", Severity::High, "src/lib.rs", 40),
+            annotation("Long function", Severity::Medium, "src/main.rs", 12),
+            AnnotationBuilder::new("Build is slow", Severity::Low)
+                .build()
+                .unwrap(),
+        ])
+    }
+
+    #[test]
+    fn summary_and_findings() {
+        let expected = "\
+## Static analysis
+
+Findings of &lt;lint&gt;
+
+**Result:** Failed
+
+| Data | Value |
+| --- | --- |
+| Line coverage | 57% |
+| Started | 2020-02-27 |
+| Duration | 2m 5s |
+
+**Annotations:** 1 high, 1 medium, 2 low
+
+### Repository
+
+| Severity | Location | Message |
+| --- | --- | --- |
+| Low |  | Build is slow |
+
+### `src/lib.rs`
+
+| Severity | Location | Message |
+| --- | --- | --- |
+| High | `src/lib.rs:40` | Possible panic |
+| Low | `src/lib.rs:3` | Unused import |
+
+### `src/main.rs`
+
+| Severity | Location | Message |
+| --- | --- | --- |
+| Medium | `src/main.rs:12` | Long function |
+";
+        let actual = render_markdown(&report(), &annotations(), &MarkdownOptions::new());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn truncation() {
+        let options = MarkdownOptions::new().max_rows(2).max_rows_per_file(1);
+        let markdown = render_markdown(&report(), &annotations(), &options);
+        assert!(markdown.contains("| High | `src/lib.rs:40` | Possible panic |\n"));
+        assert!(!markdown.contains("Unused import"));
+        assert!(!markdown.contains("src/main.rs"));
+        assert!(markdown.ends_with("\n_2 more findings not shown._\n"));
+    }
+
+    #[test]
+    fn escaping() {
+        let annotations = Annotations::new(vec![annotation(
+            "a | b <script>alert(1)</script>",
+            Severity::High,
+            "src/a|b.rs",
+            1,
+        )]);
+        let markdown = render_markdown(&report(), &annotations, &MarkdownOptions::new());
+        assert!(markdown.contains(
+            "| High | `src/a\\|b.rs:1` | a \\| b &lt;script&gt;alert(1)&lt;/script&gt; |\n"
+        ));
+    }
+}
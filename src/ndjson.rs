@@ -0,0 +1,124 @@
+use std::io::{BufRead, Write};
+
+use crate::annotation::{Annotation, Annotations};
+use crate::error::{Error, Result};
+
+impl Annotations {
+    /// Writes the annotations as JSON Lines, i.e. one JSON object per line,
+    /// so that stages of a pipeline can append to and stream them.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an annotation has fields that are longer than
+    /// Bitbucket allows, or if writing fails.
+    pub fn to_ndjson<W: Write>(&self, mut writer: W) -> Result<()> {
+        for annotation in &self.annotations {
+            annotation.validate_fields()?;
+            serde_json::to_writer(&mut writer, annotation)?;
+            writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+        }
+        writer.flush().map_err(serde_json::Error::io)?;
+        Ok(())
+    }
+
+    /// Reads annotations written as JSON Lines, skipping blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading fails, or if a line is not a valid
+    /// annotation or has fields that are longer than Bitbucket allows. The
+    /// error message contains the number of the line, counting from 1.
+    pub fn from_ndjson<R: BufRead>(reader: R) -> Result<Annotations> {
+        let mut annotations = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(serde_json::Error::io)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let annotation = serde_json::from_str::<Annotation>(&line)
+                .map_err(|error| Error::parse_source("JSON Lines", error).at_line(index + 1))?;
+            annotation
+                .validate_fields()
+                .map_err(|error| Error::parse_source("JSON Lines", error).at_line(index + 1))?;
+            annotations.push(annotation);
+        }
+        Ok(Annotations::new(annotations))
+    }
+}
+
+#[cfg(test)]
+mod json_lines {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity, Type, MESSAGE_LIMIT};
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            AnnotationBuilder::new("Possible panic", Severity::High)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .line(40)
+                .external_id("a1")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Build is slow\nsee log", Severity::Low)
+                .build()
+                .unwrap(),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut ndjson = Vec::new();
+        annotations().to_ndjson(&mut ndjson).unwrap();
+        let text = String::from_utf8(ndjson.clone()).unwrap();
+        assert_eq!(2, text.lines().count());
+        assert!(text.ends_with('\n'));
+        assert_eq!(
+            annotations(),
+            Annotations::from_ndjson(&ndjson[..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn blank_lines() {
+        let ndjson = "\
+{\"message\":\"Unused import\",\"severity\":\"LOW\"}
+
+{\"message\":\"Possible panic\",\"severity\":\"HIGH\",\"line\":3}
+";
+        let annotations = Annotations::from_ndjson(ndjson.as_bytes()).unwrap();
+        assert_eq!(2, annotations.annotations.len());
+        assert_eq!(Some(3), annotations.annotations[1].line);
+    }
+
+    #[test]
+    fn corrupt_line() {
+        let ndjson = "\
+{\"message\":\"Unused import\",\"severity\":\"LOW\"}
+{\"message\":\"Possible panic\",\"severity\":\"HIGH\"}
+{\"message\":\"Truncated\",\"sever
+{\"message\":\"Dead code\",\"severity\":\"LOW\"}
+";
+        let error = Annotations::from_ndjson(ndjson.as_bytes()).unwrap_err();
+        assert!(
+            matches!(error, Error::Parse { ref tool, line: Some(3), .. } if tool == "JSON Lines"),
+            "{error:?}"
+        );
+        assert!(
+            error
+                .to_string()
+                .starts_with("failed to parse JSON Lines output at line 3: "),
+            "{error}"
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.is::<serde_json::Error>());
+    }
+
+    #[test]
+    fn invalid_annotation() {
+        let message = "X".repeat(MESSAGE_LIMIT + 1);
+        let ndjson = format!("\n{{\"message\":\"{message}\",\"severity\":\"LOW\"}}\n");
+        let error = Annotations::from_ndjson(ndjson.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 2:"), "{error}");
+    }
+}
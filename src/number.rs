@@ -0,0 +1,278 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum N {
+    /// Always non-negative.
+    Unsigned(u64),
+    /// Always negative.
+    Signed(i64),
+    /// Always finite.
+    Float(f64),
+}
+
+/// The value of a [`Parameter::Number`](crate::Parameter::Number) data field,
+/// an integer or a finite floating point number.
+///
+/// It serializes like a JSON number, and converts to and from
+/// `serde_json::Number` when the `json` feature is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Number(N);
+
+// Floats are always finite, so every number is equal to itself.
+impl Eq for N {}
+
+impl Hash for N {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            N::Unsigned(unsigned) => unsigned.hash(state),
+            N::Signed(signed) => signed.hash(state),
+            // Zero and negative zero are equal, so they must hash the same.
+            N::Float(float) => {
+                let float = if float == 0.0 { 0.0f64 } else { float };
+                float.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+impl Number {
+    /// Constructs a number from a float, or returns `None` if it is infinite
+    /// or NaN, which JSON cannot represent.
+    pub fn from_f64(float: f64) -> Option<Number> {
+        float.is_finite().then_some(Number(N::Float(float)))
+    }
+
+    /// Returns `true` if the number is an integer representable as an `i64`.
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Returns `true` if the number is a non-negative integer.
+    pub fn is_u64(&self) -> bool {
+        matches!(self.0, N::Unsigned(_))
+    }
+
+    /// Returns `true` if the number is a float, even one with an integral
+    /// value.
+    pub fn is_f64(&self) -> bool {
+        matches!(self.0, N::Float(_))
+    }
+
+    /// Returns the number as an `i64`, if it is an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.0 {
+            N::Unsigned(unsigned) => i64::try_from(unsigned).ok(),
+            N::Signed(signed) => Some(signed),
+            N::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as a `u64`, if it is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.0 {
+            N::Unsigned(unsigned) => Some(unsigned),
+            N::Signed(_) | N::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, which may lose precision for large
+    /// integers.
+    pub fn as_f64(&self) -> f64 {
+        match self.0 {
+            N::Unsigned(unsigned) => unsigned as f64,
+            N::Signed(signed) => signed as f64,
+            N::Float(float) => float,
+        }
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(unsigned: $ty) -> Self {
+                    Number(N::Unsigned(unsigned as u64))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(signed: $ty) -> Self {
+                    match u64::try_from(signed) {
+                        Ok(unsigned) => Number(N::Unsigned(unsigned)),
+                        Err(_) => Number(N::Signed(signed as i64)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8, u16, u32, u64, usize);
+from_signed!(i8, i16, i32, i64, isize);
+
+impl fmt::Display for Number {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            N::Unsigned(unsigned) => write!(formatter, "{unsigned}"),
+            N::Signed(signed) => write!(formatter, "{signed}"),
+            // Debug formatting keeps the fraction of integral values and uses
+            // exponents for large and small values, like JSON.
+            N::Float(float) => write!(formatter, "{float:?}"),
+        }
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            N::Unsigned(unsigned) => serializer.serialize_u64(unsigned),
+            N::Signed(signed) => serializer.serialize_i64(signed),
+            N::Float(float) => serializer.serialize_f64(float),
+        }
+    }
+}
+
+struct NumberVisitor;
+
+impl Visitor<'_> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<Number, E> {
+        Ok(Number::from(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<Number, E> {
+        Ok(Number::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<Number, E> {
+        Number::from_f64(value).ok_or_else(|| E::custom("number is not finite"))
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Number> for Number {
+    fn from(number: serde_json::Number) -> Self {
+        if let Some(unsigned) = number.as_u64() {
+            Number(N::Unsigned(unsigned))
+        } else if let Some(signed) = number.as_i64() {
+            Number(N::Signed(signed))
+        } else {
+            // Without arbitrary precision, every other JSON number is a
+            // finite f64.
+            Number(N::Float(number.as_f64().unwrap_or_default()))
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<Number> for serde_json::Number {
+    fn from(number: Number) -> Self {
+        match number.0 {
+            N::Unsigned(unsigned) => unsigned.into(),
+            N::Signed(signed) => signed.into(),
+            // Floats are always finite.
+            N::Float(float) => serde_json::Number::from_f64(float).unwrap_or_else(|| 0.into()),
+        }
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Number {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Number".into()
+    }
+
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "number" })
+    }
+}
+
+#[cfg(test)]
+mod numbers {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Number::from(3u8), Number::from(3i64));
+        assert_eq!(Some(-3), Number::from(-3).as_i64());
+        assert_eq!(None, Number::from(-3).as_u64());
+        assert_eq!(None, Number::from(u64::MAX).as_i64());
+        assert!(Number::from_f64(f64::NAN).is_none());
+        assert!(Number::from_f64(3.0).unwrap().is_f64());
+        assert_ne!(Number::from(3), Number::from_f64(3.0).unwrap());
+    }
+
+    #[test]
+    fn hash() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let state = RandomState::new();
+        let zero = Number::from_f64(0.0).unwrap();
+        let negative_zero = Number::from_f64(-0.0).unwrap();
+        assert_eq!(zero, negative_zero);
+        assert_eq!(state.hash_one(zero), state.hash_one(negative_zero));
+        assert_eq!(
+            state.hash_one(Number::from(3u8)),
+            state.hash_one(Number::from(3i64))
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("-12", Number::from(-12).to_string());
+        assert_eq!("7.5", Number::from_f64(7.5).unwrap().to_string());
+        assert_eq!("3.0", Number::from_f64(3.0).unwrap().to_string());
+    }
+
+    #[test]
+    fn serialization() {
+        for (number, json) in [
+            (Number::from(u64::MAX), "18446744073709551615"),
+            (Number::from(i64::MIN), "-9223372036854775808"),
+            (Number::from_f64(0.25).unwrap(), "0.25"),
+        ] {
+            assert_eq!(json, serde_json::to_string(&number).unwrap());
+            assert_eq!(number, serde_json::from_str::<Number>(json).unwrap());
+        }
+        assert!(serde_json::from_str::<Number>("\"12\"").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_number() {
+        for json in ["12", "-12", "7.5"] {
+            let number = serde_json::from_str::<serde_json::Number>(json).unwrap();
+            assert_eq!(
+                number,
+                serde_json::Number::from(Number::from(number.clone()))
+            );
+        }
+    }
+}
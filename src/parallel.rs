@@ -0,0 +1,114 @@
+use rayon::prelude::*;
+
+use crate::annotation::{Annotation, Annotations};
+use crate::error::Error;
+
+impl Annotations {
+    /// Validates the fields of all annotations in parallel.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the index and the error of every annotation that
+    /// has fields longer than Bitbucket allows, ordered by index.
+    pub fn par_validate(&self) -> std::result::Result<(), Vec<(usize, Error)>> {
+        let errors = self
+            .annotations
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, annotation)| {
+                annotation
+                    .validate_fields()
+                    .err()
+                    .map(|error| (index, error))
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Keeps the annotations for which `predicate` returns `true`, evaluating
+    /// it in parallel, and returns the number of removed annotations. The
+    /// order of the kept annotations is unchanged.
+    pub fn par_retain<F>(&mut self, predicate: F) -> usize
+    where
+        F: Fn(&Annotation) -> bool + Sync,
+    {
+        let keep = self
+            .annotations
+            .par_iter()
+            .map(&predicate)
+            .collect::<Vec<_>>();
+        let before = self.annotations.len();
+        let mut keep = keep.into_iter();
+        self.annotations.retain(|_| keep.next().unwrap_or(true));
+        before - self.annotations.len()
+    }
+
+    /// Applies `f` to every annotation in parallel.
+    pub fn par_map_in_place<F>(&mut self, f: F)
+    where
+        F: Fn(&mut Annotation) + Sync + Send,
+    {
+        self.annotations.par_iter_mut().for_each(f);
+    }
+}
+
+#[cfg(test)]
+mod parallel_processing {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity, MESSAGE_LIMIT};
+
+    fn annotations(count: u32) -> Annotations {
+        Annotations::new(
+            (0..count)
+                .map(|index| {
+                    AnnotationBuilder::new(format!("Finding {index}"), Severity::Low)
+                        .path(format!("/workspace/src/module{}.rs", index % 97))
+                        .line(index)
+                        .build()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn matches_sequential() {
+        let relativize = |annotation: &mut Annotation| {
+            if let Some(path) = &mut annotation.path {
+                *path = path.trim_start_matches("/workspace/").to_owned();
+            }
+        };
+        let keep = |annotation: &Annotation| annotation.line.is_some_and(|line| line % 3 != 0);
+
+        let mut parallel = annotations(200_000);
+        parallel.par_map_in_place(relativize);
+        let removed = parallel.par_retain(keep);
+        assert!(parallel.par_validate().is_ok());
+
+        let mut sequential = annotations(200_000);
+        sequential.annotations.iter_mut().for_each(relativize);
+        let before = sequential.annotations.len();
+        sequential.annotations.retain(keep);
+
+        assert_eq!(before - sequential.annotations.len(), removed);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn aggregated_errors() {
+        let mut annotations = annotations(10_000);
+        for index in [9_999, 42, 7_000] {
+            annotations.annotations[index].message = "X".repeat(MESSAGE_LIMIT + 1);
+        }
+        let errors = annotations.par_validate().unwrap_err();
+        let indices = errors.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+        assert_eq!(vec![42, 7_000, 9_999], indices);
+        assert!(errors
+            .iter()
+            .all(|(_, error)| matches!(error, Error::FieldTooLong { .. })));
+    }
+}
@@ -0,0 +1,209 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+use crate::logging::log_debug;
+
+/// Selects annotations by their path using include and exclude glob patterns.
+///
+/// Patterns follow the semantics of `.gitignore`:
+///
+/// - `*` and `?` do not match `/`, while `**` matches any number of
+///   directories.
+/// - A pattern without a slash, such as `*.pb.go`, matches at any depth.
+/// - A pattern with a slash, such as `src/generated`, is relative to the
+///   repository root. A leading slash is allowed but not required.
+/// - A pattern matching a directory matches all files below it, and a
+///   trailing slash, as in `vendor/`, is allowed but not required.
+///
+/// Matching is case-sensitive.
+#[derive(Debug, Clone)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    keep_without_path: bool,
+    quiet: bool,
+}
+
+impl PathFilter {
+    /// Compiles a filter that keeps the paths matching any of the `include`
+    /// patterns, or all paths if there are none, unless they match any of the
+    /// `exclude` patterns. Exclude patterns take precedence.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the patterns is not a valid glob.
+    pub fn new(include: &[&str], exclude: &[&str]) -> Result<PathFilter> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(compile(include)?)
+        };
+
+        Ok(PathFilter {
+            include,
+            exclude: compile(exclude)?,
+            keep_without_path: true,
+            quiet: false,
+        })
+    }
+
+    /// Sets whether annotations without a path are kept. This is the default.
+    pub fn keep_without_path(mut self, keep: bool) -> Self {
+        self.keep_without_path = keep;
+        self
+    }
+
+    /// Silences the debug events logged for every removed annotation, if the
+    /// `log` feature is enabled.
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Returns `true` if `path` passes the filter.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|include| include.is_match(path));
+        included && !self.exclude.is_match(path)
+    }
+}
+
+impl Annotations {
+    /// Removes the annotations whose path does not pass `filter`, returning
+    /// the number of removed annotations.
+    pub fn filter_paths(&mut self, filter: &PathFilter) -> usize {
+        let before = self.annotations.len();
+        self.annotations.retain(|annotation| {
+            let keep = match &annotation.path {
+                Some(path) => filter.matches(path),
+                None => filter.keep_without_path,
+            };
+            if !keep {
+                log_debug!(
+                    filter.quiet,
+                    "path filter dropped annotation on {}: {}",
+                    annotation.path.as_deref().unwrap_or("(no path)"),
+                    annotation.message
+                );
+            }
+            keep
+        });
+        before - self.annotations.len()
+    }
+}
+
+/// Compiles `patterns` by the semantics documented on [`PathFilter`].
+pub(crate) fn compile(patterns: &[&str]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let invalid = |message: String| Error::InvalidPattern {
+            pattern: (*pattern).to_owned(),
+            message,
+        };
+
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.contains('/');
+        let trimmed = trimmed.trim_start_matches('/');
+        if trimmed.is_empty() {
+            return Err(invalid("empty pattern".to_owned()));
+        }
+
+        let glob = if anchored || trimmed.starts_with("**") {
+            trimmed.to_owned()
+        } else {
+            format!("**/{trimmed}")
+        };
+        for glob in [glob.clone(), format!("{glob}/**")] {
+            let glob = GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build()
+                .map_err(|error| invalid(error.kind().to_string()))?;
+            builder.add(glob);
+        }
+    }
+    builder.build().map_err(|error| Error::InvalidPattern {
+        pattern: patterns.join(", "),
+        message: error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod filtering {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn annotation(path: Option<&str>) -> crate::Annotation {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low);
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn gitignore_semantics() {
+        let filter =
+            PathFilter::new(&[], &["vendor/", "/target", "*.pb.go", "tests/**/fixtures"]).unwrap();
+        assert!(!filter.matches("vendor/lib.go"));
+        assert!(!filter.matches("third_party/vendor/lib.go"));
+        assert!(!filter.matches("target/debug/build.rs"));
+        assert!(filter.matches("crates/target/lib.rs"));
+        assert!(!filter.matches("api/v1/service.pb.go"));
+        assert!(!filter.matches("tests/fixtures/input.json"));
+        assert!(!filter.matches("tests/unit/fixtures/input.json"));
+        assert!(filter.matches("src/fixtures.rs"));
+        assert!(filter.matches("src/main.go"));
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        let filter = PathFilter::new(&["src/*.rs"], &[]).unwrap();
+        assert!(filter.matches("src/lib.rs"));
+        assert!(!filter.matches("src/nested/lib.rs"));
+    }
+
+    #[test]
+    fn exclude_wins() {
+        let filter = PathFilter::new(&["src/**"], &["**/generated/**"]).unwrap();
+        assert!(filter.matches("src/lib.rs"));
+        assert!(!filter.matches("src/generated/schema.rs"));
+        assert!(!filter.matches("docs/index.md"));
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let filter = PathFilter::new(&[], &["Vendor/"]).unwrap();
+        assert!(!filter.matches("Vendor/lib.go"));
+        assert!(filter.matches("vendor/lib.go"));
+    }
+
+    #[test]
+    fn filter_annotations() {
+        let filter = PathFilter::new(&["src/"], &["src/generated/"]).unwrap();
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("src/lib.rs")),
+            annotation(Some("src/generated/schema.rs")),
+            annotation(Some("build.rs")),
+            annotation(None),
+        ]);
+        assert_eq!(2, annotations.filter_paths(&filter));
+        assert_eq!(
+            Annotations::new(vec![annotation(Some("src/lib.rs")), annotation(None)]),
+            annotations
+        );
+
+        let filter = filter.keep_without_path(false);
+        assert_eq!(1, annotations.filter_paths(&filter));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let result = PathFilter::new(&["src/[a-"], &[]);
+        assert!(matches!(result, Err(Error::InvalidPattern { .. })));
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use crate::annotation::Annotations;
+use crate::error::{Error, Result};
+
+/// Makes `path` relative to `root` using forward slashes, or returns it
+/// unchanged if it is not below `root`.
+pub(crate) fn relativize(path: &str, root: &Path) -> String {
+    match Path::new(path).strip_prefix(root) {
+        Ok(relative) => join(relative),
+        Err(_) => path.to_owned(),
+    }
+}
+
+/// Makes paths relative to the root of a repository, as Bitbucket expects
+/// annotation paths to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoPaths {
+    root: PathBuf,
+    resolved_root: PathBuf,
+}
+
+impl RepoPaths {
+    /// Constructs a `RepoPaths` for the repository at `root`.
+    pub fn new(root: &Path) -> RepoPaths {
+        RepoPaths {
+            root: normalize(root),
+            resolved_root: resolve(root),
+        }
+    }
+
+    /// Discovers the root of the repository containing `start` by walking up
+    /// the file system until a directory containing `.git` is found.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `start` does not exist or is not inside a
+    /// repository.
+    #[cfg(feature = "git")]
+    pub fn discover(start: &Path) -> Result<RepoPaths> {
+        let not_found = || Error::RepositoryNotFound {
+            path: start.to_path_buf(),
+        };
+
+        let start = start.canonicalize().map_err(|_| not_found())?;
+        start
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(RepoPaths::new)
+            .ok_or_else(not_found)
+    }
+
+    /// Returns the root of the repository.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Makes `path` relative to the root of the repository, using forward
+    /// slashes as separators.
+    ///
+    /// Relative paths are taken to be relative to the root already. Symbolic
+    /// links are resolved when the path is not lexically below the root, so a
+    /// path through a link to the repository is accepted as well.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is outside of the repository.
+    pub fn relativize(&self, path: &Path) -> Result<String> {
+        let absolute = self.root.join(path);
+        let relative = normalize(&absolute)
+            .strip_prefix(&self.root)
+            .map(join)
+            .or_else(|_| {
+                resolve(&absolute)
+                    .strip_prefix(&self.resolved_root)
+                    .map(join)
+            });
+
+        match relative {
+            Ok(relative) if !relative.is_empty() => Ok(relative),
+            _ => Err(Error::OutsideRepository {
+                path: path.to_path_buf(),
+                root: self.root.clone(),
+            }),
+        }
+    }
+}
+
+/// The outcome of [`Annotations::relativize_paths`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelativizedPaths {
+    relativized: usize,
+    unchanged: usize,
+    dropped: usize,
+}
+
+impl RelativizedPaths {
+    /// Returns the number of annotations whose path was rewritten.
+    pub fn relativized(&self) -> usize {
+        self.relativized
+    }
+
+    /// Returns the number of annotations that were left as they were, either
+    /// because their path was relative to the root already or because they
+    /// have no path.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Returns the number of annotations that were removed because their path
+    /// is outside of the repository.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl Annotations {
+    /// Makes the paths of all annotations relative to the root of the
+    /// repository with [`RepoPaths::relativize`].
+    ///
+    /// Annotations with a path outside of the repository cannot be displayed
+    /// by Bitbucket and are removed.
+    pub fn relativize_paths(&mut self, paths: &RepoPaths) -> RelativizedPaths {
+        let mut outcome = RelativizedPaths::default();
+        self.annotations.retain_mut(|annotation| {
+            let Some(path) = &annotation.path else {
+                outcome.unchanged += 1;
+                return true;
+            };
+
+            match paths.relativize(Path::new(path)) {
+                Ok(relative) if relative == *path => outcome.unchanged += 1,
+                Ok(relative) => {
+                    annotation.path = Some(relative);
+                    outcome.relativized += 1;
+                }
+                Err(_) => {
+                    outcome.dropped += 1;
+                    return false;
+                }
+            }
+            true
+        });
+        outcome
+    }
+}
+
+impl Annotations {
+    /// Rewrites the path of every annotation that has one with `rewrite`,
+    /// returning the number of annotations that were removed because
+    /// `rewrite` returned `None` for their path.
+    pub fn rewrite_paths(&mut self, rewrite: impl Fn(&str) -> Option<String>) -> usize {
+        let before = self.annotations.len();
+        self.annotations.retain_mut(
+            |annotation| match annotation.path.as_deref().map(&rewrite) {
+                Some(Some(path)) => {
+                    annotation.path = Some(path);
+                    true
+                }
+                Some(None) => false,
+                None => true,
+            },
+        );
+        before - self.annotations.len()
+    }
+
+    /// Prepends `prefix` to all paths, e.g. to make paths reported by an
+    /// analysis of a subdirectory relative to the repository root.
+    ///
+    /// Backslashes are turned into forward slashes, and the prefix and path
+    /// are joined by a single slash.
+    pub fn prepend_prefix(&mut self, prefix: &str) {
+        let prefix = normalize_separators(prefix);
+        let prefix = prefix.trim_end_matches('/');
+        self.rewrite_paths(|path| {
+            let path = normalize_separators(path);
+            let path = path.trim_start_matches('/');
+            Some(if prefix.is_empty() {
+                path.to_owned()
+            } else {
+                format!("{prefix}/{path}")
+            })
+        });
+    }
+
+    /// Removes the leading directories `prefix` from all paths, e.g. when the
+    /// repository was checked out below the directory the analysis ran in.
+    ///
+    /// Backslashes are turned into forward slashes. The prefix is matched by
+    /// whole path components, and paths that do not start with it are left as
+    /// they are, apart from the separators. Use
+    /// [`rewrite_paths`](Annotations::rewrite_paths) to drop them instead.
+    pub fn strip_prefix(&mut self, prefix: &str) {
+        let prefix = normalize_separators(prefix);
+        let prefix = prefix.trim_end_matches('/');
+        self.rewrite_paths(|path| {
+            let path = normalize_separators(path);
+            let stripped = path
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .filter(|rest| !prefix.is_empty() && !rest.is_empty());
+            Some(stripped.map_or_else(|| path.clone(), str::to_owned))
+        });
+    }
+}
+
+impl Annotations {
+    /// Returns the distinct paths of the annotations, each once, in the order
+    /// they are first annotated.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        let mut seen = HashSet::new();
+        self.annotations
+            .iter()
+            .filter_map(|annotation| annotation.path.as_deref())
+            .filter(move |path| seen.insert(*path))
+    }
+
+    /// Returns the number of distinct paths of the annotations.
+    pub fn path_count(&self) -> usize {
+        self.paths().count()
+    }
+
+    /// Returns `true` if any annotation has no path, and so applies to the
+    /// whole repository.
+    pub fn has_repo_level(&self) -> bool {
+        self.annotations
+            .iter()
+            .any(|annotation| annotation.path.is_none())
+    }
+}
+
+/// Converts `path` to a string with forward slashes, as Bitbucket expects.
+///
+/// Fails if `path` is not valid UTF-8, or if it is absolute and
+/// `allow_absolute` is `false`.
+pub(crate) fn path_to_string(path: &Path, allow_absolute: bool) -> Result<String> {
+    let invalid = |reason: String| Error::InvalidValue {
+        field: "path".to_owned(),
+        reason,
+    };
+    let string = path
+        .to_str()
+        .ok_or_else(|| invalid(format!("'{}' is not valid UTF-8", path.display())))?;
+    if !allow_absolute && (path.is_absolute() || path.has_root()) {
+        return Err(invalid(format!("'{string}' is absolute")));
+    }
+    Ok(normalize_separators(string))
+}
+
+/// Turns backslashes into forward slashes, collapses repeated slashes and
+/// removes `.` components.
+fn normalize_separators(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let absolute = path.starts_with('/');
+    let components = path
+        .split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>()
+        .join("/");
+    if absolute {
+        format!("/{components}")
+    } else {
+        components
+    }
+}
+
+fn join(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Removes `.` components and resolves `..` components without touching the
+/// file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Resolves symbolic links in the longest existing prefix of `path`.
+fn resolve(path: &Path) -> PathBuf {
+    let path = normalize(path);
+    for existing in path.ancestors() {
+        if let Ok(resolved) = existing.canonicalize() {
+            let rest = path.strip_prefix(existing).unwrap_or(Path::new(""));
+            return resolved.join(rest);
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod repo_paths {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn repository() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("repo/.git")).unwrap();
+        fs::create_dir_all(dir.path().join("repo/src/nested")).unwrap();
+        fs::write(dir.path().join("repo/src/lib.rs"), "").unwrap();
+        dir
+    }
+
+    fn annotation(path: &str) -> crate::Annotation {
+        AnnotationBuilder::new("Message", Severity::Low)
+            .path(path)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn absolute_path() {
+        let dir = repository();
+        let paths = RepoPaths::new(&dir.path().join("repo"));
+        let path = dir.path().join("repo/src/lib.rs");
+        assert_eq!("src/lib.rs", paths.relativize(&path).unwrap());
+    }
+
+    #[test]
+    fn relative_path() {
+        let dir = repository();
+        let paths = RepoPaths::new(&dir.path().join("repo"));
+        assert_eq!(
+            "src/lib.rs",
+            paths
+                .relativize(Path::new("./src/nested/../lib.rs"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn outside_of_repository() {
+        let dir = repository();
+        let paths = RepoPaths::new(&dir.path().join("repo"));
+        for path in [dir.path().join("other/lib.rs"), PathBuf::from("../lib.rs")] {
+            let result = paths.relativize(&path);
+            assert!(matches!(result, Err(Error::OutsideRepository { .. })));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_component() {
+        let dir = repository();
+        std::os::unix::fs::symlink(dir.path().join("repo"), dir.path().join("link")).unwrap();
+
+        let paths = RepoPaths::new(&dir.path().join("repo"));
+        let path = dir.path().join("link/src/lib.rs");
+        assert_eq!("src/lib.rs", paths.relativize(&path).unwrap());
+
+        let paths = RepoPaths::new(&dir.path().join("link"));
+        let path = dir.path().join("repo/src/generated.rs");
+        assert_eq!("src/generated.rs", paths.relativize(&path).unwrap());
+    }
+
+    #[test]
+    fn relativize_annotations() {
+        let dir = repository();
+        let paths = RepoPaths::new(&dir.path().join("repo"));
+        let absolute = dir.path().join("repo/src/lib.rs");
+        let outside = dir.path().join("other.rs");
+        let mut annotations = Annotations::new(vec![
+            annotation(absolute.to_str().unwrap()),
+            annotation("src/lib.rs"),
+            annotation(outside.to_str().unwrap()),
+            AnnotationBuilder::new("Message", Severity::Low)
+                .build()
+                .unwrap(),
+        ]);
+
+        let outcome = annotations.relativize_paths(&paths);
+        assert_eq!(
+            (1, 2, 1),
+            (
+                outcome.relativized(),
+                outcome.unchanged(),
+                outcome.dropped()
+            )
+        );
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("src/lib.rs"),
+                annotation("src/lib.rs"),
+                AnnotationBuilder::new("Message", Severity::Low)
+                    .build()
+                    .unwrap(),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn rewrite_paths() {
+        let mut annotations = Annotations::new(vec![
+            annotation("src/lib.rs"),
+            annotation("tests/fixture.rs"),
+            AnnotationBuilder::new("Message", Severity::Low)
+                .build()
+                .unwrap(),
+        ]);
+        let dropped = annotations.rewrite_paths(|path| {
+            path.strip_prefix("src/")
+                .map(|path| format!("crates/core/src/{path}"))
+        });
+        assert_eq!(1, dropped);
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("crates/core/src/lib.rs"),
+                AnnotationBuilder::new("Message", Severity::Low)
+                    .build()
+                    .unwrap(),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn prepend_prefix() {
+        let mut annotations = Annotations::new(vec![
+            annotation("src/lib.rs"),
+            annotation("./src\\main.rs"),
+            annotation("/build.rs"),
+        ]);
+        annotations.prepend_prefix("services\\payments/");
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("services/payments/src/lib.rs"),
+                annotation("services/payments/src/main.rs"),
+                annotation("services/payments/build.rs"),
+            ]),
+            annotations
+        );
+    }
+
+    #[test]
+    fn strip_prefix() {
+        let mut annotations = Annotations::new(vec![
+            annotation("build/checkout/src/lib.rs"),
+            annotation("build\\checkout\\src\\main.rs"),
+            annotation("build/checkout-old/src/lib.rs"),
+            annotation("src//util.rs"),
+        ]);
+        annotations.strip_prefix("build/checkout/");
+        assert_eq!(
+            Annotations::new(vec![
+                annotation("src/lib.rs"),
+                annotation("src/main.rs"),
+                annotation("build/checkout-old/src/lib.rs"),
+                annotation("src/util.rs"),
+            ]),
+            annotations
+        );
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn discover() {
+        let dir = repository();
+        let paths = RepoPaths::discover(&dir.path().join("repo/src/nested")).unwrap();
+        assert_eq!(
+            dir.path().join("repo").canonicalize().unwrap(),
+            paths.root()
+        );
+
+        let result = RepoPaths::discover(&dir.path().join("missing"));
+        assert!(matches!(result, Err(Error::RepositoryNotFound { .. })));
+    }
+}
+
+#[cfg(test)]
+mod annotated_paths {
+    use super::*;
+    use crate::{Annotation, AnnotationBuilder, Severity};
+
+    fn annotation(path: Option<&str>) -> Annotation {
+        let mut builder = AnnotationBuilder::new("Message", Severity::Low);
+        if let Some(path) = path {
+            builder.set_path(path);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn distinct_in_order() {
+        let annotations = Annotations::new(vec![
+            annotation(Some("src/main.rs")),
+            annotation(None),
+            annotation(Some("src/lib.rs")),
+            annotation(Some("src/main.rs")),
+            annotation(Some("src/lib.rs")),
+        ]);
+        assert_eq!(
+            vec!["src/main.rs", "src/lib.rs"],
+            annotations.paths().collect::<Vec<_>>()
+        );
+        assert_eq!(2, annotations.path_count());
+        assert!(annotations.has_repo_level());
+    }
+
+    #[test]
+    fn without_repo_level() {
+        let annotations = Annotations::new(vec![annotation(Some("src/lib.rs"))]);
+        assert_eq!(1, annotations.path_count());
+        assert!(!annotations.has_repo_level());
+
+        let annotations = Annotations::new(vec![annotation(None)]);
+        assert_eq!(0, annotations.path_count());
+        assert!(annotations.has_repo_level());
+    }
+
+    #[test]
+    fn empty() {
+        let annotations = Annotations::new(Vec::new());
+        assert_eq!(None, annotations.paths().next());
+        assert_eq!(0, annotations.path_count());
+        assert!(!annotations.has_repo_level());
+    }
+}
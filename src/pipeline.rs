@@ -0,0 +1,749 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotations, Severity};
+#[cfg(feature = "fingerprint")]
+use crate::baseline::Baseline;
+use crate::budget::{CollapseScope, OverflowBehavior, RollupStrategy};
+#[cfg(feature = "diff")]
+use crate::diff::ChangedLines;
+use crate::error::{Error, Result};
+use crate::escalation::EscalationRules;
+use crate::external_id::ExternalIdNamespace;
+use crate::link::LinkTemplate;
+#[cfg(feature = "glob")]
+use crate::path_filter::PathFilter;
+use crate::paths::RepoPaths;
+use crate::processing::ProcessingSummary;
+#[cfg(feature = "regex")]
+use crate::redact::Redactor;
+#[cfg(feature = "glob")]
+use crate::severity_override::{SeverityAction, SeverityOverrides};
+
+/// A filter or transformation of annotations that can be a stage of a
+/// [`Pipeline`].
+///
+/// It is implemented by the filters and transformations of this crate, each
+/// applying the corresponding method of [`Annotations`], and by closures.
+pub trait AnnotationProcessor {
+    /// Filters or transforms `annotations`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the annotations cannot be processed.
+    fn process(&self, annotations: Annotations) -> Result<Annotations>;
+
+    /// Like [`process`](AnnotationProcessor::process), but also records what
+    /// happened to the annotations in `summary`.
+    ///
+    /// By default nothing is recorded, and a [`Pipeline`] records the removed
+    /// annotations under the name of the stage.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the annotations cannot be processed.
+    fn process_summarized(
+        &self,
+        annotations: Annotations,
+        summary: &mut ProcessingSummary,
+    ) -> Result<Annotations> {
+        let _ = summary;
+        self.process(annotations)
+    }
+}
+
+impl<F> AnnotationProcessor for F
+where
+    F: Fn(Annotations) -> Result<Annotations>,
+{
+    fn process(&self, annotations: Annotations) -> Result<Annotations> {
+        self(annotations)
+    }
+}
+
+/// Implements [`AnnotationProcessor`] by recording into a summary, which is
+/// discarded by `process`.
+macro_rules! summarized_processor {
+    ($(#[$attr:meta])* $ty:ty, |$this:ident, $annotations:ident, $summary:ident| $body:block) => {
+        $(#[$attr])*
+        impl AnnotationProcessor for $ty {
+            fn process(&self, annotations: Annotations) -> Result<Annotations> {
+                self.process_summarized(annotations, &mut ProcessingSummary::new())
+            }
+
+            fn process_summarized(
+                &self,
+                #[allow(unused_mut)] mut $annotations: Annotations,
+                $summary: &mut ProcessingSummary,
+            ) -> Result<Annotations> {
+                let $this = self;
+                $body
+                Ok($annotations)
+            }
+        }
+    };
+}
+
+summarized_processor!(
+    /// Applies [`Annotations::suppress`].
+    #[cfg(feature = "fingerprint")]
+    Baseline,
+    |baseline, annotations, summary| {
+        summary.record_dropped("baseline", annotations.suppress(baseline));
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::retain_changed`].
+    #[cfg(feature = "diff")]
+    ChangedLines,
+    |changed, annotations, summary| {
+        summary.record_dropped("outside diff", annotations.retain_changed(changed));
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::apply_escalations`].
+    EscalationRules,
+    |rules, annotations, summary| {
+        let before = annotations
+            .annotations
+            .iter()
+            .map(|annotation| annotation.severity)
+            .collect::<Vec<_>>();
+        annotations.apply_escalations(rules);
+        // Escalations only add annotations after the existing ones.
+        summary.record_severities_changed(
+            before
+                .iter()
+                .zip(&annotations.annotations)
+                .filter(|(severity, annotation)| **severity != annotation.severity)
+                .count(),
+        );
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::namespace_external_ids`].
+    ExternalIdNamespace,
+    |namespace, annotations, _summary| {
+        annotations.namespace_external_ids(namespace);
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::apply_link_template`].
+    LinkTemplate,
+    |template, annotations, _summary| {
+        annotations.apply_link_template(template);
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::filter_paths`].
+    #[cfg(feature = "glob")]
+    PathFilter,
+    |filter, annotations, summary| {
+        summary.record_dropped("path filter", annotations.filter_paths(filter));
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::relativize_paths`].
+    RepoPaths,
+    |paths, annotations, summary| {
+        let relativized = annotations.relativize_paths(paths);
+        summary.record_dropped("outside repository", relativized.dropped());
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::redact`].
+    #[cfg(feature = "regex")]
+    Redactor,
+    |redactor, annotations, summary| {
+        summary.record_redacted(annotations.redact(redactor));
+    }
+);
+
+summarized_processor!(
+    /// Applies [`Annotations::apply_severity_overrides`].
+    #[cfg(feature = "glob")]
+    SeverityOverrides,
+    |overrides, annotations, summary| {
+        let (dropped, changed) = annotations.apply_severity_overrides_counted(overrides);
+        summary.record_dropped("severity override", dropped);
+        summary.record_severities_changed(changed);
+    }
+);
+
+/// The stages of a [`PipelineConfig`] that take parameters instead of a
+/// filter or transformation.
+#[derive(Debug, Clone, Copy)]
+enum Transform {
+    CapPerFile(usize, OverflowBehavior),
+    CollapseDuplicates(CollapseScope),
+    KeepTop(usize),
+    RetainSeverity(Severity),
+    Rollup(usize, RollupStrategy),
+    TruncateMessages,
+}
+
+summarized_processor!(Transform, |transform, annotations, summary| {
+    match *transform {
+        Transform::CapPerFile(max, overflow) => {
+            summary.record_dropped("per-file cap", annotations.cap_per_file(max, overflow));
+        }
+        Transform::CollapseDuplicates(scope) => {
+            summary.record_dropped("duplicate", annotations.collapse_duplicates(scope));
+        }
+        Transform::KeepTop(n) => {
+            let dropped;
+            (annotations, dropped) = annotations.keep_top_by_severity(n);
+            summary.record_dropped("budget", dropped);
+        }
+        Transform::RetainSeverity(min) => {
+            summary.record_dropped("severity", annotations.retain_severity(min));
+        }
+        Transform::Rollup(budget, strategy) => {
+            let dropped;
+            (annotations, dropped) = annotations.rollup_counted(budget, strategy);
+            summary.record_dropped("rollup", dropped);
+        }
+        Transform::TruncateMessages => {
+            summary.record_truncated(annotations.truncate_messages());
+        }
+    }
+});
+
+type Stage = Arc<dyn AnnotationProcessor + Send + Sync>;
+
+/// Runs [`AnnotationProcessor`]s one after the other, so that every
+/// integration applies its filters and transformations in the same order.
+///
+/// ```
+/// use code_insights::{
+///     Annotations, CollapseScope, LinkTemplate, Pipeline, RepoPaths,
+/// };
+/// use std::path::Path;
+///
+/// let pipeline = Pipeline::new()
+///     .stage("relativize", RepoPaths::new(Path::new("/src/project")))
+///     .stage("dedup", |mut annotations: Annotations| {
+///         annotations.collapse_duplicates(CollapseScope::PerFile);
+///         Ok(annotations)
+///     })
+///     .stage(
+///         "links",
+///         LinkTemplate::new("https://example.com/{path}#L{line}").unwrap(),
+///     );
+/// let (annotations, counts) = pipeline.run(Annotations::new(vec![])).unwrap();
+/// assert_eq!(3, counts.len());
+/// ```
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<(String, Stage)>,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field(
+                "stages",
+                &self.stages.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Pipeline {
+    /// Constructs a pipeline without any stages.
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Constructs a pipeline from a configuration, e.g. one shared between
+    /// teams as a file.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::Stage`] if a stage is invalid, such as a glob
+    /// pattern that does not compile or a baseline that cannot be read.
+    pub fn from_config(config: &PipelineConfig) -> Result<Pipeline> {
+        let mut pipeline = Pipeline::new();
+        for stage in &config.stages {
+            let processor = stage.kind.compile().map_err(|error| Error::Stage {
+                stage: stage.name().to_owned(),
+                source: Box::new(error),
+            })?;
+            pipeline.stages.push((stage.name().to_owned(), processor));
+        }
+        Ok(pipeline)
+    }
+
+    /// Appends a stage called `name`, which runs after the stages before it.
+    pub fn stage<N, P>(mut self, name: N, processor: P) -> Self
+    where
+        N: Into<String>,
+        P: AnnotationProcessor + Send + Sync + 'static,
+    {
+        self.stages.push((name.into(), Arc::new(processor)));
+        self
+    }
+
+    /// Returns the names of the stages in the order they run.
+    pub fn stage_names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Runs every stage on the annotations left by the stage before it,
+    /// returning the resulting annotations along with the number of
+    /// annotations going in and out of every stage.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::Stage`] naming the first stage that fails,
+    /// without running the stages after it.
+    pub fn run(&self, annotations: Annotations) -> Result<(Annotations, Vec<StageCount>)> {
+        self.run_summarized(annotations, &mut ProcessingSummary::new())
+    }
+
+    /// Like [`run`](Pipeline::run), but also records what the stages did to
+    /// the annotations in `summary`.
+    ///
+    /// Annotations removed by a stage that does not record them are recorded
+    /// under the name of the stage.
+    ///
+    /// # Errors
+    ///
+    /// See [`run`](Pipeline::run). The summary is incomplete then.
+    pub fn run_summarized(
+        &self,
+        mut annotations: Annotations,
+        summary: &mut ProcessingSummary,
+    ) -> Result<(Annotations, Vec<StageCount>)> {
+        let mut counts = Vec::with_capacity(self.stages.len());
+        for (name, processor) in &self.stages {
+            let input = annotations.annotations.len();
+            let recorded = summary.dropped_total();
+            annotations = processor
+                .process_summarized(annotations, summary)
+                .map_err(|error| Error::Stage {
+                    stage: name.clone(),
+                    source: Box::new(error),
+                })?;
+            let unrecorded = input
+                .saturating_sub(annotations.annotations.len())
+                .saturating_sub(summary.dropped_total() - recorded);
+            summary.record_dropped(name.as_str(), unrecorded);
+            counts.push(StageCount {
+                name: name.clone(),
+                input,
+                output: annotations.annotations.len(),
+            });
+        }
+        Ok((annotations, counts))
+    }
+}
+
+/// Runs the pipeline as a single stage, e.g. of another pipeline.
+impl AnnotationProcessor for Pipeline {
+    fn process(&self, annotations: Annotations) -> Result<Annotations> {
+        self.run(annotations).map(|(annotations, _)| annotations)
+    }
+
+    fn process_summarized(
+        &self,
+        annotations: Annotations,
+        summary: &mut ProcessingSummary,
+    ) -> Result<Annotations> {
+        self.run_summarized(annotations, summary)
+            .map(|(annotations, _)| annotations)
+    }
+}
+
+/// The number of annotations going in and out of a stage of a [`Pipeline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageCount {
+    name: String,
+    input: usize,
+    output: usize,
+}
+
+impl StageCount {
+    /// Returns the name of the stage.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of annotations the stage was given.
+    pub fn input(&self) -> usize {
+        self.input
+    }
+
+    /// Returns the number of annotations the stage returned.
+    pub fn output(&self) -> usize {
+        self.output
+    }
+
+    /// Returns the number of annotations the stage removed, minus those it
+    /// added, such as summaries.
+    pub fn dropped(&self) -> usize {
+        self.input.saturating_sub(self.output)
+    }
+}
+
+/// The configuration of a [`Pipeline`], as read by
+/// [`Pipeline::from_config`].
+///
+/// ```json
+/// {
+///   "stages": [
+///     {"kind": "relativize_paths", "root": "/src/project"},
+///     {"kind": "collapse_duplicates", "scope": "per_file"},
+///     {"kind": "cap_per_file", "name": "budget", "max": 10, "overflow": "summarize"}
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PipelineConfig {
+    pub stages: Vec<StageConfig>,
+}
+
+/// A stage of a [`PipelineConfig`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StageConfig {
+    /// The name of the stage, which defaults to its kind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: StageKind,
+}
+
+impl StageConfig {
+    /// Returns the name of the stage, or its kind if it has no name.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.kind.name())
+    }
+}
+
+/// A rule of a [`StageKind::SeverityOverrides`] stage.
+#[cfg(feature = "glob")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SeverityRuleConfig {
+    pub pattern: String,
+    pub action: SeverityAction,
+    /// See [`SeverityOverrides::raise_caps`].
+    #[serde(default)]
+    pub raise_caps: bool,
+}
+
+/// The filter or transformation of a [`StageConfig`], each corresponding to
+/// a method of [`Annotations`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StageKind {
+    /// [`Annotations::cap_per_file`].
+    CapPerFile {
+        max: usize,
+        overflow: OverflowBehavior,
+    },
+
+    /// [`Annotations::collapse_duplicates`].
+    CollapseDuplicates { scope: CollapseScope },
+
+    /// [`Annotations::filter_paths`] with [`PathFilter::new`].
+    #[cfg(feature = "glob")]
+    FilterPaths {
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default = "keep_without_path")]
+        keep_without_path: bool,
+    },
+
+    /// [`Annotations::keep_top_by_severity`].
+    KeepTop { n: usize },
+
+    /// [`Annotations::apply_link_template`] with [`LinkTemplate::new`].
+    LinkTemplate { template: String },
+
+    /// [`Annotations::namespace_external_ids`] with
+    /// [`ExternalIdNamespace::new`].
+    NamespaceExternalIds { tool: String },
+
+    /// [`Annotations::redact`] with the built-in patterns of
+    /// [`Redactor::new`], unless `built_in` is `false`, and the additional
+    /// `patterns`.
+    #[cfg(feature = "regex")]
+    Redact {
+        #[serde(default = "built_in")]
+        built_in: bool,
+        #[serde(default)]
+        patterns: Vec<String>,
+    },
+
+    /// [`Annotations::relativize_paths`] with [`RepoPaths::new`].
+    RelativizePaths { root: PathBuf },
+
+    /// [`Annotations::retain_severity`].
+    RetainSeverity { min: Severity },
+
+    /// [`Annotations::rollup`].
+    Rollup {
+        budget: usize,
+        strategy: RollupStrategy,
+    },
+
+    /// [`Annotations::apply_severity_overrides`].
+    #[cfg(feature = "glob")]
+    SeverityOverrides { rules: Vec<SeverityRuleConfig> },
+
+    /// [`Annotations::suppress`] with the baseline read by
+    /// [`Baseline::from_json_file`].
+    #[cfg(feature = "fingerprint")]
+    Suppress { baseline: PathBuf },
+
+    /// [`Annotations::truncate_messages`].
+    TruncateMessages,
+}
+
+#[cfg(feature = "glob")]
+fn keep_without_path() -> bool {
+    true
+}
+
+#[cfg(feature = "regex")]
+fn built_in() -> bool {
+    true
+}
+
+impl StageKind {
+    /// Returns the name of the kind, as it is written in a configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StageKind::CapPerFile { .. } => "cap_per_file",
+            StageKind::CollapseDuplicates { .. } => "collapse_duplicates",
+            #[cfg(feature = "glob")]
+            StageKind::FilterPaths { .. } => "filter_paths",
+            StageKind::KeepTop { .. } => "keep_top",
+            StageKind::LinkTemplate { .. } => "link_template",
+            StageKind::NamespaceExternalIds { .. } => "namespace_external_ids",
+            #[cfg(feature = "regex")]
+            StageKind::Redact { .. } => "redact",
+            StageKind::RelativizePaths { .. } => "relativize_paths",
+            StageKind::RetainSeverity { .. } => "retain_severity",
+            StageKind::Rollup { .. } => "rollup",
+            #[cfg(feature = "glob")]
+            StageKind::SeverityOverrides { .. } => "severity_overrides",
+            #[cfg(feature = "fingerprint")]
+            StageKind::Suppress { .. } => "suppress",
+            StageKind::TruncateMessages => "truncate_messages",
+        }
+    }
+
+    fn compile(&self) -> Result<Stage> {
+        Ok(match self {
+            &StageKind::CapPerFile { max, overflow } => {
+                Arc::new(Transform::CapPerFile(max, overflow))
+            }
+            &StageKind::CollapseDuplicates { scope } => {
+                Arc::new(Transform::CollapseDuplicates(scope))
+            }
+            #[cfg(feature = "glob")]
+            StageKind::FilterPaths {
+                include,
+                exclude,
+                keep_without_path,
+            } => Arc::new(
+                PathFilter::new(&as_strs(include), &as_strs(exclude))?
+                    .keep_without_path(*keep_without_path),
+            ),
+            &StageKind::KeepTop { n } => Arc::new(Transform::KeepTop(n)),
+            StageKind::LinkTemplate { template } => Arc::new(LinkTemplate::new(template)?),
+            StageKind::NamespaceExternalIds { tool } => Arc::new(ExternalIdNamespace::new(tool)?),
+            #[cfg(feature = "regex")]
+            StageKind::Redact { built_in, patterns } => {
+                let redactor = if *built_in {
+                    Redactor::new()
+                } else {
+                    Redactor::empty()
+                };
+                Arc::new(
+                    patterns
+                        .iter()
+                        .try_fold(redactor, |redactor, pattern| redactor.pattern(pattern))?,
+                )
+            }
+            StageKind::RelativizePaths { root } => Arc::new(RepoPaths::new(root)),
+            &StageKind::RetainSeverity { min } => Arc::new(Transform::RetainSeverity(min)),
+            &StageKind::Rollup { budget, strategy } => {
+                Arc::new(Transform::Rollup(budget, strategy))
+            }
+            #[cfg(feature = "glob")]
+            StageKind::SeverityOverrides { rules } => Arc::new(rules.iter().try_fold(
+                SeverityOverrides::new(),
+                |overrides, rule| {
+                    if rule.raise_caps {
+                        overrides.raise_caps(&rule.pattern, rule.action)
+                    } else {
+                        overrides.rule(&rule.pattern, rule.action)
+                    }
+                },
+            )?),
+            #[cfg(feature = "fingerprint")]
+            StageKind::Suppress { baseline } => Arc::new(Baseline::from_json_file(baseline)?),
+            StageKind::TruncateMessages => Arc::new(Transform::TruncateMessages),
+        })
+    }
+}
+
+#[cfg(feature = "glob")]
+fn as_strs(patterns: &[String]) -> Vec<&str> {
+    patterns.iter().map(String::as_str).collect()
+}
+
+#[cfg(test)]
+mod stages {
+    use super::*;
+    use crate::{Annotation, AnnotationBuilder, Severity};
+
+    fn annotation(message: &str, path: &str, severity: Severity) -> Annotation {
+        AnnotationBuilder::new(message, severity)
+            .path(path)
+            .build()
+            .unwrap()
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            annotation("Unused import", "/src/project/src/lib.rs", Severity::Low),
+            annotation("Unused import", "/src/project/src/lib.rs", Severity::Low),
+            annotation("Possible panic", "/src/project/src/lib.rs", Severity::High),
+            annotation("Possible panic", "/src/project/src/main.rs", Severity::High),
+            annotation("Outside", "/elsewhere/main.rs", Severity::Medium),
+        ])
+    }
+
+    #[test]
+    fn counts() {
+        let pipeline = Pipeline::new()
+            .stage(
+                "relativize",
+                RepoPaths::new(std::path::Path::new("/src/project")),
+            )
+            .stage("dedup", |mut annotations: Annotations| {
+                annotations.collapse_duplicates(CollapseScope::PerFile);
+                Ok(annotations)
+            })
+            .stage("top", |annotations: Annotations| {
+                Ok(annotations.keep_top_by_severity(2).0)
+            });
+
+        let (annotations, counts) = pipeline.run(annotations()).unwrap();
+        assert_eq!(
+            vec![
+                ("relativize", 5, 4, 1),
+                ("dedup", 4, 3, 1),
+                ("top", 3, 2, 1)
+            ],
+            counts
+                .iter()
+                .map(|count| (count.name(), count.input(), count.output(), count.dropped()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Some("src/lib.rs"), Some("src/main.rs")],
+            annotations
+                .annotations
+                .iter()
+                .map(|annotation| annotation.path.as_deref())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn failing_stage() {
+        let ran = std::sync::atomic::AtomicBool::new(false);
+        let ran = std::sync::Arc::new(ran);
+        let last = ran.clone();
+        let pipeline = Pipeline::new()
+            .stage(
+                "relativize",
+                RepoPaths::new(std::path::Path::new("/src/project")),
+            )
+            .stage("check", |_: Annotations| -> Result<Annotations> {
+                Err(Error::InvalidValue {
+                    field: "path".to_owned(),
+                    reason: "not allowed".to_owned(),
+                })
+            })
+            .stage("last", move |annotations: Annotations| {
+                last.store(true, std::sync::atomic::Ordering::SeqCst);
+                Ok(annotations)
+            });
+
+        let error = pipeline.run(annotations()).unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                Error::Stage { stage, source }
+                    if stage == "check" && matches!(**source, Error::InvalidValue { .. })
+            ),
+            "{error}"
+        );
+        assert!(!ran.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn config() {
+        let config: PipelineConfig = serde_json::from_str(
+            r#"{
+                "stages": [
+                    {"kind": "relativize_paths", "root": "/src/project"},
+                    {"kind": "collapse_duplicates", "scope": "per_file"},
+                    {"kind": "keep_top", "name": "budget", "n": 2}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let pipeline = Pipeline::from_config(&config).unwrap();
+        assert_eq!(
+            vec!["relativize_paths", "collapse_duplicates", "budget"],
+            pipeline.stage_names().collect::<Vec<_>>()
+        );
+
+        let (annotations, counts) = pipeline.run(annotations()).unwrap();
+        assert_eq!(2, annotations.annotations.len());
+        assert_eq!(
+            vec![1, 1, 1],
+            counts.iter().map(StageCount::dropped).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_config() {
+        let config = PipelineConfig {
+            stages: vec![StageConfig {
+                name: Some("links".to_owned()),
+                kind: StageKind::LinkTemplate {
+                    template: "https://example.com/{unknown}".to_owned(),
+                },
+            }],
+        };
+        let error = Pipeline::from_config(&config).unwrap_err();
+        assert!(
+            matches!(&error, Error::Stage { stage, .. } if stage == "links"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use std::time::Duration;
+
+use crate::annotation::{Severity, Type};
+use crate::error::{Error, Result};
+use crate::report::{
+    Data, Percentage, Report, ReportBuilder, ReportResult, ReportType, DATA_LIMIT,
+};
+use crate::stats::AnnotationStats;
+
+impl Data {
+    /// Returns the number of passed, failed and skipped tests as three data
+    /// fields, i.e. half of the [`DATA_LIMIT`].
+    pub fn tests_summary(passed: u64, failed: u64, skipped: u64) -> Vec<Data> {
+        vec![
+            Data::number("Passed", passed),
+            Data::number("Failed", failed),
+            Data::number("Skipped", skipped),
+        ]
+    }
+
+    /// Returns the line coverage and, if it is known, the branch coverage as
+    /// one or two data fields of the [`DATA_LIMIT`].
+    pub fn coverage_summary(line: Percentage, branch: Option<Percentage>) -> Vec<Data> {
+        let mut fields = vec![Data::new("Line coverage", line.into())];
+        if let Some(branch) = branch {
+            fields.push(Data::new("Branch coverage", branch.into()));
+        }
+        fields
+    }
+
+    /// Returns the number of annotations per severity as three data fields,
+    /// i.e. half of the [`DATA_LIMIT`].
+    pub fn findings_summary(stats: &AnnotationStats) -> Vec<Data> {
+        let count = |severity| stats.severity(severity) as u64;
+        vec![
+            Data::number("High severity", count(Severity::High)),
+            Data::number("Medium severity", count(Severity::Medium)),
+            Data::number("Low severity", count(Severity::Low)),
+        ]
+    }
+}
+
+/// The titles of the data fields generated from [`AnnotationStats`] by
+/// [`ReportBuilder::data_from_stats`], e.g. to translate them. A field whose
+/// title is `None` is left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsDataOptions {
+    pub high: Option<String>,
+    pub medium: Option<String>,
+    pub low: Option<String>,
+    pub vulnerabilities: Option<String>,
+    pub bugs: Option<String>,
+    pub files: Option<String>,
+}
+
+impl Default for StatsDataOptions {
+    fn default() -> Self {
+        StatsDataOptions {
+            high: Some("High severity".to_owned()),
+            medium: Some("Medium severity".to_owned()),
+            low: Some("Low severity".to_owned()),
+            vulnerabilities: Some("Vulnerabilities".to_owned()),
+            bugs: Some("Bugs".to_owned()),
+            files: Some("Files affected".to_owned()),
+        }
+    }
+}
+
+impl ReportBuilder {
+    /// Sets the data fields to the number of annotations per severity, the
+    /// number of vulnerabilities and bugs and the number of annotated files,
+    /// titled as by `options`.
+    ///
+    /// There are at most six fields, which is the [`DATA_LIMIT`].
+    pub fn data_from_stats(mut self, stats: &AnnotationStats, options: &StatsDataOptions) -> Self {
+        self.set_data_from_stats(stats, options);
+        self
+    }
+
+    /// Like [`data_from_stats`](ReportBuilder::data_from_stats).
+    pub fn set_data_from_stats(
+        &mut self,
+        stats: &AnnotationStats,
+        options: &StatsDataOptions,
+    ) -> &mut Self {
+        let fields = [
+            (&options.high, stats.severity(Severity::High)),
+            (&options.medium, stats.severity(Severity::Medium)),
+            (&options.low, stats.severity(Severity::Low)),
+            (
+                &options.vulnerabilities,
+                stats.annotation_type(Type::Vulnerability),
+            ),
+            (&options.bugs, stats.annotation_type(Type::Bug)),
+            (&options.files, stats.files()),
+        ];
+        self.set_data(
+            fields
+                .into_iter()
+                .filter_map(|(title, count)| Some(Data::number(title.as_deref()?, count as u64)))
+                .take(DATA_LIMIT),
+        )
+    }
+}
+
+/// Appends the `extra` data fields to the `existing` ones, e.g. to combine
+/// the summaries of [`Data`].
+///
+/// # Errors
+///
+/// Will return `Err` if there would be more than [`DATA_LIMIT`] data fields,
+/// in which case `existing` is left unchanged.
+pub fn fit_data(existing: &mut Vec<Data>, extra: Vec<Data>) -> Result<()> {
+    let len = existing.len() + extra.len();
+    if len > DATA_LIMIT {
+        return Err(Error::FieldTooLong {
+            name: "data".to_owned(),
+            len,
+            limit: DATA_LIMIT,
+        });
+    }
+    existing.extend(extra);
+    Ok(())
+}
+
+impl Report {
+    /// Assembles a coverage report by `tool` with the coverage and the
+    /// threshold as data fields, which passes if the coverage is at least
+    /// `threshold` percent.
+    ///
+    /// The report has two data fields, so more can be added with
+    /// [`add_data`](Report::add_data).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `threshold` is greater than 100, or if `title` or
+    /// `tool` are too long, see [`ReportBuilder::build`].
+    pub fn coverage_preset<T, R>(
+        title: T,
+        tool: R,
+        coverage: Percentage,
+        threshold: u8,
+    ) -> Result<Report>
+    where
+        T: Into<String>,
+        R: Into<String>,
+    {
+        let threshold = Percentage::new(threshold)?;
+        let result = if coverage >= threshold {
+            ReportResult::Pass
+        } else {
+            ReportResult::Fail
+        };
+
+        ReportBuilder::new(title)
+            .reporter(tool)
+            .report_type(ReportType::Coverage)
+            .result(result)
+            .data(vec![
+                Data::new("Coverage", coverage.into()),
+                Data::new("Threshold", threshold.into()),
+            ])
+            .build()
+    }
+
+    /// Assembles a security report by `tool` with the number of annotations
+    /// per severity and the number of vulnerabilities as data fields, which
+    /// fails if there are any high severity annotations.
+    ///
+    /// The report has four data fields, so more can be added with
+    /// [`add_data`](Report::add_data).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `title` or `tool` are too long, see
+    /// [`ReportBuilder::build`].
+    pub fn security_preset<T, R>(title: T, tool: R, stats: &AnnotationStats) -> Result<Report>
+    where
+        T: Into<String>,
+        R: Into<String>,
+    {
+        let result = if stats.severity(Severity::High) > 0 {
+            ReportResult::Fail
+        } else {
+            ReportResult::Pass
+        };
+        let mut fields = Data::findings_summary(stats);
+        fields.push(Data::number(
+            "Vulnerabilities",
+            stats.annotation_type(Type::Vulnerability) as u64,
+        ));
+
+        ReportBuilder::new(title)
+            .reporter(tool)
+            .report_type(ReportType::Security)
+            .result(result)
+            .data(fields)
+            .build()
+    }
+
+    /// Assembles a test report by `tool` with the number of passed, failed and
+    /// skipped tests and the duration of the test run as data fields, which
+    /// fails if any test failed.
+    ///
+    /// The report has four data fields, so more can be added with
+    /// [`add_data`](Report::add_data).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `title` or `tool` are too long, see
+    /// [`ReportBuilder::build`].
+    pub fn test_preset<T, R>(
+        title: T,
+        tool: R,
+        passed: u64,
+        failed: u64,
+        skipped: u64,
+        duration: Duration,
+    ) -> Result<Report>
+    where
+        T: Into<String>,
+        R: Into<String>,
+    {
+        let result = if failed > 0 {
+            ReportResult::Fail
+        } else {
+            ReportResult::Pass
+        };
+        let millis = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+
+        ReportBuilder::new(title)
+            .reporter(tool)
+            .report_type(ReportType::Test)
+            .result(result)
+            .data(
+                [
+                    Data::tests_summary(passed, failed, skipped),
+                    vec![Data::duration("Duration", millis)],
+                ]
+                .concat(),
+            )
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod presets {
+    use super::*;
+    use crate::{AnnotationBuilder, Annotations, Error, DATA_LIMIT};
+    use serde_json::json;
+
+    fn percentage(value: u8) -> Percentage {
+        Percentage::new(value).unwrap()
+    }
+
+    #[test]
+    fn coverage() {
+        let report = Report::coverage_preset("Coverage", "tarpaulin", percentage(84), 80).unwrap();
+        let expected = json!({
+            "title": "Coverage",
+            "reporter": "tarpaulin",
+            "reportType": "COVERAGE",
+            "result": "PASS",
+            "data": [
+                {"title": "Coverage", "type": "PERCENTAGE", "value": 84},
+                {"title": "Threshold", "type": "PERCENTAGE", "value": 80},
+            ],
+        });
+        assert_eq!(expected, serde_json::to_value(report).unwrap());
+    }
+
+    #[test]
+    fn coverage_threshold() {
+        let result = |coverage, threshold| {
+            Report::coverage_preset("Coverage", "tarpaulin", percentage(coverage), threshold)
+                .unwrap()
+                .result
+        };
+        assert_eq!(Some(ReportResult::Pass), result(80, 80));
+        assert_eq!(Some(ReportResult::Fail), result(79, 80));
+        assert_eq!(Some(ReportResult::Pass), result(0, 0));
+        assert_eq!(Some(ReportResult::Fail), result(99, 100));
+
+        let invalid = Report::coverage_preset("Coverage", "tarpaulin", percentage(50), 101);
+        assert!(matches!(invalid, Err(Error::InvalidValue { .. })));
+        assert!(Percentage::new(101).is_err());
+    }
+
+    #[test]
+    fn security() {
+        let annotations = Annotations::new(vec![
+            AnnotationBuilder::new("SQL injection", Severity::High)
+                .annotation_type(Type::Vulnerability)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Weak hash", Severity::Medium)
+                .annotation_type(Type::Vulnerability)
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Unused import", Severity::Low)
+                .build()
+                .unwrap(),
+        ]);
+        let report = Report::security_preset("Security", "semgrep", &annotations.stats()).unwrap();
+        let expected = json!({
+            "title": "Security",
+            "reporter": "semgrep",
+            "reportType": "SECURITY",
+            "result": "FAIL",
+            "data": [
+                {"title": "High severity", "type": "NUMBER", "value": 1},
+                {"title": "Medium severity", "type": "NUMBER", "value": 1},
+                {"title": "Low severity", "type": "NUMBER", "value": 1},
+                {"title": "Vulnerabilities", "type": "NUMBER", "value": 2},
+            ],
+        });
+        assert_eq!(expected, serde_json::to_value(report).unwrap());
+
+        let clean = Report::security_preset("Security", "semgrep", &AnnotationStats::default());
+        assert_eq!(Some(ReportResult::Pass), clean.unwrap().result);
+    }
+
+    #[test]
+    fn test() {
+        let duration = Duration::from_millis(83_250);
+        let report = Report::test_preset("Tests", "cargo test", 120, 0, 3, duration).unwrap();
+        let expected = json!({
+            "title": "Tests",
+            "reporter": "cargo test",
+            "reportType": "TEST",
+            "result": "PASS",
+            "data": [
+                {"title": "Passed", "type": "NUMBER", "value": 120},
+                {"title": "Failed", "type": "NUMBER", "value": 0},
+                {"title": "Skipped", "type": "NUMBER", "value": 3},
+                {"title": "Duration", "type": "DURATION", "value": 83250},
+            ],
+        });
+        assert_eq!(expected, serde_json::to_value(report).unwrap());
+
+        let failed = Report::test_preset("Tests", "cargo test", 120, 1, 3, duration);
+        assert_eq!(Some(ReportResult::Fail), failed.unwrap().result);
+    }
+
+    #[test]
+    fn add_data_up_to_limit() {
+        let mut report =
+            Report::test_preset("Tests", "cargo test", 1, 0, 0, Duration::ZERO).unwrap();
+        let extra = || Data::text("Commit", "abc123");
+        assert!(report
+            .add_data(extra())
+            .and_then(|report| report.add_data(extra()))
+            .is_ok());
+        assert!(matches!(
+            report.add_data(extra()),
+            Err(Error::FieldTooLong { .. })
+        ));
+        assert_eq!(Some(DATA_LIMIT), report.data.as_ref().map(Vec::len));
+    }
+
+    #[test]
+    fn summaries() {
+        assert_eq!(
+            json!([
+                {"title": "Passed", "type": "NUMBER", "value": 120},
+                {"title": "Failed", "type": "NUMBER", "value": 2},
+                {"title": "Skipped", "type": "NUMBER", "value": 5},
+            ]),
+            serde_json::to_value(Data::tests_summary(120, 2, 5)).unwrap()
+        );
+        assert_eq!(
+            json!([
+                {"title": "Line coverage", "type": "PERCENTAGE", "value": 84},
+                {"title": "Branch coverage", "type": "PERCENTAGE", "value": 61},
+            ]),
+            serde_json::to_value(Data::coverage_summary(percentage(84), Some(percentage(61))))
+                .unwrap()
+        );
+        assert_eq!(1, Data::coverage_summary(percentage(84), None).len());
+
+        let stats = Annotations::new(vec![AnnotationBuilder::new("Panic", Severity::High)
+            .build()
+            .unwrap()])
+        .stats();
+        assert_eq!(
+            json!([
+                {"title": "High severity", "type": "NUMBER", "value": 1},
+                {"title": "Medium severity", "type": "NUMBER", "value": 0},
+                {"title": "Low severity", "type": "NUMBER", "value": 0},
+            ]),
+            serde_json::to_value(Data::findings_summary(&stats)).unwrap()
+        );
+    }
+
+    #[test]
+    fn data_from_stats() {
+        let stats = Annotations::new(vec![
+            AnnotationBuilder::new("Injection", Severity::High)
+                .annotation_type(Type::Vulnerability)
+                .path("src/db.rs")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Panic", Severity::Medium)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Unused", Severity::Low)
+                .path("src/lib.rs")
+                .build()
+                .unwrap(),
+        ])
+        .stats();
+
+        let report = ReportBuilder::new("Analysis")
+            .data_from_stats(&stats, &StatsDataOptions::default())
+            .build()
+            .unwrap();
+        assert!(report.data().len() <= DATA_LIMIT);
+        assert_eq!(
+            json!([
+                {"title": "High severity", "type": "NUMBER", "value": 1},
+                {"title": "Medium severity", "type": "NUMBER", "value": 1},
+                {"title": "Low severity", "type": "NUMBER", "value": 1},
+                {"title": "Vulnerabilities", "type": "NUMBER", "value": 1},
+                {"title": "Bugs", "type": "NUMBER", "value": 1},
+                {"title": "Files affected", "type": "NUMBER", "value": 2},
+            ]),
+            serde_json::to_value(report.data()).unwrap()
+        );
+
+        let options = StatsDataOptions {
+            high: Some("Hoch".to_owned()),
+            medium: Some("Mittel".to_owned()),
+            low: Some("Niedrig".to_owned()),
+            vulnerabilities: None,
+            bugs: None,
+            files: Some("Betroffene Dateien".to_owned()),
+        };
+        let mut builder = ReportBuilder::new("Analyse");
+        builder.set_data_from_stats(&stats, &options);
+        let report = builder.build().unwrap();
+        assert_eq!(
+            vec!["Hoch", "Mittel", "Niedrig", "Betroffene Dateien"],
+            report
+                .data()
+                .iter()
+                .map(|field| field.title.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fit() {
+        let mut fields = Data::tests_summary(1, 0, 0);
+        fields.extend(Data::coverage_summary(percentage(84), Some(percentage(61))));
+        assert_eq!(5, fields.len());
+
+        let error = fit_data(&mut fields, Data::tests_summary(1, 0, 0)).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::FieldTooLong {
+                len: 8,
+                limit: DATA_LIMIT,
+                ..
+            }
+        ));
+        assert_eq!(5, fields.len());
+
+        fit_data(&mut fields, Data::coverage_summary(percentage(90), None)).unwrap();
+        assert_eq!(DATA_LIMIT, fields.len());
+        assert_eq!("Line coverage", fields[5].title);
+        assert!(fit_data(&mut fields, vec![]).is_ok());
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::{Data, Parameter};
+
+/// What filtering and transforming did to the annotations of a report, to
+/// answer why a finding is missing from it.
+///
+/// A [`Pipeline`](crate::Pipeline) records into a summary with
+/// [`run_summarized`](crate::Pipeline::run_summarized), where the stages of
+/// this crate record the annotations they remove under these reasons:
+///
+/// | Reason | Stage |
+/// | --- | --- |
+/// | `baseline` | [`Annotations::suppress`](crate::Annotations::suppress) |
+/// | `budget` | [`Annotations::keep_top_by_severity`](crate::Annotations::keep_top_by_severity) |
+/// | `duplicate` | [`Annotations::collapse_duplicates`](crate::Annotations::collapse_duplicates) |
+/// | `outside diff` | `Annotations::retain_changed` |
+/// | `outside repository` | [`Annotations::relativize_paths`](crate::Annotations::relativize_paths) |
+/// | `path filter` | `Annotations::filter_paths` |
+/// | `per-file cap` | [`Annotations::cap_per_file`](crate::Annotations::cap_per_file) |
+/// | `rollup` | [`Annotations::rollup`](crate::Annotations::rollup) |
+/// | `severity` | [`Annotations::retain_severity`](crate::Annotations::retain_severity) |
+/// | `severity override` | `Annotations::apply_severity_overrides` |
+///
+/// Annotations removed by other stages are recorded under the name of the
+/// stage. Annotations that are summarized count as removed, since they are
+/// no longer shown on their own. When transforming annotations without a
+/// pipeline, the counts returned by the methods can be recorded explicitly.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProcessingSummary {
+    #[serde(default)]
+    dropped: BTreeMap<String, usize>,
+    #[serde(default)]
+    truncated: usize,
+    #[serde(default)]
+    severities_changed: usize,
+    #[serde(default)]
+    redacted: usize,
+}
+
+impl ProcessingSummary {
+    /// Constructs an empty summary.
+    pub fn new() -> Self {
+        ProcessingSummary::default()
+    }
+
+    /// Records that `count` annotations were removed for `reason`.
+    pub fn record_dropped<R: Into<String>>(&mut self, reason: R, count: usize) {
+        if count > 0 {
+            *self.dropped.entry(reason.into()).or_default() += count;
+        }
+    }
+
+    /// Records that `count` messages were truncated.
+    pub fn record_truncated(&mut self, count: usize) {
+        self.truncated += count;
+    }
+
+    /// Records that the severity of `count` annotations was changed.
+    pub fn record_severities_changed(&mut self, count: usize) {
+        self.severities_changed += count;
+    }
+
+    /// Records that `count` annotations were redacted.
+    pub fn record_redacted(&mut self, count: usize) {
+        self.redacted += count;
+    }
+
+    /// Adds the counts of `other` to this summary.
+    pub fn merge(&mut self, other: &ProcessingSummary) {
+        for (reason, count) in &other.dropped {
+            self.record_dropped(reason.as_str(), *count);
+        }
+        self.truncated += other.truncated;
+        self.severities_changed += other.severities_changed;
+        self.redacted += other.redacted;
+    }
+
+    /// Returns the number of annotations removed for `reason`.
+    pub fn dropped(&self, reason: &str) -> usize {
+        self.dropped.get(reason).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of removed annotations by reason.
+    pub fn dropped_by_reason(&self) -> &BTreeMap<String, usize> {
+        &self.dropped
+    }
+
+    /// Returns the number of removed annotations.
+    pub fn dropped_total(&self) -> usize {
+        self.dropped.values().sum()
+    }
+
+    /// Returns the number of truncated messages.
+    pub fn truncated(&self) -> usize {
+        self.truncated
+    }
+
+    /// Returns the number of annotations whose severity was changed.
+    pub fn severities_changed(&self) -> usize {
+        self.severities_changed
+    }
+
+    /// Returns the number of redacted annotations.
+    pub fn redacted(&self) -> usize {
+        self.redacted
+    }
+
+    /// Returns `true` if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.dropped.is_empty()
+            && self.truncated == 0
+            && self.severities_changed == 0
+            && self.redacted == 0
+    }
+
+    /// Returns a data field stating the number of removed annotations, e.g.
+    /// "Findings hidden: 212", or `None` if no annotations were removed.
+    pub fn to_data(&self) -> Option<Data> {
+        let hidden = self.dropped_total();
+        (hidden > 0).then(|| Data {
+            title: "Findings hidden".to_owned(),
+            parameter: Parameter::Number(hidden.into()),
+        })
+    }
+}
+
+/// Describes the summary on one line for a build log, e.g. `212 findings
+/// hidden (outside diff: 120, per-file cap: 92), 3 messages truncated`.
+impl fmt::Display for ProcessingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no findings hidden or changed");
+        }
+
+        let mut parts = Vec::new();
+        if !self.dropped.is_empty() {
+            let reasons = self
+                .dropped
+                .iter()
+                .map(|(reason, count)| format!("{reason}: {count}"))
+                .collect::<Vec<_>>();
+            parts.push(format!(
+                "{} hidden ({})",
+                counted(self.dropped_total(), "finding", "findings"),
+                reasons.join(", ")
+            ));
+        }
+        if self.truncated > 0 {
+            parts.push(format!(
+                "{} truncated",
+                counted(self.truncated, "message", "messages")
+            ));
+        }
+        if self.severities_changed > 0 {
+            parts.push(format!(
+                "{} changed",
+                counted(self.severities_changed, "severity", "severities")
+            ));
+        }
+        if self.redacted > 0 {
+            parts.push(format!(
+                "{} redacted",
+                counted(self.redacted, "finding", "findings")
+            ));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+fn counted(count: usize, singular: &str, plural: &str) -> String {
+    format!("{count} {}", if count == 1 { singular } else { plural })
+}
+
+#[cfg(test)]
+mod summaries {
+    use super::*;
+    use crate::{
+        Annotation, AnnotationBuilder, Annotations, CollapseScope, Pipeline, PipelineConfig,
+        Severity, StageConfig, StageKind, MESSAGE_LIMIT,
+    };
+
+    fn annotation(message: &str, path: &str, severity: Severity) -> Annotation {
+        AnnotationBuilder::new(message, severity)
+            .path(path)
+            .build()
+            .unwrap()
+    }
+
+    fn stage(kind: StageKind) -> StageConfig {
+        StageConfig { name: None, kind }
+    }
+
+    fn pipeline() -> Pipeline {
+        let config = PipelineConfig {
+            stages: vec![
+                stage(StageKind::RelativizePaths {
+                    root: "/src/project".into(),
+                }),
+                stage(StageKind::CollapseDuplicates {
+                    scope: CollapseScope::PerFile,
+                }),
+                stage(StageKind::TruncateMessages),
+                stage(StageKind::KeepTop { n: 3 }),
+            ],
+        };
+        Pipeline::from_config(&config)
+            .unwrap()
+            .stage("no low", |mut annotations: Annotations| {
+                annotations
+                    .annotations
+                    .retain(|annotation| annotation.severity != Severity::Low);
+                Ok(annotations)
+            })
+    }
+
+    #[test]
+    fn pipeline_summary() {
+        let mut long = annotation("Long", "/src/project/src/main.rs", Severity::Medium);
+        long.message = "X".repeat(MESSAGE_LIMIT + 1);
+        let annotations = Annotations::new(vec![
+            annotation("Unused import", "/src/project/src/lib.rs", Severity::Low),
+            annotation("Unused import", "/src/project/src/lib.rs", Severity::Low),
+            annotation("Possible panic", "/src/project/src/lib.rs", Severity::High),
+            long,
+            annotation("Outside", "/elsewhere/main.rs", Severity::Medium),
+            annotation("Typo", "/src/project/src/main.rs", Severity::Low),
+        ]);
+
+        let mut summary = ProcessingSummary::new();
+        let (annotations, _) = pipeline()
+            .run_summarized(annotations, &mut summary)
+            .unwrap();
+        assert_eq!(2, annotations.annotations.len());
+
+        assert_eq!(
+            vec![
+                ("budget", 1),
+                ("duplicate", 1),
+                ("no low", 1),
+                ("outside repository", 1)
+            ],
+            summary
+                .dropped_by_reason()
+                .iter()
+                .map(|(reason, count)| (reason.as_str(), *count))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(4, summary.dropped_total());
+        assert_eq!(1, summary.truncated());
+        assert_eq!(0, summary.severities_changed());
+        assert_eq!(
+            "4 findings hidden (budget: 1, duplicate: 1, no low: 1, outside repository: 1), \
+             1 message truncated",
+            summary.to_string()
+        );
+
+        let data = summary.to_data().unwrap();
+        assert_eq!("Findings hidden", data.title);
+        assert_eq!(Parameter::Number(4u64.into()), data.parameter);
+    }
+
+    #[test]
+    fn explicit() {
+        let mut summary = ProcessingSummary::new();
+        assert!(summary.is_empty());
+        assert_eq!(None, summary.to_data());
+        assert_eq!("no findings hidden or changed", summary.to_string());
+
+        let mut annotations = Annotations::new(vec![
+            annotation("Unused import", "src/lib.rs", Severity::Low),
+            annotation("Unused import", "src/lib.rs", Severity::Low),
+        ]);
+        summary.record_dropped(
+            "duplicate",
+            annotations.collapse_duplicates(CollapseScope::Global),
+        );
+        summary.record_dropped("unused", 0);
+        summary.record_severities_changed(2);
+
+        let mut other = ProcessingSummary::new();
+        other.record_dropped("duplicate", 2);
+        other.record_redacted(1);
+        summary.merge(&other);
+        assert_eq!(3, summary.dropped("duplicate"));
+        assert_eq!(0, summary.dropped("unused"));
+        assert_eq!(
+            "3 findings hidden (duplicate: 3), 2 severities changed, 1 finding redacted",
+            summary.to_string()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialization() {
+        let mut summary = ProcessingSummary::new();
+        summary.record_dropped("outside diff", 212);
+        summary.record_truncated(3);
+
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(
+            r#"{"dropped":{"outside diff":212},"truncated":3,"severities_changed":0,"redacted":0}"#,
+            json
+        );
+        assert_eq!(
+            summary,
+            serde_json::from_str::<ProcessingSummary>(&json).unwrap()
+        );
+    }
+}
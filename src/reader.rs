@@ -0,0 +1,253 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read};
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+use crate::annotation::{Annotation, Annotations};
+use crate::error::{Error, Result};
+
+/// Default maximum size of the input of [`Annotations::from_reader`].
+pub const READ_LIMIT_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Default maximum number of annotations read by
+/// [`Annotations::from_reader`].
+pub const READ_LIMIT_ANNOTATIONS: usize = 1_000_000;
+
+/// Fails reading once more than `remaining` bytes have been read.
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+    exceeded: bool,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            // Any further byte means that the input is too large.
+            if self.inner.read(&mut [0])? == 0 {
+                return Ok(0);
+            }
+            self.exceeded = true;
+            return Err(io::Error::other("byte limit exceeded"));
+        }
+        let len = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let read = self.inner.read(&mut buf[..len])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Deserializes annotations, failing once there are more than `max`.
+struct LimitedAnnotations<'a> {
+    max: usize,
+    exceeded: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for LimitedAnnotations<'_> {
+    type Value = Vec<Annotation>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LimitedAnnotations<'_> {
+    type Value = Vec<Annotation>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("annotations")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut annotations = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "annotations" {
+                let seed = LimitedSeq {
+                    max: self.max,
+                    exceeded: self.exceeded,
+                };
+                annotations = Some(map.next_value_seed(seed)?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        annotations.ok_or_else(|| de::Error::missing_field("annotations"))
+    }
+}
+
+struct LimitedSeq<'a> {
+    max: usize,
+    exceeded: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for LimitedSeq<'_> {
+    type Value = Vec<Annotation>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LimitedSeq<'_> {
+    type Value = Vec<Annotation>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of annotations")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut annotations = Vec::new();
+        while let Some(annotation) = seq.next_element::<Annotation>()? {
+            if annotations.len() == self.max {
+                self.exceeded.set(true);
+                return Err(de::Error::custom("annotation limit exceeded"));
+            }
+            annotations.push(annotation);
+        }
+        Ok(annotations)
+    }
+}
+
+impl Annotations {
+    /// Reads annotations as JSON from `reader`, with the default limits of
+    /// [`READ_LIMIT_BYTES`] and [`READ_LIMIT_ANNOTATIONS`].
+    ///
+    /// # Errors
+    ///
+    /// See [`from_reader_limited`](Annotations::from_reader_limited).
+    pub fn from_reader<R: Read>(reader: R) -> Result<Annotations> {
+        Annotations::from_reader_limited(reader, READ_LIMIT_BYTES, READ_LIMIT_ANNOTATIONS)
+    }
+
+    /// Reads annotations as JSON from `reader`, failing as soon as more than
+    /// `max_bytes` bytes or more than `max_annotations` annotations are read.
+    ///
+    /// The input is deserialized while it is read, so that a misbehaving
+    /// analyzer cannot exhaust the memory with a huge file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if either limit is exceeded, if reading fails, if
+    /// the input are not valid annotations, or if the fields of any
+    /// annotation are longer than Bitbucket allows.
+    pub fn from_reader_limited<R: Read>(
+        reader: R,
+        max_bytes: u64,
+        max_annotations: usize,
+    ) -> Result<Annotations> {
+        let mut reader = LimitedReader {
+            inner: reader,
+            remaining: max_bytes,
+            exceeded: false,
+        };
+        let exceeded = Cell::new(false);
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut reader);
+        let result = LimitedAnnotations {
+            max: max_annotations,
+            exceeded: &exceeded,
+        }
+        .deserialize(&mut deserializer)
+        .and_then(|annotations| deserializer.end().map(|()| annotations));
+
+        let annotations = match result {
+            Ok(annotations) => annotations,
+            Err(_) if reader.exceeded => {
+                return Err(Error::LimitExceeded {
+                    limit: max_bytes,
+                    unit: "bytes".to_owned(),
+                })
+            }
+            Err(_) if exceeded.get() => {
+                return Err(Error::LimitExceeded {
+                    limit: max_annotations as u64,
+                    unit: "annotations".to_owned(),
+                })
+            }
+            Err(error) => return Err(error.into()),
+        };
+        for annotation in &annotations {
+            annotation.validate_fields()?;
+        }
+        Ok(Annotations::new(annotations))
+    }
+}
+
+#[cfg(test)]
+mod limited_reading {
+    use super::*;
+    use crate::{AnnotationBuilder, Severity};
+
+    fn json(count: usize) -> Vec<u8> {
+        let annotations = (0..count)
+            .map(|index| {
+                AnnotationBuilder::new(format!("Finding {index}"), Severity::Low)
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_vec(&Annotations::new(annotations)).unwrap()
+    }
+
+    #[test]
+    fn within_limits() {
+        let json = json(3);
+        let annotations =
+            Annotations::from_reader_limited(&json[..], json.len() as u64, 3).unwrap();
+        assert_eq!(3, annotations.annotations.len());
+        assert_eq!(annotations, Annotations::from_reader(&json[..]).unwrap());
+    }
+
+    #[test]
+    fn byte_limit() {
+        let json = json(3);
+        let error =
+            Annotations::from_reader_limited(&json[..], json.len() as u64 - 1, 10).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::LimitExceeded { limit, ref unit } if limit == json.len() as u64 - 1 && unit == "bytes"
+        ));
+
+        let mut padded = json.clone();
+        padded.extend_from_slice(b"   ");
+        let error =
+            Annotations::from_reader_limited(&padded[..], json.len() as u64, 10).unwrap_err();
+        assert!(matches!(error, Error::LimitExceeded { .. }));
+    }
+
+    #[test]
+    fn annotation_limit() {
+        let json = json(3);
+        let error = Annotations::from_reader_limited(&json[..], u64::MAX, 2).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::LimitExceeded { limit: 2, ref unit } if unit == "annotations"
+        ));
+        assert_eq!(
+            "input exceeds the limit of 2 annotations",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let result = Annotations::from_reader(&b"{\"annotations\": [{}]}"[..]);
+        assert!(matches!(result, Err(Error::SerdeError(_))));
+        let result = Annotations::from_reader(&b"{}"[..]);
+        assert!(matches!(result, Err(Error::SerdeError(_))));
+    }
+}
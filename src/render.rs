@@ -0,0 +1,180 @@
+use std::fmt;
+
+use crate::annotation::Annotation;
+use crate::report::{Data, Parameter, Report, ReportResult};
+
+/// Formats a Unix timestamp in milliseconds as a UTC date.
+pub(crate) fn render_date(millis: u64) -> String {
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = millis / 86_400_000 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+pub(crate) fn render_duration(millis: u64) -> String {
+    let seconds = millis / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, _) => format!("{minutes}m {seconds}s"),
+        _ => format!("{hours}h {minutes}m {seconds}s"),
+    }
+}
+
+/// Formats the value for humans, e.g. `Yes`, `57%` or `2m 5s`.
+impl fmt::Display for Parameter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Boolean(true) => formatter.write_str("Yes"),
+            Parameter::Boolean(false) => formatter.write_str("No"),
+            Parameter::Date(millis) => formatter.write_str(&render_date(*millis)),
+            Parameter::Duration(millis) => formatter.write_str(&render_duration(*millis)),
+            Parameter::Link { linktext, href } => write!(formatter, "{linktext} ({href})"),
+            Parameter::Number(number) => write!(formatter, "{number}"),
+            Parameter::Percentage(percentage) => write!(formatter, "{percentage}%"),
+            Parameter::Text(text) => formatter.write_str(text),
+        }
+    }
+}
+
+/// Formats the data field for humans as `title: value`.
+impl fmt::Display for Data {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {}", self.title, self.parameter)
+    }
+}
+
+/// Formats the annotation for humans as `severity path:line – message`, e.g.
+/// for CI logs, leaving out the parts that are not set.
+///
+/// This is not a stable format and must not be parsed; serialize the
+/// annotation as JSON for that.
+impl fmt::Display for Annotation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.severity.label())?;
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => write!(formatter, " {path}:{line}")?,
+            (Some(path), None) => write!(formatter, " {path}")?,
+            (None, Some(line)) => write!(formatter, " line {line}")?,
+            (None, None) => {}
+        }
+        write!(formatter, " – {}", self.message)
+    }
+}
+
+/// Formats a short summary of the report for humans, e.g. for CI logs, with
+/// the title, result and reporter on the first line and a line per data
+/// field.
+///
+/// This is not a stable format and must not be parsed; serialize the report
+/// as JSON for that.
+impl fmt::Display for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.title)?;
+        match self.result {
+            Some(ReportResult::Pass) => formatter.write_str(" [Passed]")?,
+            Some(ReportResult::Fail) => formatter.write_str(" [Failed]")?,
+            None => {}
+        }
+        if let Some(reporter) = &self.reporter {
+            write!(formatter, " by {reporter}")?;
+        }
+        for data in self.data.iter().flatten() {
+            write!(formatter, "\n  {data}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod display {
+    use super::*;
+    use crate::{AnnotationBuilder, ReportBuilder, Severity};
+
+    #[test]
+    fn annotation() {
+        let annotation = AnnotationBuilder::new("Possible panic", Severity::High)
+            .path("src/lib.rs")
+            .line(40)
+            .build()
+            .unwrap();
+        assert_eq!(
+            "High src/lib.rs:40 – Possible panic",
+            annotation.to_string()
+        );
+
+        let annotation = AnnotationBuilder::new("Unused file", Severity::Low)
+            .path("src/old.rs")
+            .build()
+            .unwrap();
+        assert_eq!("Low src/old.rs – Unused file", annotation.to_string());
+
+        let annotation = AnnotationBuilder::new("Slow build", Severity::Medium)
+            .build()
+            .unwrap();
+        assert_eq!("Medium – Slow build", annotation.to_string());
+    }
+
+    #[test]
+    fn report() {
+        let report = ReportBuilder::new("Static analysis")
+            .result(ReportResult::Fail)
+            .reporter("clippy")
+            .data(vec![
+                Data {
+                    title: "Safe to merge?".to_owned(),
+                    parameter: Parameter::Boolean(false),
+                },
+                Data {
+                    title: "Started".to_owned(),
+                    parameter: Parameter::Date(1582841968000),
+                },
+                Data {
+                    title: "Duration".to_owned(),
+                    parameter: Parameter::Duration(125_000),
+                },
+                Data {
+                    title: "Pipeline".to_owned(),
+                    parameter: Parameter::Link {
+                        linktext: "Build #12".to_owned(),
+                        href: "https://ci.example.com/12".to_owned(),
+                    },
+                },
+                Data {
+                    title: "Warnings".to_owned(),
+                    parameter: Parameter::Number(12.into()),
+                },
+                Data {
+                    title: "Coverage".to_owned(),
+                    parameter: Parameter::Percentage(84),
+                },
+            ])
+            .build()
+            .unwrap();
+        let expected = "\
+Static analysis [Failed] by clippy
+  Safe to merge?: No
+  Started: 2020-02-27
+  Duration: 2m 5s
+  Pipeline: Build #12 (https://ci.example.com/12)
+  Warnings: 12
+  Coverage: 84%";
+        assert_eq!(expected, report.to_string());
+
+        let report = ReportBuilder::new("Static analysis").build().unwrap();
+        assert_eq!("Static analysis", report.to_string());
+    }
+}
@@ -1,8 +1,17 @@
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serde::{Deserialize, Serialize};
-use serde_json::{Number, Value};
+#[cfg(feature = "json")]
+use serde_json::Value;
 
 use crate::error::{Error, Result};
-use crate::validation::{validate_field, validate_optional_field};
+use crate::logging::log_warn;
+use crate::number::Number;
+#[cfg(feature = "json")]
+use crate::validation::validate_extension_key;
+use crate::validation::{validate_field, validate_len, validate_optional_field};
 
 /// Maximum length of a report title.
 pub const TITLE_LIMIT: usize = 450;
@@ -16,19 +25,222 @@ pub const DATA_LIMIT: usize = 6;
 /// Maximum length of a reporter.
 pub const REPORTER_LIMIT: usize = 450;
 
+/// Dates below this number of milliseconds, in early 1973, are most likely
+/// Unix timestamps in seconds.
+const SECONDS_THRESHOLD: u64 = 100_000_000_000;
+
 /// Indicates whether a `Report` is in a passed or failed state.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ReportResult {
     Pass,
     Fail,
 }
 
+/// Categorizes a `Report` by the kind of analysis it presents.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ReportType {
+    Security,
+    Coverage,
+    Test,
+    Bug,
+}
+
+/// A percentage between 0 and 100, as displayed by a
+/// [`Parameter::Percentage`] data field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Percentage(u8);
+
+impl Percentage {
+    /// Constructs a percentage.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is greater than 100.
+    pub fn new(value: u8) -> Result<Percentage> {
+        if value > 100 {
+            return Err(Error::InvalidValue {
+                field: "percentage".to_owned(),
+                reason: format!("{value} is greater than 100"),
+            });
+        }
+        Ok(Percentage(value))
+    }
+
+    /// Returns the percentage as a number between 0 and 100.
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<Percentage> for Parameter {
+    fn from(percentage: Percentage) -> Self {
+        Parameter::Percentage(percentage.0)
+    }
+}
+
+impl Parameter {
+    /// Constructs a [`Parameter::Number`] from a float, e.g. the result of a
+    /// division that may be `0.0 / 0.0`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is NaN or infinite, which JSON cannot
+    /// represent.
+    pub fn number_from_f64(value: f64) -> Result<Parameter> {
+        Number::from_f64(value)
+            .map(Parameter::Number)
+            .ok_or_else(|| Error::InvalidValue {
+                field: "number".to_owned(),
+                reason: format!("{value} is not a finite number"),
+            })
+    }
+
+    /// Constructs a [`Parameter::Number`] from a signed integer.
+    pub fn number_from_i64(value: i64) -> Parameter {
+        Parameter::Number(value.into())
+    }
+
+    /// Constructs a [`Parameter::Number`] from an unsigned integer.
+    pub fn number_from_u64(value: u64) -> Parameter {
+        Parameter::Number(value.into())
+    }
+
+    /// Constructs a [`Parameter::Date`] from a point in time, converting it
+    /// to milliseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `time` is before the Unix epoch.
+    pub fn date_from(time: SystemTime) -> Result<Parameter> {
+        let since_epoch = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|error| Error::InvalidValue {
+                field: "date".to_owned(),
+                reason: format!("{}ms before the Unix epoch", error.duration().as_millis()),
+            })?;
+        Ok(Parameter::Date(
+            u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX),
+        ))
+    }
+
+    /// Constructs a [`Parameter::Date`] of the current time.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the system clock is set before the Unix epoch.
+    pub fn date_now() -> Result<Parameter> {
+        Parameter::date_from(SystemTime::now())
+    }
+
+    /// Constructs a [`Parameter::Date`] from a Unix timestamp in seconds,
+    /// which [`Parameter::Date`] itself expects in milliseconds.
+    pub fn date_from_secs(secs: u64) -> Parameter {
+        Parameter::Date(secs.saturating_mul(1000))
+    }
+}
+
+impl From<i64> for Parameter {
+    fn from(value: i64) -> Self {
+        Parameter::number_from_i64(value)
+    }
+}
+
+impl From<u64> for Parameter {
+    fn from(value: u64) -> Self {
+        Parameter::number_from_u64(value)
+    }
+}
+
+impl TryFrom<f64> for Parameter {
+    type Error = Error;
+
+    fn try_from(value: f64) -> std::result::Result<Self, Self::Error> {
+        Parameter::number_from_f64(value)
+    }
+}
+
+impl From<bool> for Parameter {
+    fn from(value: bool) -> Self {
+        Parameter::Boolean(value)
+    }
+}
+
+impl From<&str> for Parameter {
+    fn from(value: &str) -> Self {
+        Parameter::Text(value.to_owned())
+    }
+}
+
+impl From<String> for Parameter {
+    fn from(value: String) -> Self {
+        Parameter::Text(value)
+    }
+}
+
+/// The key that identifies a report of a commit in Bitbucket, which is part
+/// of the URL the report is published to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct ReportKey(String);
+
+impl ReportKey {
+    /// Constructs a report key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `key` is empty or contains a `/`, which would
+    /// change the URL of the report.
+    pub fn new<T: Into<String>>(key: T) -> Result<ReportKey> {
+        let key = key.into();
+        let reason = if key.is_empty() {
+            "is empty"
+        } else if key.contains('/') {
+            "contains '/'"
+        } else {
+            return Ok(ReportKey(key));
+        };
+        Err(Error::InvalidValue {
+            field: "key".to_owned(),
+            reason: format!("'{key}' {reason}"),
+        })
+    }
+
+    /// Returns the key as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for ReportKey {
+    type Error = Error;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        ReportKey::new(value)
+    }
+}
+
+impl From<ReportKey> for String {
+    fn from(key: ReportKey) -> Self {
+        key.0
+    }
+}
+
+impl std::fmt::Display for ReportKey {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
 /// Used to represent a data field in a `Report`.
 ///
 /// A data field contains information that will be displayed in the Code
 /// Insights report summary in Bitbucket Server..
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Data {
     /// A string describing what this data field represents.
     pub title: String,
@@ -38,8 +250,69 @@ pub struct Data {
     pub parameter: Parameter,
 }
 
+impl Data {
+    /// Constructs a data field titled `title` with the value `parameter`.
+    pub fn new<T: Into<String>>(title: T, parameter: Parameter) -> Data {
+        Data {
+            title: title.into(),
+            parameter,
+        }
+    }
+
+    /// Constructs a [`Parameter::Boolean`] data field.
+    pub fn boolean<T: Into<String>>(title: T, value: bool) -> Data {
+        Data::new(title, Parameter::Boolean(value))
+    }
+
+    /// Constructs a [`Parameter::Date`] data field from a Unix timestamp in
+    /// milliseconds.
+    pub fn date<T: Into<String>>(title: T, millis: u64) -> Data {
+        Data::new(title, Parameter::Date(millis))
+    }
+
+    /// Constructs a [`Parameter::Duration`] data field from a duration in
+    /// milliseconds.
+    pub fn duration<T: Into<String>>(title: T, millis: u64) -> Data {
+        Data::new(title, Parameter::Duration(millis))
+    }
+
+    /// Constructs a [`Parameter::Link`] data field showing `linktext` and
+    /// linking to `href`.
+    pub fn link<T, L, H>(title: T, linktext: L, href: H) -> Data
+    where
+        T: Into<String>,
+        L: Into<String>,
+        H: Into<String>,
+    {
+        Data::new(
+            title,
+            Parameter::Link {
+                linktext: linktext.into(),
+                href: href.into(),
+            },
+        )
+    }
+
+    /// Constructs a [`Parameter::Number`] data field.
+    pub fn number<T: Into<String>, N: Into<Number>>(title: T, value: N) -> Data {
+        Data::new(title, Parameter::Number(value.into()))
+    }
+
+    /// Constructs a [`Parameter::Percentage`] data field. Values greater than
+    /// 100 fail validation, see [`Percentage`] to check them up front.
+    pub fn percentage<T: Into<String>>(title: T, value: u8) -> Data {
+        Data::new(title, Parameter::Percentage(value))
+    }
+
+    /// Constructs a [`Parameter::Text`] data field.
+    pub fn text<T: Into<String>, V: Into<String>>(title: T, value: V) -> Data {
+        Data::new(title, Parameter::Text(value.into()))
+    }
+}
+
 /// Describes the value for a `Data` field in a `Report`.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "value")]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Parameter {
@@ -64,8 +337,9 @@ pub enum Parameter {
     Number(Number),
 
     /// The value is a number between 0 and 100 and will be displayed with a
-    /// percentage sign.
-    Percentage(u8),
+    /// percentage sign. Reports with a greater value fail validation, see
+    /// [`Percentage`] to check it up front.
+    Percentage(#[cfg_attr(feature = "schemars", schemars(range(max = 100)))] u8),
 
     /// The value is text that will be displayed as-is.
     Text(String),
@@ -77,62 +351,258 @@ pub enum Parameter {
 /// of the results of the analysis and display data that is not specific to any
 /// given file. A report must be created before any annotations are able to be
 /// created as annotations must be associated with an existing report.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Report {
     /// A short string representing the name of the report.
-    title: String,
+    pub(crate) title: String,
 
     /// A string to describe the purpose of the report. This string may contain
     /// escaped newlines and if it does it will display the content
     /// accordingly.
     #[serde(skip_serializing_if = "Option::is_none")]
-    details: Option<String>,
+    pub(crate) details: Option<String>,
 
     /// Indicates whether the report is in a passed or failed state.
     #[serde(skip_serializing_if = "Option::is_none")]
-    result: Option<ReportResult>,
+    pub(crate) result: Option<ReportResult>,
 
     /// An array of data fields (described below) to display information on the
     /// report.
     #[serde(skip_serializing_if = "Option::is_none")]
-    data: Option<Vec<Data>>,
+    pub(crate) data: Option<Vec<Data>>,
 
     /// A string to describe the tool or company who created the report.
     #[serde(skip_serializing_if = "Option::is_none")]
-    reporter: Option<String>,
+    pub(crate) reporter: Option<String>,
 
     /// A URL linking to the results of the report in an external tool.
     #[serde(skip_serializing_if = "Option::is_none")]
-    link: Option<String>,
+    pub(crate) link: Option<String>,
 
     /// A URL to the report logo. If none is provided, the default insights
     /// logo will be used.
     #[serde(skip_serializing_if = "Option::is_none")]
-    logo_url: Option<String>,
+    pub(crate) logo_url: Option<String>,
+
+    /// The kind of analysis the report presents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) report_type: Option<ReportType>,
+
+    /// Additional properties that this crate does not model, e.g. for
+    /// Bitbucket plugins.
+    #[cfg(feature = "json")]
+    #[serde(flatten)]
+    pub(crate) extensions: BTreeMap<String, Value>,
 }
 
+/// The serialized names of the fields of a `Report`.
+#[cfg(feature = "json")]
+const REPORT_FIELDS: &[&str] = &[
+    "title",
+    "details",
+    "result",
+    "data",
+    "reporter",
+    "link",
+    "logoUrl",
+    "reportType",
+];
+
 impl Report {
+    /// Returns the title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the details, if they are set.
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+
+    /// Returns the result, if it is set.
+    pub fn result(&self) -> Option<ReportResult> {
+        self.result
+    }
+
+    /// Returns the data fields, which are empty if none are set.
+    pub fn data(&self) -> &[Data] {
+        self.data.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the reporter, if it is set.
+    pub fn reporter(&self) -> Option<&str> {
+        self.reporter.as_deref()
+    }
+
+    /// Returns the link to the results in an external tool, if it is set.
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    /// Returns the URL of the logo, if it is set.
+    pub fn logo_url(&self) -> Option<&str> {
+        self.logo_url.as_deref()
+    }
+
+    /// Returns the kind of analysis, if it is set.
+    pub fn report_type(&self) -> Option<ReportType> {
+        self.report_type
+    }
+
+    /// Returns the extension fields.
+    #[cfg(feature = "json")]
+    pub fn extensions(&self) -> &BTreeMap<String, Value> {
+        &self.extensions
+    }
+
+    /// Returns the extension field `key`, if it is set.
+    #[cfg(feature = "json")]
+    pub fn extension(&self, key: &str) -> Option<&Value> {
+        self.extensions.get(key)
+    }
+
+    /// Appends a data field.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the report already has [`DATA_LIMIT`] data fields.
+    pub fn add_data(&mut self, data: Data) -> Result<&mut Self> {
+        let fields = self.data.get_or_insert_with(Vec::new);
+        if fields.len() >= DATA_LIMIT {
+            return Err(Error::FieldTooLong {
+                name: "data".to_owned(),
+                len: fields.len() + 1,
+                limit: DATA_LIMIT,
+            });
+        }
+        fields.push(data);
+        Ok(self)
+    }
+
+    /// Validates the report like [`ReportBuilder::build`], e.g. after
+    /// deserializing it from the output of another tool.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if fields are longer than the Bitbucket API allows,
+    /// or if there are more than [`DATA_LIMIT`] data fields.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_fields()
+    }
+
     /// Validates fields that have limits imposed on them by Bitbucket.
-    fn validate_fields(&self) -> Result<()> {
+    pub(crate) fn validate_fields(&self) -> Result<()> {
         validate_field!(self, title, TITLE_LIMIT);
         validate_optional_field!(self, details, DETAILS_LIMIT);
         validate_optional_field!(self, reporter, REPORTER_LIMIT);
 
-        if let Some(data) = &self.data {
-            let len = data.len();
-            if len > DATA_LIMIT {
-                return Err(Error::FieldTooLong {
-                    name: "data".to_owned(),
-                    len,
-                    limit: DATA_LIMIT,
-                });
-            }
+        validate_data(self.data.as_deref().unwrap_or_default())
+    }
+}
+
+/// Setters that change a report in place, e.g. to set the result of a report
+/// that was created at the start of a build. Fields with limits are validated
+/// like by [`ReportBuilder::build`], and left unchanged if they are invalid.
+impl Report {
+    /// Sets the title.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `title` is longer than [`TITLE_LIMIT`].
+    pub fn set_title<T: Into<String>>(&mut self, title: T) -> Result<&mut Self> {
+        let title = title.into();
+        validate_len("title", &title, TITLE_LIMIT)?;
+        self.title = title;
+        Ok(self)
+    }
+
+    /// Sets the details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `details` is longer than [`DETAILS_LIMIT`].
+    pub fn set_details<T: Into<String>>(&mut self, details: T) -> Result<&mut Self> {
+        let details = details.into();
+        validate_len("details", &details, DETAILS_LIMIT)?;
+        self.details = Some(details);
+        Ok(self)
+    }
+
+    /// Sets the result.
+    pub fn set_result(&mut self, result: ReportResult) -> &mut Self {
+        self.result = Some(result);
+        self
+    }
+
+    /// Replaces the data fields.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there are more than [`DATA_LIMIT`] data fields or
+    /// a percentage is greater than 100.
+    pub fn set_data(&mut self, data: Vec<Data>) -> Result<&mut Self> {
+        validate_data(&data)?;
+        self.data = Some(data);
+        Ok(self)
+    }
+
+    /// Sets the reporter.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `reporter` is longer than [`REPORTER_LIMIT`].
+    pub fn set_reporter<T: Into<String>>(&mut self, reporter: T) -> Result<&mut Self> {
+        let reporter = reporter.into();
+        validate_len("reporter", &reporter, REPORTER_LIMIT)?;
+        self.reporter = Some(reporter);
+        Ok(self)
+    }
+
+    /// Sets the link to the results in an external tool.
+    pub fn set_link<T: Into<String>>(&mut self, link: T) -> &mut Self {
+        self.link = Some(link.into());
+        self
+    }
+
+    /// Sets the URL of the logo.
+    pub fn set_logo_url<T: Into<String>>(&mut self, logo_url: T) -> &mut Self {
+        self.logo_url = Some(logo_url.into());
+        self
+    }
+
+    /// Sets the kind of analysis.
+    pub fn set_report_type(&mut self, report_type: ReportType) -> &mut Self {
+        self.report_type = Some(report_type);
+        self
+    }
+}
+
+/// Validates the number of data fields and their values.
+fn validate_data(data: &[Data]) -> Result<()> {
+    if data.len() > DATA_LIMIT {
+        return Err(Error::FieldTooLong {
+            name: "data".to_owned(),
+            len: data.len(),
+            limit: DATA_LIMIT,
+        });
+    }
+    for (index, field) in data.iter().enumerate() {
+        if let Parameter::Percentage(value) = field.parameter {
+            Percentage::new(value).map_err(|error| match error {
+                Error::InvalidValue { reason, .. } => Error::InvalidValue {
+                    field: format!("data[{index}].value"),
+                    reason,
+                },
+                error => error,
+            })?;
         }
-        Ok(())
     }
+    Ok(())
 }
 
+#[cfg(feature = "json")]
 impl TryFrom<Report> for String {
     type Error = Error;
 
@@ -142,6 +612,7 @@ impl TryFrom<Report> for String {
     }
 }
 
+#[cfg(feature = "json")]
 impl TryFrom<Report> for Value {
     type Error = Error;
 
@@ -151,14 +622,19 @@ impl TryFrom<Report> for Value {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct ReportBuilder {
     title: String,
     details: Option<String>,
     result: Option<ReportResult>,
-    data: Option<Vec<Data>>,
+    pub(crate) data: Option<Vec<Data>>,
     reporter: Option<String>,
     link: Option<String>,
     logo_url: Option<String>,
+    report_type: Option<ReportType>,
+    #[cfg(feature = "json")]
+    extensions: BTreeMap<String, Value>,
+    quiet: bool,
 }
 
 impl ReportBuilder {
@@ -176,6 +652,10 @@ impl ReportBuilder {
             reporter: None,
             link: None,
             logo_url: None,
+            report_type: None,
+            #[cfg(feature = "json")]
+            extensions: BTreeMap::new(),
+            quiet: false,
         }
     }
 
@@ -188,14 +668,14 @@ impl ReportBuilder {
     /// The maximum length of `details` is given by [`DETAILS_LIMIT`]. This is
     /// a Bitbucket limitation.
     pub fn details<T: Into<String>>(mut self, details: T) -> Self {
-        self.details = Some(details.into());
+        self.set_details(details);
         self
     }
 
     /// Sets the result of the `Report` which indicates whether the report is
     /// in a passed or failed state.
     pub fn result(mut self, result: ReportResult) -> Self {
-        self.result = Some(result);
+        self.set_result(result);
         self
     }
 
@@ -207,11 +687,41 @@ impl ReportBuilder {
     ///
     /// A maximum of [`DATA_LIMIT`] `data` fields are allowed. This is a
     /// Bitbucket limitation.
-    pub fn data(mut self, data: Vec<Data>) -> Self {
-        self.data = Some(data);
+    pub fn data<I: IntoIterator<Item = Data>>(mut self, data: I) -> Self {
+        self.set_data(data);
         self
     }
 
+    /// Sets the data fields from pairs of titles and values, see
+    /// [`data`](ReportBuilder::data).
+    pub fn data_from_pairs<I, T>(mut self, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (T, Parameter)>,
+        T: Into<String>,
+    {
+        self.set_data_from_pairs(pairs);
+        self
+    }
+
+    /// Appends a data field, e.g. when several parts of a program contribute
+    /// one field each.
+    ///
+    /// Like with [`data`](ReportBuilder::data), exceeding [`DATA_LIMIT`] only
+    /// makes [`build`](ReportBuilder::build) fail.
+    pub fn add_data(mut self, data: Data) -> Self {
+        self.data.get_or_insert_with(Vec::new).push(data);
+        self
+    }
+
+    /// Appends a data field with `title` and `parameter`, see
+    /// [`add_data`](ReportBuilder::add_data).
+    pub fn add_data_field<T: Into<String>>(self, title: T, parameter: Parameter) -> Self {
+        self.add_data(Data {
+            title: title.into(),
+            parameter,
+        })
+    }
+
     /// Sets the reporter.
     ///
     /// The reporter describes the tool or company which created the Code
@@ -220,7 +730,7 @@ impl ReportBuilder {
     /// The maximum length of `reporter` is [`REPORTER_LIMIT`]. This is a
     /// Bitbucket limitation.
     pub fn reporter<T: Into<String>>(mut self, reporter: T) -> Self {
-        self.reporter = Some(reporter.into());
+        self.set_reporter(reporter);
         self
     }
 
@@ -229,7 +739,7 @@ impl ReportBuilder {
     /// The `link` is a URL linking to the results of the report in an external
     /// tool.
     pub fn link<T: Into<String>>(mut self, link: T) -> Self {
-        self.link = Some(link.into());
+        self.set_link(link);
         self
     }
 
@@ -238,7 +748,39 @@ impl ReportBuilder {
     /// The report logo will be displayed by Bitbucket when the report is
     /// presented to the user. It is recommended to use an SVG logo.
     pub fn logo_url<T: Into<String>>(mut self, logo_url: T) -> Self {
-        self.logo_url = Some(logo_url.into());
+        self.set_logo_url(logo_url);
+        self
+    }
+
+    /// Sets the report type, which categorizes the kind of analysis the report
+    /// presents.
+    pub fn report_type(mut self, report_type: ReportType) -> Self {
+        self.set_report_type(report_type);
+        self
+    }
+
+    /// Sets an extension field, which is serialized next to the fields of the
+    /// report, e.g. for a Bitbucket plugin.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `key` is the name of a field of the report, such
+    /// as `title` or `logoUrl`.
+    #[cfg(feature = "json")]
+    pub fn extension<K, V>(mut self, key: K, value: V) -> Result<Self>
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.set_extension(key, value)?;
+        Ok(self)
+    }
+
+    /// Silences the warnings logged when the report is built, e.g. about a
+    /// date that looks like it is in seconds, if the `log` feature is
+    /// enabled.
+    pub fn quiet(mut self) -> Self {
+        self.set_quiet();
         self
     }
 
@@ -251,6 +793,15 @@ impl ReportBuilder {
     /// [`DETAILS_LIMIT`], [`REPORTER_LIMIT`] and [`DATA_LIMIT`].
     pub fn build(self) -> Result<Report> {
         self.validate_fields()?;
+        for data in self.data.iter().flatten() {
+            if matches!(data.parameter, Parameter::Date(date) if date < SECONDS_THRESHOLD) {
+                log_warn!(
+                    self.quiet,
+                    "date of data field '{}' looks like seconds instead of milliseconds",
+                    data.title
+                );
+            }
+        }
         let ReportBuilder {
             title,
             details,
@@ -259,6 +810,10 @@ impl ReportBuilder {
             reporter,
             link,
             logo_url,
+            report_type,
+            #[cfg(feature = "json")]
+            extensions,
+            quiet: _,
         } = self;
 
         Ok(Report {
@@ -269,6 +824,9 @@ impl ReportBuilder {
             reporter,
             link,
             logo_url,
+            report_type,
+            #[cfg(feature = "json")]
+            extensions,
         })
     }
 
@@ -278,17 +836,89 @@ impl ReportBuilder {
         validate_optional_field!(self, details, DETAILS_LIMIT);
         validate_optional_field!(self, reporter, REPORTER_LIMIT);
 
-        if let Some(data) = &self.data {
-            let len = data.len();
-            if len > DATA_LIMIT {
-                return Err(Error::FieldTooLong {
-                    name: "data".to_owned(),
-                    len,
-                    limit: DATA_LIMIT,
-                });
-            }
-        }
-        Ok(())
+        validate_data(self.data.as_deref().unwrap_or_default())
+    }
+}
+
+/// Setters that take the builder by mutable reference, e.g. to set fields
+/// conditionally. They behave exactly like the consuming setters of the same
+/// name.
+impl ReportBuilder {
+    /// Like [`quiet`](ReportBuilder::quiet).
+    pub fn set_quiet(&mut self) -> &mut Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Like [`details`](ReportBuilder::details).
+    pub fn set_details<T: Into<String>>(&mut self, details: T) -> &mut Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Like [`result`](ReportBuilder::result).
+    pub fn set_result(&mut self, result: ReportResult) -> &mut Self {
+        self.result = Some(result);
+        self
+    }
+
+    /// Like [`data`](ReportBuilder::data).
+    pub fn set_data<I: IntoIterator<Item = Data>>(&mut self, data: I) -> &mut Self {
+        self.data = Some(data.into_iter().collect());
+        self
+    }
+
+    /// Like [`data_from_pairs`](ReportBuilder::data_from_pairs).
+    pub fn set_data_from_pairs<I, T>(&mut self, pairs: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (T, Parameter)>,
+        T: Into<String>,
+    {
+        self.set_data(pairs.into_iter().map(|(title, parameter)| Data {
+            title: title.into(),
+            parameter,
+        }))
+    }
+
+    /// Like [`reporter`](ReportBuilder::reporter).
+    pub fn set_reporter<T: Into<String>>(&mut self, reporter: T) -> &mut Self {
+        self.reporter = Some(reporter.into());
+        self
+    }
+
+    /// Like [`link`](ReportBuilder::link).
+    pub fn set_link<T: Into<String>>(&mut self, link: T) -> &mut Self {
+        self.link = Some(link.into());
+        self
+    }
+
+    /// Like [`logo_url`](ReportBuilder::logo_url).
+    pub fn set_logo_url<T: Into<String>>(&mut self, logo_url: T) -> &mut Self {
+        self.logo_url = Some(logo_url.into());
+        self
+    }
+
+    /// Like [`report_type`](ReportBuilder::report_type).
+    pub fn set_report_type(&mut self, report_type: ReportType) -> &mut Self {
+        self.report_type = Some(report_type);
+        self
+    }
+
+    /// Like [`extension`](ReportBuilder::extension).
+    ///
+    /// # Errors
+    ///
+    /// See [`extension`](ReportBuilder::extension).
+    #[cfg(feature = "json")]
+    pub fn set_extension<K, V>(&mut self, key: K, value: V) -> Result<&mut Self>
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        let key = key.into();
+        validate_extension_key(&key, REPORT_FIELDS)?;
+        self.extensions.insert(key, value.into());
+        Ok(self)
     }
 }
 
@@ -332,6 +962,109 @@ mod field_validation {
         }
         assert!(ReportBuilder::new("Title").data(data).build().is_err());
     }
+
+    #[test]
+    fn percentage() {
+        let report = |value| {
+            ReportBuilder::new("Title")
+                .data(vec![
+                    Data {
+                        title: "Safe to merge?".to_owned(),
+                        parameter: Parameter::Boolean(true),
+                    },
+                    Data {
+                        title: "Coverage".to_owned(),
+                        parameter: Parameter::Percentage(value),
+                    },
+                ])
+                .build()
+        };
+        assert!(report(0).is_ok());
+        assert!(report(100).is_ok());
+        assert!(matches!(
+            report(101),
+            Err(Error::InvalidValue { ref field, ref reason })
+                if field == "data[1].value" && reason == "101 is greater than 100"
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialized_percentage() {
+        let report = |value: u8| {
+            serde_json::from_value::<Report>(serde_json::json!({
+                "title": "Title",
+                "data": [{"title": "Coverage", "type": "PERCENTAGE", "value": value}]
+            }))
+            .unwrap()
+        };
+        assert!(String::try_from(report(0)).is_ok());
+        assert!(Value::try_from(report(100)).is_ok());
+        assert!(matches!(
+            String::try_from(report(101)),
+            Err(Error::InvalidValue { ref field, .. }) if field == "data[0].value"
+        ));
+        assert!(report(101).validate().is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialized() {
+        let report = serde_json::from_value::<Report>(serde_json::json!({
+            "title": "X".repeat(TITLE_LIMIT + 1),
+            "result": "PASS"
+        }))
+        .unwrap();
+        // Validating does not consume the report, so it can be repeated.
+        for _ in 0..2 {
+            assert!(matches!(
+                report.validate(),
+                Err(Error::FieldTooLong { ref name, limit: TITLE_LIMIT, .. }) if name == "title"
+            ));
+        }
+
+        let report = serde_json::from_value::<Report>(serde_json::json!({"title": "Title"}));
+        assert!(report.unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn mutable_setters() {
+        let logo_url = Some("https://example.com/logo.svg");
+        let mut builder = ReportBuilder::new("Title");
+        builder
+            .set_result(ReportResult::Pass)
+            .set_reporter("clippy");
+        if let Some(logo_url) = logo_url {
+            builder.set_logo_url(logo_url);
+        }
+        #[cfg(feature = "json")]
+        assert!(builder.set_extension("title", "x").is_err());
+        let mut expected = ReportBuilder::new("Title")
+            .result(ReportResult::Pass)
+            .reporter("clippy");
+        if let Some(logo_url) = logo_url {
+            expected = expected.logo_url(logo_url);
+        }
+        assert_eq!(expected.build().unwrap(), builder.build().unwrap());
+
+        let mut builder = ReportBuilder::new("Title");
+        builder.set_details("X".repeat(DETAILS_LIMIT + 1));
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn builder_template() {
+        let template = ReportBuilder::new("Static analysis").reporter("clippy");
+        let passed = template.clone().result(ReportResult::Pass).build().unwrap();
+        let failed = template.result(ReportResult::Fail).build().unwrap();
+        assert_eq!(Some(ReportResult::Pass), passed.result);
+        assert_eq!(Some(ReportResult::Fail), failed.result);
+        assert_eq!(passed.reporter, failed.reporter);
+
+        let debug = format!("{:?}", ReportBuilder::new("Static analysis"));
+        assert!(debug.starts_with("ReportBuilder {"), "{debug}");
+        assert!(debug.contains("\"Static analysis\""), "{debug}");
+    }
 }
 
 #[cfg(test)]
@@ -391,4 +1124,520 @@ mod parameter_serialization {
         let actual = serde_json::to_value(Parameter::Text("Some string".to_owned())).unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn primitives() {
+        let tag = |parameter: Parameter| serde_json::to_value(parameter).unwrap()["type"].clone();
+        assert_eq!(json!("BOOLEAN"), tag(true.into()));
+        assert_eq!(json!("NUMBER"), tag(1_582_841_968_000u64.into()));
+        assert_eq!(json!("NUMBER"), tag((-3i64).into()));
+        assert_eq!(json!("NUMBER"), tag(Parameter::try_from(0.5).unwrap()));
+        assert_eq!(json!("TEXT"), tag("abc123".into()));
+        assert_eq!(json!("TEXT"), tag(String::from("abc123").into()));
+
+        let report = ReportBuilder::new("Title")
+            .add_data_field("Safe to merge?", false.into())
+            .add_data_field("Commit", "abc123".into())
+            .build()
+            .unwrap();
+        assert_eq!(
+            json!([
+                {"title": "Safe to merge?", "type": "BOOLEAN", "value": false},
+                {"title": "Commit", "type": "TEXT", "value": "abc123"},
+            ]),
+            serde_json::to_value(report.data()).unwrap()
+        );
+    }
+
+    #[test]
+    fn data_constructors() {
+        let expected = json!([
+            {"title": "Safe to merge?", "type": "BOOLEAN", "value": true},
+            {"title": "Analyzed", "type": "DATE", "value": 1582841968000u64},
+            {"title": "Build time", "type": "DURATION", "value": 3600},
+            {"title": "Run", "type": "LINK", "value": {"linktext": "#12", "href": "https://link.test"}},
+            {"title": "Issues", "type": "NUMBER", "value": 3},
+            {"title": "Coverage", "type": "PERCENTAGE", "value": 83},
+            {"title": "Commit", "type": "TEXT", "value": "abc123"},
+        ]);
+        let actual = serde_json::to_value([
+            Data::boolean("Safe to merge?", true),
+            Data::date("Analyzed", 1582841968000),
+            Data::duration("Build time", 3600),
+            Data::link("Run", "#12", "https://link.test"),
+            Data::number("Issues", 3u64),
+            Data::percentage("Coverage", 83),
+            Data::text("Commit", "abc123"),
+        ])
+        .unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!(
+            Data {
+                title: "Issues".to_owned(),
+                parameter: Parameter::Number(3u64.into()),
+            },
+            Data::new("Issues", Parameter::Number(3u64.into()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod cloning {
+    use super::*;
+    use crate::{AnnotationBuilder, Annotations, Severity, Type};
+
+    #[test]
+    fn populated() {
+        let report = ReportBuilder::new("Static analysis")
+            .details("Found 1 issue")
+            .result(ReportResult::Fail)
+            .data(vec![Data {
+                title: "Pipeline".to_owned(),
+                parameter: Parameter::Link {
+                    linktext: "Build #12".to_owned(),
+                    href: "https://ci.example.com/12".to_owned(),
+                },
+            }])
+            .reporter("Analyzer")
+            .link("https://analyzer.example.com/runs/12")
+            .logo_url("https://analyzer.example.com/logo.png")
+            .report_type(ReportType::Security)
+            .build()
+            .unwrap();
+        let annotations = Annotations::new(vec![AnnotationBuilder::new(
+            "Possible SQL injection",
+            Severity::High,
+        )
+        .annotation_type(Type::Vulnerability)
+        .path("src/db.rs")
+        .line(42)
+        .link("https://analyzer.example.com/issues/7")
+        .external_id("analyzer-7")
+        .build()
+        .unwrap()]);
+
+        let (report_copy, annotations_copy) = (report.clone(), annotations.clone());
+        assert_eq!(report, report_copy);
+        assert_eq!(annotations, annotations_copy);
+    }
+}
+
+#[cfg(test)]
+mod number_parameters {
+    use super::*;
+
+    #[test]
+    fn non_finite() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(
+                matches!(
+                    Parameter::number_from_f64(value),
+                    Err(Error::InvalidValue { ref field, .. }) if field == "number"
+                ),
+                "{value}"
+            );
+            assert!(Parameter::try_from(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn finite() {
+        assert_eq!(
+            Parameter::Number(Number::from_f64(-0.5).unwrap()),
+            Parameter::try_from(-0.5).unwrap()
+        );
+        assert_eq!(
+            Parameter::Number(Number::from_f64(f64::MAX).unwrap()),
+            Parameter::number_from_f64(f64::MAX).unwrap()
+        );
+    }
+
+    #[test]
+    fn large_integers() {
+        let Parameter::Number(number) = Parameter::from(u64::MAX) else {
+            panic!("not a number");
+        };
+        assert_eq!(Some(u64::MAX), number.as_u64());
+
+        let Parameter::Number(number) = Parameter::from(i64::MIN) else {
+            panic!("not a number");
+        };
+        assert_eq!(Some(i64::MIN), number.as_i64());
+        assert_eq!(Parameter::number_from_i64(7), Parameter::number_from_u64(7));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialization() {
+        assert_eq!(
+            serde_json::json!({"type": "NUMBER", "value": u64::MAX}),
+            serde_json::to_value(Parameter::from(u64::MAX)).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!({"type": "NUMBER", "value": i64::MIN}),
+            serde_json::to_value(Parameter::from(i64::MIN)).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod date_parameters {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn system_time() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_582_841_968_123);
+        assert_eq!(
+            Parameter::Date(1_582_841_968_123),
+            Parameter::date_from(time).unwrap()
+        );
+        assert_eq!(
+            Parameter::Date(0),
+            Parameter::date_from(UNIX_EPOCH).unwrap()
+        );
+        assert_eq!(
+            Parameter::Date(1_582_841_968_000),
+            Parameter::date_from_secs(1_582_841_968)
+        );
+        assert_eq!(
+            Parameter::Date(u64::MAX),
+            Parameter::date_from_secs(u64::MAX)
+        );
+
+        let Parameter::Date(now) = Parameter::date_now().unwrap() else {
+            panic!("not a date");
+        };
+        assert!(now >= SECONDS_THRESHOLD);
+    }
+
+    #[test]
+    fn before_epoch() {
+        let time = UNIX_EPOCH - Duration::from_secs(1);
+        assert!(matches!(
+            Parameter::date_from(time),
+            Err(Error::InvalidValue { ref field, ref reason })
+                if field == "date" && reason == "1000ms before the Unix epoch"
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod getters {
+    use super::*;
+
+    #[test]
+    fn deserialized() {
+        let report =
+            serde_json::from_str::<Report>(include_str!("../tests/fixtures/report.json")).unwrap();
+        assert_eq!("Static analysis", report.title());
+        assert_eq!(Some("Found 3 issues"), report.details());
+        assert_eq!(Some(ReportResult::Fail), report.result());
+        assert_eq!(
+            &[
+                Data {
+                    title: "Safe to merge?".to_owned(),
+                    parameter: Parameter::Boolean(false),
+                },
+                Data {
+                    title: "Issues".to_owned(),
+                    parameter: Parameter::Number(3u64.into()),
+                },
+            ],
+            report.data()
+        );
+        assert_eq!(Some("Analyzer"), report.reporter());
+        assert_eq!(Some("https://analyzer.example.com/runs/12"), report.link());
+        assert_eq!(
+            Some("https://analyzer.example.com/logo.png"),
+            report.logo_url()
+        );
+        assert_eq!(Some(ReportType::Bug), report.report_type());
+    }
+
+    #[test]
+    fn unset() {
+        let report = ReportBuilder::new("Title").build().unwrap();
+        assert_eq!("Title", report.title());
+        assert_eq!(None, report.details());
+        assert_eq!(None, report.result());
+        assert!(report.data().is_empty());
+        assert_eq!(None, report.reporter());
+        assert_eq!(None, report.link());
+        assert_eq!(None, report.logo_url());
+        assert_eq!(None, report.report_type());
+    }
+}
+
+#[cfg(test)]
+mod report_setters {
+    use super::*;
+
+    fn fields(count: usize) -> Vec<Data> {
+        (0..count)
+            .map(|index| Data {
+                title: format!("Field {index}"),
+                parameter: Parameter::Boolean(true),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mutation() {
+        let mut report = ReportBuilder::new("Static analysis")
+            .reporter("Analyzer")
+            .build()
+            .unwrap();
+        report
+            .set_title("Lints")
+            .unwrap()
+            .set_result(ReportResult::Fail)
+            .set_link("https://analyzer.example.com/runs/12")
+            .set_logo_url("https://analyzer.example.com/logo.png")
+            .set_report_type(ReportType::Bug);
+        report.set_details("Found 2 issues").unwrap();
+        report.set_reporter("Clippy").unwrap();
+        report.set_data(fields(2)).unwrap();
+
+        let expected = ReportBuilder::new("Lints")
+            .details("Found 2 issues")
+            .result(ReportResult::Fail)
+            .data(fields(2))
+            .reporter("Clippy")
+            .link("https://analyzer.example.com/runs/12")
+            .logo_url("https://analyzer.example.com/logo.png")
+            .report_type(ReportType::Bug)
+            .build()
+            .unwrap();
+        assert_eq!(expected, report);
+        #[cfg(feature = "json")]
+        assert_eq!(
+            serde_json::to_string(&expected).unwrap(),
+            String::try_from(report).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let mut report = ReportBuilder::new("Title").build().unwrap();
+        assert!(report.set_title("X".repeat(TITLE_LIMIT + 1)).is_err());
+        assert!(report.set_details("X".repeat(DETAILS_LIMIT + 1)).is_err());
+        assert!(report.set_reporter("X".repeat(REPORTER_LIMIT + 1)).is_err());
+        assert!(matches!(
+            report.set_data(fields(DATA_LIMIT + 1)),
+            Err(Error::FieldTooLong { ref name, .. }) if name == "data"
+        ));
+        assert!(report
+            .set_data(vec![Data {
+                title: "Coverage".to_owned(),
+                parameter: Parameter::Percentage(101),
+            }])
+            .is_err());
+        assert_eq!(ReportBuilder::new("Title").build().unwrap(), report);
+    }
+}
+
+#[cfg(test)]
+mod incremental_data {
+    use super::*;
+
+    #[test]
+    fn add_data() {
+        let report = ReportBuilder::new("Title")
+            .add_data_field("Coverage", Parameter::Percentage(80))
+            .add_data(Data {
+                title: "Safe to merge?".to_owned(),
+                parameter: Parameter::Boolean(true),
+            })
+            .build()
+            .unwrap();
+        assert_eq!(
+            vec!["Coverage", "Safe to merge?"],
+            report
+                .data()
+                .iter()
+                .map(|field| field.title.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let report = ReportBuilder::new("Title")
+            .data(vec![Data {
+                title: "Issues".to_owned(),
+                parameter: Parameter::Number(3u64.into()),
+            }])
+            .add_data_field("Safe to merge?", Parameter::Boolean(false))
+            .build()
+            .unwrap();
+        assert_eq!(2, report.data().len());
+    }
+
+    #[test]
+    fn iterators() {
+        let counts = [("Errors", 2u64), ("Warnings", 0), ("Notes", 5)];
+        let report = ReportBuilder::new("Title")
+            .data(
+                counts
+                    .iter()
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(title, count)| Data {
+                        title: (*title).to_owned(),
+                        parameter: Parameter::Number((*count).into()),
+                    }),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            vec!["Errors", "Notes"],
+            report
+                .data()
+                .iter()
+                .map(|field| field.title.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let report = ReportBuilder::new("Title")
+            .data_from_pairs(
+                counts
+                    .into_iter()
+                    .map(|(title, count)| (title, Parameter::Number(count.into()))),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            &Data {
+                title: "Warnings".to_owned(),
+                parameter: Parameter::Number(0u64.into()),
+            },
+            &report.data()[1]
+        );
+
+        let mut builder = ReportBuilder::new("Title");
+        builder.set_data_from_pairs(
+            (0..=DATA_LIMIT).map(|index| (format!("Field {index}"), Parameter::Boolean(true))),
+        );
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn over_limit() {
+        let builder = (0..=DATA_LIMIT).fold(ReportBuilder::new("Title"), |builder, index| {
+            builder.add_data_field(format!("Field {index}"), Parameter::Boolean(true))
+        });
+        assert!(matches!(
+            builder.build(),
+            Err(Error::FieldTooLong { ref name, len: 7, limit: DATA_LIMIT }) if name == "data"
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod extension_fields {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let json = json!({
+            "title": "Static analysis",
+            "result": "PASS",
+            "pluginData": {"version": 2, "tags": ["a", "b"]}
+        });
+        let report = serde_json::from_value::<Report>(json.clone()).unwrap();
+        assert_eq!(
+            Some(&json!({"version": 2, "tags": ["a", "b"]})),
+            report.extension("pluginData")
+        );
+        assert_eq!(json, serde_json::to_value(&report).unwrap());
+    }
+
+    #[test]
+    fn builder() {
+        let report = ReportBuilder::new("Title")
+            .extension("pluginData", "value")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(1, report.extensions().len());
+        let value = Value::try_from(report).unwrap();
+        assert_eq!(json!({"title": "Title", "pluginData": "value"}), value);
+
+        let report = ReportBuilder::new("Title").build().unwrap();
+        assert_eq!(json!({"title": "Title"}), Value::try_from(report).unwrap());
+    }
+
+    #[test]
+    fn collision() {
+        let result = ReportBuilder::new("Title").extension("logoUrl", "x");
+        assert!(matches!(result, Err(Error::InvalidValue { ref field, .. }) if field == "logoUrl"));
+    }
+}
+
+#[cfg(all(test, feature = "schemars"))]
+mod json_schema {
+    use super::*;
+    use crate::{AnnotationBuilder, Annotations, Severity, Type};
+    use serde_json::json;
+
+    fn validator<T: schemars::JsonSchema>() -> jsonschema::Validator {
+        let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap();
+        jsonschema::validator_for(&schema).unwrap()
+    }
+
+    #[test]
+    fn report() {
+        let report = ReportBuilder::new("Static analysis")
+            .result(ReportResult::Fail)
+            .report_type(ReportType::Bug)
+            .data(vec![
+                Data {
+                    title: "Safe to merge?".to_owned(),
+                    parameter: Parameter::Boolean(false),
+                },
+                Data {
+                    title: "Pipeline".to_owned(),
+                    parameter: Parameter::Link {
+                        linktext: "Build #12".to_owned(),
+                        href: "https://ci.example.com/12".to_owned(),
+                    },
+                },
+                Data {
+                    title: "Coverage".to_owned(),
+                    parameter: Parameter::Percentage(84),
+                },
+            ])
+            .build()
+            .unwrap();
+        let validator = validator::<Report>();
+        let value = serde_json::to_value(report).unwrap();
+        assert!(validator.is_valid(&value), "{value}");
+
+        for invalid in [
+            json!({"details": "No title"}),
+            json!({"title": "T", "result": "MAYBE"}),
+            json!({"title": "T", "data": [{"title": "Coverage", "type": "PERCENTAGE", "value": 101}]}),
+            json!({"title": "T", "data": [{"title": "Warnings", "type": "NUMBER", "value": "12"}]}),
+            json!({"title": "T", "data": [{"title": "Pipeline", "type": "LINK", "value": "https://ci.example.com"}]}),
+        ] {
+            assert!(!validator.is_valid(&invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn annotations() {
+        let annotations = Annotations::new(vec![AnnotationBuilder::new(
+            "Possible panic",
+            Severity::High,
+        )
+        .annotation_type(Type::Bug)
+        .path("src/lib.rs")
+        .line(40)
+        .build()
+        .unwrap()]);
+        let validator = validator::<Annotations>();
+        assert!(validator.is_valid(&serde_json::to_value(&annotations).unwrap()));
+
+        let invalid = json!({"annotations": [{"message": "M", "severity": "CRITICAL"}]});
+        assert!(!validator.is_valid(&invalid));
+    }
 }
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use crate::report::{Data, Report, ReportResult, ReportType};
+
+/// A field of a report that differs between two reports, with both values
+/// rendered as strings, or `None` if the field is not set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    field: String,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+impl FieldChange {
+    /// Returns the serialized name of the field, such as `logoUrl`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Returns the value of the field in the old report.
+    pub fn old_value(&self) -> Option<&str> {
+        self.old.as_deref()
+    }
+
+    /// Returns the value of the field in the new report.
+    pub fn new_value(&self) -> Option<&str> {
+        self.new.as_deref()
+    }
+}
+
+/// A data field, matched by title, that differs between two reports.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataChange {
+    /// The data field is only in the new report.
+    Added(Data),
+    /// The data field is only in the old report.
+    Removed(Data),
+    /// The data field has a different value in the new report.
+    Changed { old: Data, new: Data },
+}
+
+/// The difference between two reports, computed by [`Report::compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportDiff {
+    fields: Vec<FieldChange>,
+    data: Vec<DataChange>,
+    data_reordered: bool,
+}
+
+impl ReportDiff {
+    /// Returns the fields that differ, apart from the data fields.
+    pub fn fields(&self) -> &[FieldChange] {
+        &self.fields
+    }
+
+    /// Returns the data fields that were added, removed or changed.
+    pub fn data(&self) -> &[DataChange] {
+        &self.data
+    }
+
+    /// Returns `true` if the data fields the reports have in common are in a
+    /// different order.
+    pub fn data_reordered(&self) -> bool {
+        self.data_reordered
+    }
+
+    /// Returns `true` if the reports only differ in the order of their data
+    /// fields, if at all.
+    pub fn is_equivalent(&self) -> bool {
+        self.fields.is_empty() && self.data.is_empty()
+    }
+}
+
+fn result_name(result: ReportResult) -> &'static str {
+    match result {
+        ReportResult::Pass => "PASS",
+        ReportResult::Fail => "FAIL",
+    }
+}
+
+fn report_type_name(report_type: ReportType) -> &'static str {
+    match report_type {
+        ReportType::Security => "SECURITY",
+        ReportType::Coverage => "COVERAGE",
+        ReportType::Test => "TEST",
+        ReportType::Bug => "BUG",
+    }
+}
+
+fn compare_field(
+    fields: &mut Vec<FieldChange>,
+    field: &str,
+    old: Option<String>,
+    new: Option<String>,
+) {
+    if old != new {
+        fields.push(FieldChange {
+            field: field.to_owned(),
+            old,
+            new,
+        });
+    }
+}
+
+/// Returns the titles of `data` that are also in `other`, in order.
+fn common_titles<'a>(data: &'a [Data], other: &[Data]) -> Vec<&'a str> {
+    data.iter()
+        .map(|data| data.title.as_str())
+        .filter(|title| other.iter().any(|other| other.title == *title))
+        .collect()
+}
+
+impl Report {
+    /// Compares the report field by field with `other`, the newer report.
+    ///
+    /// Data fields are matched by their title, so that reordered data fields
+    /// are not reported as changed.
+    pub fn compare(&self, other: &Report) -> ReportDiff {
+        let mut fields = Vec::new();
+        compare_field(
+            &mut fields,
+            "title",
+            Some(self.title.clone()),
+            Some(other.title.clone()),
+        );
+        for (field, old, new) in [
+            ("details", &self.details, &other.details),
+            ("reporter", &self.reporter, &other.reporter),
+            ("link", &self.link, &other.link),
+            ("logoUrl", &self.logo_url, &other.logo_url),
+        ] {
+            compare_field(&mut fields, field, old.clone(), new.clone());
+        }
+        compare_field(
+            &mut fields,
+            "result",
+            self.result.map(|result| result_name(result).to_owned()),
+            other.result.map(|result| result_name(result).to_owned()),
+        );
+        compare_field(
+            &mut fields,
+            "reportType",
+            self.report_type
+                .map(|report_type| report_type_name(report_type).to_owned()),
+            other
+                .report_type
+                .map(|report_type| report_type_name(report_type).to_owned()),
+        );
+        #[cfg(feature = "json")]
+        {
+            let keys = self.extensions.keys().chain(
+                other
+                    .extensions
+                    .keys()
+                    .filter(|key| !self.extensions.contains_key(*key)),
+            );
+            for key in keys {
+                compare_field(
+                    &mut fields,
+                    key,
+                    self.extensions.get(key).map(ToString::to_string),
+                    other.extensions.get(key).map(ToString::to_string),
+                );
+            }
+        }
+
+        let old = self.data.as_deref().unwrap_or_default();
+        let new = other.data.as_deref().unwrap_or_default();
+        let mut data = Vec::new();
+        for old_data in old {
+            match new.iter().find(|new| new.title == old_data.title) {
+                None => data.push(DataChange::Removed(old_data.clone())),
+                Some(new_data) if new_data != old_data => data.push(DataChange::Changed {
+                    old: old_data.clone(),
+                    new: new_data.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for new_data in new {
+            if !old.iter().any(|old| old.title == new_data.title) {
+                data.push(DataChange::Added(new_data.clone()));
+            }
+        }
+
+        ReportDiff {
+            fields,
+            data,
+            data_reordered: common_titles(old, new) != common_titles(new, old),
+        }
+    }
+}
+
+/// Lists the differences one per line, e.g. for CI logs.
+impl fmt::Display for ReportDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = Vec::new();
+        let value = |value: &Option<String>| value.as_deref().unwrap_or("(not set)").to_owned();
+        for change in &self.fields {
+            lines.push(format!(
+                "{}: {} -> {}",
+                change.field,
+                value(&change.old),
+                value(&change.new)
+            ));
+        }
+        for change in &self.data {
+            lines.push(match change {
+                DataChange::Added(data) => format!("data added: {data}"),
+                DataChange::Removed(data) => format!("data removed: {data}"),
+                DataChange::Changed { old, new } => {
+                    format!("data changed: {old} -> {}", new.parameter)
+                }
+            });
+        }
+        if self.data_reordered {
+            lines.push("data reordered".to_owned());
+        }
+        if lines.is_empty() {
+            return formatter.write_str("no differences");
+        }
+        formatter.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod report_comparison {
+    use super::*;
+    use crate::{Parameter, ReportBuilder};
+
+    fn data(title: &str, value: u8) -> Data {
+        Data {
+            title: title.to_owned(),
+            parameter: Parameter::Percentage(value),
+        }
+    }
+
+    fn report(result: ReportResult, data: Vec<Data>) -> Report {
+        ReportBuilder::new("Coverage")
+            .result(result)
+            .data(data)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn data_only() {
+        let old = report(
+            ReportResult::Pass,
+            vec![data("Lines", 80), data("Branches", 60)],
+        );
+        let new = report(
+            ReportResult::Pass,
+            vec![data("Lines", 85), data("Functions", 90)],
+        );
+        let diff = old.compare(&new);
+        assert!(diff.fields().is_empty());
+        assert!(!diff.is_equivalent());
+        assert_eq!(
+            &[
+                DataChange::Changed {
+                    old: data("Lines", 80),
+                    new: data("Lines", 85)
+                },
+                DataChange::Removed(data("Branches", 60)),
+                DataChange::Added(data("Functions", 90)),
+            ],
+            diff.data()
+        );
+        assert_eq!(
+            "data changed: Lines: 80% -> 85%\n\
+             data removed: Branches: 60%\n\
+             data added: Functions: 90%",
+            diff.to_string()
+        );
+    }
+
+    #[test]
+    fn result_flip() {
+        let old = report(ReportResult::Pass, vec![data("Lines", 80)]);
+        let new = report(ReportResult::Fail, vec![data("Lines", 80)]);
+        let diff = old.compare(&new);
+        assert_eq!(1, diff.fields().len());
+        assert_eq!("result", diff.fields()[0].field());
+        assert_eq!(Some("PASS"), diff.fields()[0].old_value());
+        assert_eq!(Some("FAIL"), diff.fields()[0].new_value());
+        assert!(diff.data().is_empty());
+        assert_eq!("result: PASS -> FAIL", diff.to_string());
+
+        let new = ReportBuilder::new("Coverage").build().unwrap();
+        let diff = old.compare(&new);
+        assert_eq!(
+            "result: PASS -> (not set)",
+            diff.to_string().lines().next().unwrap()
+        );
+    }
+
+    #[test]
+    fn reordered_data() {
+        let old = report(
+            ReportResult::Pass,
+            vec![data("Lines", 80), data("Branches", 60)],
+        );
+        let new = report(
+            ReportResult::Pass,
+            vec![data("Branches", 60), data("Lines", 80)],
+        );
+        let diff = old.compare(&new);
+        assert!(diff.is_equivalent());
+        assert!(diff.data_reordered());
+        assert_eq!("data reordered", diff.to_string());
+
+        let diff = old.compare(&report(
+            ReportResult::Pass,
+            vec![data("Lines", 80), data("Branches", 60)],
+        ));
+        assert!(diff.is_equivalent());
+        assert!(!diff.data_reordered());
+        assert_eq!("no differences", diff.to_string());
+    }
+}
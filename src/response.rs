@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::annotation::Annotation;
+use crate::report::Report;
+
+/// A report as returned by Bitbucket, e.g. when it is fetched before being
+/// updated.
+///
+/// Properties that this crate does not know about, such as those added by
+/// newer versions of Bitbucket or by other plugins, are kept in
+/// [`unknown_fields`](ReportResponse::unknown_fields) and serialized again,
+/// so that they are not lost when the report is sent back.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "RawReportResponse")]
+pub struct ReportResponse {
+    key: Option<String>,
+    created_date: Option<u64>,
+    report: Report,
+    unknown_fields: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawReportResponse {
+    key: Option<String>,
+    created_date: Option<u64>,
+    #[serde(flatten)]
+    report: Report,
+}
+
+impl From<RawReportResponse> for ReportResponse {
+    fn from(mut raw: RawReportResponse) -> Self {
+        // Everything Bitbucket sent that is not a known field ends up in the
+        // extensions of the report, which are meant for fields set on purpose.
+        let unknown_fields = mem::take(&mut raw.report.extensions);
+        ReportResponse {
+            key: raw.key,
+            created_date: raw.created_date,
+            report: raw.report,
+            unknown_fields,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportResponseRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_date: Option<u64>,
+    #[serde(flatten)]
+    report: &'a Report,
+    #[serde(flatten)]
+    unknown_fields: &'a BTreeMap<String, Value>,
+}
+
+impl Serialize for ReportResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        ReportResponseRef {
+            key: self.key.as_deref(),
+            created_date: self.created_date,
+            report: &self.report,
+            unknown_fields: &self.unknown_fields,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl ReportResponse {
+    /// Returns the key of the report.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Returns the time the report was created, in milliseconds since the
+    /// epoch.
+    pub fn created_date(&self) -> Option<u64> {
+        self.created_date
+    }
+
+    /// Returns the report.
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Returns the properties of the response that this crate does not know
+    /// about.
+    pub fn unknown_fields(&self) -> &BTreeMap<String, Value> {
+        &self.unknown_fields
+    }
+
+    /// Converts the response into a report that can be sent to Bitbucket
+    /// again, keeping the unknown fields as extensions of the report.
+    pub fn into_report(self) -> Report {
+        let mut report = self.report;
+        report.extensions.extend(self.unknown_fields);
+        report
+    }
+}
+
+/// An annotation as returned by Bitbucket.
+///
+/// Like [`ReportResponse`], properties that this crate does not know about
+/// are kept in [`unknown_fields`](AnnotationResponse::unknown_fields).
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "RawAnnotationResponse")]
+pub struct AnnotationResponse {
+    report_key: Option<String>,
+    annotation: Annotation,
+    unknown_fields: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAnnotationResponse {
+    report_key: Option<String>,
+    #[serde(flatten)]
+    annotation: Annotation,
+}
+
+impl From<RawAnnotationResponse> for AnnotationResponse {
+    fn from(mut raw: RawAnnotationResponse) -> Self {
+        let unknown_fields = mem::take(&mut raw.annotation.extensions);
+        AnnotationResponse {
+            report_key: raw.report_key,
+            annotation: raw.annotation,
+            unknown_fields,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnnotationResponseRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report_key: Option<&'a str>,
+    #[serde(flatten)]
+    annotation: &'a Annotation,
+    #[serde(flatten)]
+    unknown_fields: &'a BTreeMap<String, Value>,
+}
+
+impl Serialize for AnnotationResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        AnnotationResponseRef {
+            report_key: self.report_key.as_deref(),
+            annotation: &self.annotation,
+            unknown_fields: &self.unknown_fields,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl AnnotationResponse {
+    /// Returns the key of the report the annotation belongs to.
+    pub fn report_key(&self) -> Option<&str> {
+        self.report_key.as_deref()
+    }
+
+    /// Returns the annotation.
+    pub fn annotation(&self) -> &Annotation {
+        &self.annotation
+    }
+
+    /// Returns the properties of the response that this crate does not know
+    /// about.
+    pub fn unknown_fields(&self) -> &BTreeMap<String, Value> {
+        &self.unknown_fields
+    }
+
+    /// Converts the response into an annotation that can be sent to
+    /// Bitbucket again, keeping the unknown fields as extensions of the
+    /// annotation.
+    pub fn into_annotation(self) -> Annotation {
+        let mut annotation = self.annotation;
+        annotation.extensions.extend(self.unknown_fields);
+        annotation
+    }
+}
+
+#[cfg(test)]
+mod unknown_fields {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn report_round_trip() {
+        let fixture = json!({
+            "key": "static-analysis",
+            "createdDate": 1582841968000u64,
+            "title": "Static analysis",
+            "result": "FAIL",
+            "data": [{"title": "Warnings", "type": "NUMBER", "value": 3}],
+            "reporter": "clippy",
+            "reportType": "BUG",
+            "pluginMetadata": {"owner": "another-plugin", "revision": 7},
+            "insightsVersion": 3
+        });
+        let response = serde_json::from_value::<ReportResponse>(fixture.clone()).unwrap();
+        assert_eq!(Some("static-analysis"), response.key());
+        assert_eq!(Some(1582841968000), response.created_date());
+        assert_eq!(
+            vec!["insightsVersion", "pluginMetadata"],
+            response.unknown_fields().keys().collect::<Vec<_>>()
+        );
+        assert!(response.report().extensions().is_empty());
+        assert_eq!(fixture, serde_json::to_value(&response).unwrap());
+
+        let report = response.into_report();
+        assert_eq!(Some(&json!(3)), report.extension("insightsVersion"));
+    }
+
+    #[test]
+    fn annotation_round_trip() {
+        let fixture = json!({
+            "reportKey": "static-analysis",
+            "externalId": "a1",
+            "message": "Possible panic",
+            "severity": "HIGH",
+            "type": "BUG",
+            "path": "src/lib.rs",
+            "line": 40,
+            "createdDate": 1582841968000u64,
+            "pluginMetadata": ["triaged"]
+        });
+        let response = serde_json::from_value::<AnnotationResponse>(fixture.clone()).unwrap();
+        assert_eq!(Some("static-analysis"), response.report_key());
+        assert_eq!(2, response.unknown_fields().len());
+        assert!(response.annotation().extensions().is_empty());
+        assert_eq!(fixture, serde_json::to_value(&response).unwrap());
+
+        let annotation = response.into_annotation();
+        assert_eq!(
+            Some(&json!(["triaged"])),
+            annotation.extension("pluginMetadata")
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotations, Severity};
+use crate::report::{ReportBuilder, ReportResult};
+use crate::stats::AnnotationStats;
+
+/// Decides whether a report passes based on the number of annotations per
+/// severity, e.g. failing on any High annotation or on ten Medium ones.
+///
+/// A report fails once the number of annotations of a severity is at least
+/// its threshold. A severity without a threshold never fails the report, so
+/// the default policy always passes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ResultPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on_high: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on_medium: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on_low: Option<usize>,
+}
+
+impl ResultPolicy {
+    /// Constructs a policy that always passes.
+    pub fn new() -> Self {
+        ResultPolicy::default()
+    }
+
+    /// Fails once there are at least `threshold` annotations of `severity`.
+    pub fn fail_on(mut self, severity: Severity, threshold: usize) -> Self {
+        *self.threshold_mut(severity) = Some(threshold);
+        self
+    }
+
+    /// Returns the threshold of `severity`, if any.
+    pub fn threshold(&self, severity: Severity) -> Option<usize> {
+        match severity {
+            Severity::High => self.fail_on_high,
+            Severity::Medium => self.fail_on_medium,
+            Severity::Low => self.fail_on_low,
+        }
+    }
+
+    fn threshold_mut(&mut self, severity: Severity) -> &mut Option<usize> {
+        match severity {
+            Severity::High => &mut self.fail_on_high,
+            Severity::Medium => &mut self.fail_on_medium,
+            Severity::Low => &mut self.fail_on_low,
+        }
+    }
+
+    /// Returns the result of a report with the counts of `stats`.
+    pub fn evaluate_stats(&self, stats: &AnnotationStats) -> ReportResult {
+        let failed = [Severity::High, Severity::Medium, Severity::Low]
+            .into_iter()
+            .any(|severity| {
+                self.threshold(severity)
+                    .is_some_and(|threshold| stats.severity(severity) >= threshold)
+            });
+        if failed {
+            ReportResult::Fail
+        } else {
+            ReportResult::Pass
+        }
+    }
+
+    /// Returns the result of a report with `annotations`.
+    pub fn evaluate(&self, annotations: &Annotations) -> ReportResult {
+        self.evaluate_stats(&annotations.stats())
+    }
+}
+
+impl ReportBuilder {
+    /// Sets the result to the one `policy` gives for `annotations`.
+    pub fn result_from(mut self, annotations: &Annotations, policy: &ResultPolicy) -> Self {
+        self.set_result_from(annotations, policy);
+        self
+    }
+
+    /// Like [`result_from`](ReportBuilder::result_from).
+    pub fn set_result_from(
+        &mut self,
+        annotations: &Annotations,
+        policy: &ResultPolicy,
+    ) -> &mut Self {
+        self.set_result(policy.evaluate(annotations))
+    }
+}
+
+#[cfg(test)]
+mod policies {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    fn annotations(high: usize, medium: usize, low: usize) -> Annotations {
+        let severities = [
+            (Severity::High, high),
+            (Severity::Medium, medium),
+            (Severity::Low, low),
+        ];
+        Annotations::new(
+            severities
+                .into_iter()
+                .flat_map(|(severity, count)| std::iter::repeat_n(severity, count))
+                .map(|severity| AnnotationBuilder::new("Finding", severity).build().unwrap())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn empty() {
+        let strict = ResultPolicy::new()
+            .fail_on(Severity::High, 0)
+            .fail_on(Severity::Medium, 1)
+            .fail_on(Severity::Low, 1);
+        assert_eq!(
+            ReportResult::Pass,
+            ResultPolicy::new().evaluate(&annotations(0, 0, 0))
+        );
+        assert_eq!(
+            ReportResult::Pass,
+            ResultPolicy::new().evaluate(&annotations(5, 5, 5))
+        );
+        // A threshold of zero fails even without annotations.
+        assert_eq!(ReportResult::Fail, strict.evaluate(&annotations(0, 0, 0)));
+    }
+
+    #[test]
+    fn thresholds() {
+        for (index, severity) in [Severity::High, Severity::Medium, Severity::Low]
+            .into_iter()
+            .enumerate()
+        {
+            let policy = ResultPolicy::new().fail_on(severity, 2);
+            assert_eq!(Some(2), policy.threshold(severity));
+            for (count, expected) in [
+                (1, ReportResult::Pass),
+                (2, ReportResult::Fail),
+                (3, ReportResult::Fail),
+            ] {
+                let mut counts = [0; 3];
+                counts[index] = count;
+                let [high, medium, low] = counts;
+                assert_eq!(
+                    expected,
+                    policy.evaluate(&annotations(high, medium, low)),
+                    "{severity:?} {count}"
+                );
+            }
+            // Other severities do not count towards the threshold.
+            let mut counts = [5; 3];
+            counts[index] = 1;
+            let [high, medium, low] = counts;
+            assert_eq!(
+                ReportResult::Pass,
+                policy.evaluate(&annotations(high, medium, low))
+            );
+        }
+    }
+
+    #[test]
+    fn combined() {
+        let policy = ResultPolicy {
+            fail_on_high: Some(1),
+            fail_on_medium: Some(10),
+            fail_on_low: None,
+        };
+        assert_eq!(ReportResult::Pass, policy.evaluate(&annotations(0, 9, 100)));
+        assert_eq!(ReportResult::Fail, policy.evaluate(&annotations(1, 0, 0)));
+        assert_eq!(ReportResult::Fail, policy.evaluate(&annotations(0, 10, 0)));
+        assert_eq!(ReportResult::Fail, policy.evaluate(&annotations(1, 10, 0)));
+
+        let report = ReportBuilder::new("Analysis")
+            .result_from(&annotations(0, 10, 0), &policy)
+            .build()
+            .unwrap();
+        assert_eq!(Some(ReportResult::Fail), report.result());
+        let mut builder = ReportBuilder::new("Analysis");
+        builder.set_result_from(&annotations(0, 9, 0), &policy);
+        assert_eq!(Some(ReportResult::Pass), builder.build().unwrap().result());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialization() {
+        let policy = serde_json::from_str::<ResultPolicy>(r#"{"fail_on_high": 1}"#).unwrap();
+        assert_eq!(ResultPolicy::new().fail_on(Severity::High, 1), policy);
+        assert_eq!(
+            r#"{"fail_on_high":1}"#,
+            serde_json::to_string(&policy).unwrap()
+        );
+    }
+}
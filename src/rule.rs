@@ -0,0 +1,187 @@
+use crate::annotation::{Annotation, AnnotationBuilder};
+use crate::error::{Error, Result};
+
+/// Returns the reason why `name` cannot be the tool or rule of a rule prefix.
+fn invalid_name(name: &str, is_tool: bool) -> Option<&'static str> {
+    if name.is_empty() {
+        Some("is empty")
+    } else if name.contains(|c: char| c.is_whitespace() || c == '[' || c == ']') {
+        Some("contains whitespace or a bracket")
+    } else if is_tool && name.contains(':') {
+        Some("contains ':'")
+    } else {
+        None
+    }
+}
+
+/// Returns the rule prefix of `tool` and `rule`, e.g. `[clippy::unwrap_used] `,
+/// replacing characters that are not allowed in it by `_`, or `None` if
+/// either is empty.
+pub(crate) fn rule_prefix(tool: &str, rule: &str) -> Option<String> {
+    if tool.is_empty() || rule.is_empty() {
+        return None;
+    }
+    let sanitize = |name: &str, is_tool: bool| -> String {
+        name.chars()
+            .map(|c| {
+                if c.is_whitespace() || c == '[' || c == ']' || (is_tool && c == ':') {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect()
+    };
+    Some(format!(
+        "[{}::{}] ",
+        sanitize(tool, true),
+        sanitize(rule, false)
+    ))
+}
+
+/// Splits `message` into the tool, the rule and the rest of the message, if
+/// it starts with a rule prefix.
+fn parse(message: &str) -> Option<(&str, &str, &str)> {
+    let (prefix, rest) = message.strip_prefix('[')?.split_once("] ")?;
+    let (tool, rule) = prefix.split_once("::")?;
+    if invalid_name(tool, true).is_some() || invalid_name(rule, false).is_some() {
+        return None;
+    }
+    Some((tool, rule, rest))
+}
+
+impl AnnotationBuilder {
+    /// Prefixes the message with the tool and the rule that produced the
+    /// annotation, as in `[clippy::needless_clone] message`, since Bitbucket
+    /// has no field for them. They can be parsed back with
+    /// [`Annotation::rule`].
+    ///
+    /// The prefix is `[`, the tool, `::`, the rule, `]` and a space. The tool
+    /// cannot contain `:`, and neither can contain whitespace or brackets. A
+    /// prefix that is already set is replaced. The prefix is part of the
+    /// message, so it counts against [`MESSAGE_LIMIT`], and it is kept when
+    /// the message is [truncated](AnnotationBuilder::truncate_message).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `tool` or `rule` is empty or has characters that
+    /// are not allowed in the prefix.
+    ///
+    /// [`MESSAGE_LIMIT`]: crate::MESSAGE_LIMIT
+    pub fn rule(mut self, tool: &str, rule: &str) -> Result<Self> {
+        self.set_rule(tool, rule)?;
+        Ok(self)
+    }
+
+    /// Like [`rule`](AnnotationBuilder::rule).
+    ///
+    /// # Errors
+    ///
+    /// See [`rule`](AnnotationBuilder::rule).
+    pub fn set_rule(&mut self, tool: &str, rule: &str) -> Result<&mut Self> {
+        for (field, name, is_tool) in [("tool", tool, true), ("rule", rule, false)] {
+            if let Some(reason) = invalid_name(name, is_tool) {
+                return Err(Error::InvalidValue {
+                    field: field.to_owned(),
+                    reason: format!("'{name}' {reason}"),
+                });
+            }
+        }
+        let message = parse(&self.message).map_or(self.message.as_str(), |(_, _, rest)| rest);
+        self.message = format!("[{tool}::{rule}] {message}");
+        Ok(self)
+    }
+}
+
+impl Annotation {
+    /// Returns the tool and the rule of the prefix set by
+    /// [`AnnotationBuilder::rule`], or `None` if the message has no such
+    /// prefix.
+    pub fn rule(&self) -> Option<(&str, &str)> {
+        parse(&self.message).map(|(tool, rule, _)| (tool, rule))
+    }
+}
+
+#[cfg(test)]
+mod rule_prefix {
+    use super::*;
+    use crate::{Severity, MESSAGE_LIMIT};
+
+    #[test]
+    fn round_trip() {
+        let annotation = AnnotationBuilder::new("Redundant clone", Severity::Low)
+            .rule("clippy", "redundant_clone")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            "[clippy::redundant_clone] Redundant clone",
+            annotation.message
+        );
+        assert_eq!(Some(("clippy", "redundant_clone")), annotation.rule());
+
+        let annotation = AnnotationBuilder::new("Redundant clone", Severity::Low)
+            .rule("clippy", "redundant_clone")
+            .unwrap()
+            .rule("rustc", "E0382")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!("[rustc::E0382] Redundant clone", annotation.message);
+    }
+
+    #[test]
+    fn without_prefix() {
+        for message in [
+            "Redundant clone",
+            "[WIP] Redundant clone",
+            "[a::b]no space",
+            "[::b] empty tool",
+            "[a b::c] whitespace",
+        ] {
+            let annotation = AnnotationBuilder::new(message, Severity::Low)
+                .build()
+                .unwrap();
+            assert_eq!(None, annotation.rule(), "{message}");
+        }
+
+        let annotation = AnnotationBuilder::new("[WIP] Redundant clone", Severity::Low)
+            .rule("clippy", "redundant_clone")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            "[clippy::redundant_clone] [WIP] Redundant clone",
+            annotation.message
+        );
+        assert_eq!(Some(("clippy", "redundant_clone")), annotation.rule());
+    }
+
+    #[test]
+    fn invalid_names() {
+        let builder = || AnnotationBuilder::new("Message", Severity::Low);
+        assert!(builder().rule("", "rule").is_err());
+        assert!(builder().rule("a::b", "rule").is_err());
+        assert!(builder().rule("tool", "a]b").is_err());
+        assert!(builder().rule("tool", "pedantic::rule").is_ok());
+    }
+
+    #[test]
+    fn truncation() {
+        let message = "X".repeat(MESSAGE_LIMIT);
+        assert!(AnnotationBuilder::new(&message, Severity::Low)
+            .rule("clippy", "too_many_lines")
+            .unwrap()
+            .build()
+            .is_err());
+
+        let annotation = AnnotationBuilder::new(message, Severity::Low)
+            .rule("clippy", "too_many_lines")
+            .unwrap()
+            .truncate_message()
+            .build()
+            .unwrap();
+        assert!(annotation.message.len() <= MESSAGE_LIMIT);
+        assert_eq!(Some(("clippy", "too_many_lines")), annotation.rule());
+    }
+}
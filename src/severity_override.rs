@@ -0,0 +1,276 @@
+use globset::GlobSet;
+use serde::{Deserialize, Serialize};
+
+use crate::annotation::{Annotations, Severity};
+use crate::error::Result;
+use crate::path_filter::compile;
+
+/// What a rule of [`SeverityOverrides`] does to the annotations on matching
+/// paths.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityAction {
+    /// Sets the severity.
+    SetSeverity(Severity),
+
+    /// Lowers the severity to at most the given one.
+    CapAt(Severity),
+
+    /// Removes the annotations.
+    Drop,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    globs: GlobSet,
+    action: SeverityAction,
+    raise_caps: bool,
+}
+
+/// Changes the severity of annotations by their path, e.g. to keep findings
+/// in vendored or generated code visible without failing the build.
+///
+/// Patterns follow the semantics of [`PathFilter`](crate::PathFilter).
+/// Annotations without a path are never matched.
+///
+/// The rules are applied in the order they were added, and every rule
+/// matching the path of an annotation is applied:
+///
+/// - [`SetSeverity`](SeverityAction::SetSeverity) sets the severity, but not
+///   above the cap of an earlier [`CapAt`](SeverityAction::CapAt) rule,
+///   unless the rule was added with
+///   [`raise_caps`](SeverityOverrides::raise_caps), which also lifts the cap
+///   for later rules.
+/// - [`CapAt`](SeverityAction::CapAt) lowers the severity to the cap, and
+///   keeps it below the cap for later rules. Several caps add up to the
+///   lowest of them.
+/// - [`Drop`](SeverityAction::Drop) removes the annotation, so later rules
+///   do not matter.
+///
+/// ```
+/// use code_insights::{Severity, SeverityAction, SeverityOverrides};
+///
+/// let overrides = SeverityOverrides::new()
+///     .rule("third_party/", SeverityAction::CapAt(Severity::Low))
+///     .unwrap()
+///     .rule("generated/", SeverityAction::Drop)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SeverityOverrides {
+    rules: Vec<Rule>,
+}
+
+impl SeverityOverrides {
+    /// Constructs overrides without any rules.
+    pub fn new() -> Self {
+        SeverityOverrides::default()
+    }
+
+    /// Adds a rule applying `action` to the annotations whose path matches
+    /// `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `pattern` is not a valid glob.
+    pub fn rule(mut self, pattern: &str, action: SeverityAction) -> Result<Self> {
+        self.rules.push(Rule {
+            globs: compile(&[pattern])?,
+            action,
+            raise_caps: false,
+        });
+        Ok(self)
+    }
+
+    /// Like [`rule`](SeverityOverrides::rule), but the rule may set a
+    /// severity above the caps of earlier rules.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `pattern` is not a valid glob.
+    pub fn raise_caps(mut self, pattern: &str, action: SeverityAction) -> Result<Self> {
+        self.rules.push(Rule {
+            globs: compile(&[pattern])?,
+            action,
+            raise_caps: true,
+        });
+        Ok(self)
+    }
+
+    /// Returns the severity of an annotation on `path` with `severity`, or
+    /// `None` if it is dropped.
+    fn apply(&self, path: &str, mut severity: Severity) -> Option<Severity> {
+        let path = path.trim_start_matches("./");
+        let mut cap: Option<Severity> = None;
+        for rule in self.rules.iter().filter(|rule| rule.globs.is_match(path)) {
+            match rule.action {
+                SeverityAction::SetSeverity(set) => {
+                    severity = match cap {
+                        Some(cap) if !rule.raise_caps && set > cap => cap,
+                        _ => set,
+                    };
+                    if rule.raise_caps {
+                        cap = None;
+                    }
+                }
+                SeverityAction::CapAt(new) => {
+                    let new = cap.map_or(new, |cap| cap.min(new));
+                    severity = severity.min(new);
+                    cap = Some(new);
+                }
+                SeverityAction::Drop => return None,
+            }
+        }
+        Some(severity)
+    }
+}
+
+impl Annotations {
+    /// Applies `overrides` to the severity of the annotations, returning the
+    /// number of dropped annotations.
+    ///
+    /// This should happen before the result of the report is decided from
+    /// the severities.
+    pub fn apply_severity_overrides(&mut self, overrides: &SeverityOverrides) -> usize {
+        self.apply_severity_overrides_counted(overrides).0
+    }
+
+    /// Like [`apply_severity_overrides`](Annotations::apply_severity_overrides),
+    /// but also returns the number of annotations whose severity changed.
+    pub(crate) fn apply_severity_overrides_counted(
+        &mut self,
+        overrides: &SeverityOverrides,
+    ) -> (usize, usize) {
+        let before = self.annotations.len();
+        let mut changed = 0;
+        self.annotations.retain_mut(|annotation| {
+            let Some(path) = &annotation.path else {
+                return true;
+            };
+            match overrides.apply(path, annotation.severity) {
+                Some(severity) => {
+                    changed += usize::from(severity != annotation.severity);
+                    annotation.severity = severity;
+                    true
+                }
+                None => false,
+            }
+        });
+        (before - self.annotations.len(), changed)
+    }
+}
+
+#[cfg(test)]
+mod severity_overrides {
+    use super::*;
+    use crate::{Annotation, AnnotationBuilder};
+
+    fn annotation(path: Option<&str>, severity: Severity) -> Annotation {
+        let mut builder = AnnotationBuilder::new("Message", severity);
+        if let Some(path) = path {
+            builder = builder.path(path);
+        }
+        builder.build().unwrap()
+    }
+
+    fn severities(annotations: &Annotations) -> Vec<Severity> {
+        annotations
+            .annotations
+            .iter()
+            .map(|annotation| annotation.severity)
+            .collect()
+    }
+
+    #[test]
+    fn cap() {
+        let overrides = SeverityOverrides::new()
+            .rule("third_party/", SeverityAction::CapAt(Severity::Medium))
+            .unwrap();
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("third_party/zlib/inflate.c"), Severity::High),
+            annotation(Some("./third_party/zlib/inflate.c"), Severity::Low),
+            annotation(Some("src/lib.rs"), Severity::High),
+            annotation(None, Severity::High),
+        ]);
+        assert_eq!(0, annotations.apply_severity_overrides(&overrides));
+        assert_eq!(
+            vec![
+                Severity::Medium,
+                Severity::Low,
+                Severity::High,
+                Severity::High
+            ],
+            severities(&annotations)
+        );
+    }
+
+    #[test]
+    fn set_and_drop() {
+        let overrides = SeverityOverrides::new()
+            .rule("tests/fixtures", SeverityAction::SetSeverity(Severity::Low))
+            .unwrap()
+            .rule("/generated", SeverityAction::Drop)
+            .unwrap();
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("tests/fixtures/bad.rs"), Severity::High),
+            annotation(Some("generated/api.rs"), Severity::High),
+            annotation(Some("src/generated/api.rs"), Severity::Medium),
+        ]);
+        assert_eq!(1, annotations.apply_severity_overrides(&overrides));
+        assert_eq!(
+            vec![Severity::Low, Severity::Medium],
+            severities(&annotations)
+        );
+    }
+
+    #[test]
+    fn overlapping_globs() {
+        // The cap of the first rule holds for the second.
+        let capped = SeverityOverrides::new()
+            .rule("vendor/", SeverityAction::CapAt(Severity::Low))
+            .unwrap()
+            .rule("*.c", SeverityAction::SetSeverity(Severity::High))
+            .unwrap();
+        let mut annotations = Annotations::new(vec![
+            annotation(Some("vendor/lib.c"), Severity::Medium),
+            annotation(Some("src/lib.c"), Severity::Low),
+        ]);
+        annotations.apply_severity_overrides(&capped);
+        assert_eq!(
+            vec![Severity::Low, Severity::High],
+            severities(&annotations)
+        );
+
+        // Unless the second rule may raise it.
+        let raised = SeverityOverrides::new()
+            .rule("vendor/", SeverityAction::CapAt(Severity::Low))
+            .unwrap()
+            .raise_caps("*.c", SeverityAction::SetSeverity(Severity::High))
+            .unwrap();
+        let mut annotations =
+            Annotations::new(vec![annotation(Some("vendor/lib.c"), Severity::Medium)]);
+        annotations.apply_severity_overrides(&raised);
+        assert_eq!(vec![Severity::High], severities(&annotations));
+
+        // A later, higher cap does not lift an earlier, lower one.
+        let caps = SeverityOverrides::new()
+            .rule("vendor/", SeverityAction::CapAt(Severity::Low))
+            .unwrap()
+            .rule("*.c", SeverityAction::CapAt(Severity::Medium))
+            .unwrap()
+            .rule("*.c", SeverityAction::SetSeverity(Severity::High))
+            .unwrap();
+        let mut annotations =
+            Annotations::new(vec![annotation(Some("vendor/lib.c"), Severity::High)]);
+        annotations.apply_severity_overrides(&caps);
+        assert_eq!(vec![Severity::Low], severities(&annotations));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(SeverityOverrides::new()
+            .rule("src/[", SeverityAction::Drop)
+            .is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::annotation::{Annotation, Annotations, Severity, Type};
+use crate::error::{Error, Result};
+use crate::finding::{Finding, FindingMapper, SeverityMap};
+
+/// Engine ID used when exporting annotations without an external ID in the
+/// `engineId:ruleId` form.
+pub const SONAR_DEFAULT_ENGINE_ID: &str = "code_insights";
+
+/// Rule ID used when exporting annotations without an external ID.
+pub const SONAR_DEFAULT_RULE_ID: &str = "annotation";
+
+#[derive(Serialize, Deserialize)]
+struct SonarReport {
+    issues: Vec<SonarIssue>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarIssue {
+    engine_id: String,
+    rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<SonarSeverity>,
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issue_type: Option<Type>,
+    primary_location: SonarLocation,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarLocation {
+    message: String,
+    file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_range: Option<SonarTextRange>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarTextRange {
+    start_line: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum SonarSeverity {
+    Blocker,
+    Critical,
+    Major,
+    Minor,
+    Info,
+}
+
+impl From<SonarSeverity> for Severity {
+    fn from(severity: SonarSeverity) -> Self {
+        match severity {
+            SonarSeverity::Blocker | SonarSeverity::Critical => Severity::High,
+            SonarSeverity::Major => Severity::Medium,
+            SonarSeverity::Minor | SonarSeverity::Info => Severity::Low,
+        }
+    }
+}
+
+impl From<&Severity> for SonarSeverity {
+    fn from(severity: &Severity) -> Self {
+        match severity {
+            Severity::High => SonarSeverity::Critical,
+            Severity::Medium => SonarSeverity::Major,
+            Severity::Low => SonarSeverity::Minor,
+        }
+    }
+}
+
+/// Converts issues in SonarQube's generic issue import format.
+///
+/// Severities are mapped as follows: `BLOCKER` and `CRITICAL` become
+/// [`Severity::High`], `MAJOR` becomes [`Severity::Medium`], and `MINOR` and
+/// `INFO` become [`Severity::Low`]. Issues without a severity get the default
+/// severity of `severity_map`, which is [`Severity::Medium`] unless configured
+/// otherwise, and the map can override the other severities as well. The
+/// issue type maps directly onto [`Type`], and the external ID of each
+/// annotation is `engineId:ruleId`.
+///
+/// # Errors
+///
+/// Will return `Err` if `value` is not a generic issue report, or if an issue
+/// does not fit within the limits of an [`Annotation`].
+pub fn from_sonar_generic_json(
+    value: &Value,
+    severity_map: Option<&SeverityMap>,
+) -> Result<Annotations> {
+    let report =
+        SonarReport::deserialize(value).map_err(|error| Error::parse_source("SonarQube", error))?;
+
+    let findings = report.issues.into_iter().map(|issue| {
+        let location = issue.primary_location;
+
+        Finding {
+            path: Some(location.file_path),
+            line: location.text_range.map(|range| range.start_line),
+            severity: issue.severity.map(Severity::from),
+            finding_type: issue.issue_type,
+            fingerprint: Some(format!("{}:{}", issue.engine_id, issue.rule_id)),
+            rule_id: Some(issue.rule_id),
+            ..Finding::new("SonarQube", location.message, "")
+        }
+    });
+
+    FindingMapper::new()
+        .severity_map(severity_map.cloned().unwrap_or_default())
+        .message_template("{message}")
+        .map_all(findings)
+}
+
+/// Converts annotations to SonarQube's generic issue import format.
+///
+/// This is the reverse of [`from_sonar_generic_json`], where the severities
+/// map to `CRITICAL`, `MAJOR` and `MINOR` respectively. An external ID in the
+/// `engineId:ruleId` form is split into the engine and rule IDs, any other
+/// external ID is used as the rule ID of [`SONAR_DEFAULT_ENGINE_ID`].
+/// Annotations without a type are exported as `CODE_SMELL`.
+///
+/// SonarQube requires every issue to be located in a file, so annotations
+/// without a path are left out, and file-level annotations are exported
+/// without a text range. Links are not part of the format.
+///
+/// # Errors
+///
+/// Will return `Err` if the issues cannot be serialized.
+pub fn to_sonar_generic_json(annotations: &Annotations) -> Result<Value> {
+    let issues = annotations
+        .annotations
+        .iter()
+        .filter_map(|annotation| {
+            let file_path = annotation.path.clone()?;
+            let (engine_id, rule_id) = engine_and_rule(annotation);
+
+            Some(SonarIssue {
+                engine_id,
+                rule_id,
+                severity: Some(SonarSeverity::from(&annotation.severity)),
+                issue_type: Some(match annotation.annotation_type {
+                    Some(Type::Vulnerability) => Type::Vulnerability,
+                    Some(Type::Bug) => Type::Bug,
+                    Some(Type::CodeSmell) | None => Type::CodeSmell,
+                }),
+                primary_location: SonarLocation {
+                    message: annotation.message.clone(),
+                    file_path,
+                    text_range: annotation
+                        .line
+                        .filter(|line| *line > 0)
+                        .map(|start_line| SonarTextRange { start_line }),
+                },
+            })
+        })
+        .collect();
+
+    serde_json::to_value(SonarReport { issues }).map_err(Error::SerdeError)
+}
+
+fn engine_and_rule(annotation: &Annotation) -> (String, String) {
+    match annotation.external_id.as_deref() {
+        Some(id) => match id.split_once(':') {
+            Some((engine_id, rule_id)) => (engine_id.to_owned(), rule_id.to_owned()),
+            None => (SONAR_DEFAULT_ENGINE_ID.to_owned(), id.to_owned()),
+        },
+        None => (
+            SONAR_DEFAULT_ENGINE_ID.to_owned(),
+            SONAR_DEFAULT_RULE_ID.to_owned(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod generic_issue_format {
+    use super::*;
+    use crate::AnnotationBuilder;
+    use serde_json::json;
+
+    fn issues() -> Value {
+        json!({"issues": [
+            {
+                "engineId": "eslint",
+                "ruleId": "no-eval",
+                "severity": "BLOCKER",
+                "type": "VULNERABILITY",
+                "primaryLocation": {
+                    "message": "eval can be harmful",
+                    "filePath": "src/index.js",
+                    "textRange": {"startLine": 12, "endLine": 12, "startColumn": 4, "endColumn": 8}
+                },
+                "effortMinutes": 10
+            },
+            {
+                "engineId": "eslint",
+                "ruleId": "no-unused-vars",
+                "severity": "INFO",
+                "type": "CODE_SMELL",
+                "primaryLocation": {
+                    "message": "'x' is defined but never used",
+                    "filePath": "src/util.js"
+                }
+            }
+        ]})
+    }
+
+    #[test]
+    fn import() {
+        let expected = json!({"annotations": [
+            {
+                "message": "eval can be harmful",
+                "severity": "HIGH",
+                "type": "VULNERABILITY",
+                "path": "src/index.js",
+                "line": 12,
+                "externalId": "eslint:no-eval"
+            },
+            {
+                "message": "'x' is defined but never used",
+                "severity": "LOW",
+                "type": "CODE_SMELL",
+                "path": "src/util.js",
+                "externalId": "eslint:no-unused-vars"
+            }
+        ]});
+        let annotations = from_sonar_generic_json(&issues(), None).unwrap();
+        assert_eq!(expected, serde_json::to_value(annotations).unwrap());
+    }
+
+    #[test]
+    fn severities() {
+        let severities = ["BLOCKER", "CRITICAL", "MAJOR", "MINOR", "INFO"].map(|severity| {
+            let mut issues = issues();
+            issues["issues"][0]["severity"] = json!(severity);
+            from_sonar_generic_json(&issues, None)
+                .unwrap()
+                .annotations
+                .remove(0)
+                .severity
+        });
+        assert_eq!(
+            [
+                Severity::High,
+                Severity::High,
+                Severity::Medium,
+                Severity::Low,
+                Severity::Low
+            ],
+            severities
+        );
+    }
+
+    #[test]
+    fn missing_severity() {
+        let mut issues = issues();
+        issues["issues"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("severity");
+        let severity_map = SeverityMap::builder()
+            .default(Severity::Low)
+            .rule("no-unused-vars", Severity::High)
+            .build();
+
+        let annotations = from_sonar_generic_json(&issues, None).unwrap();
+        assert_eq!(Severity::Medium, annotations.annotations[0].severity);
+        let annotations = from_sonar_generic_json(&issues, Some(&severity_map)).unwrap();
+        assert_eq!(Severity::Low, annotations.annotations[0].severity);
+        assert_eq!(Severity::High, annotations.annotations[1].severity);
+    }
+
+    #[test]
+    fn export() {
+        let annotations = Annotations::new(vec![
+            AnnotationBuilder::new("Possible null dereference", Severity::Medium)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .line(7)
+                .external_id("clippy:unwrap_used")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("File is too long", Severity::Low)
+                .path("src/main.rs")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Not located in a file", Severity::High)
+                .build()
+                .unwrap(),
+        ]);
+        let expected = json!({"issues": [
+            {
+                "engineId": "clippy",
+                "ruleId": "unwrap_used",
+                "severity": "MAJOR",
+                "type": "BUG",
+                "primaryLocation": {
+                    "message": "Possible null dereference",
+                    "filePath": "src/lib.rs",
+                    "textRange": {"startLine": 7}
+                }
+            },
+            {
+                "engineId": SONAR_DEFAULT_ENGINE_ID,
+                "ruleId": SONAR_DEFAULT_RULE_ID,
+                "severity": "MINOR",
+                "type": "CODE_SMELL",
+                "primaryLocation": {
+                    "message": "File is too long",
+                    "filePath": "src/main.rs"
+                }
+            }
+        ]});
+        assert_eq!(expected, to_sonar_generic_json(&annotations).unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut issues = issues();
+        issues["issues"][0]["severity"] = json!("CRITICAL");
+        issues["issues"][1]["severity"] = json!("MINOR");
+
+        let annotations = from_sonar_generic_json(&issues, None).unwrap();
+        let exported = to_sonar_generic_json(&annotations).unwrap();
+        assert_eq!(
+            annotations,
+            from_sonar_generic_json(&exported, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let result = from_sonar_generic_json(&json!({"issues": [{"engineId": "x"}]}), None);
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeSet;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::annotation::{Annotations, Severity, Type};
+
+/// Counts of annotations by severity and type, computed by
+/// [`Annotations::stats`].
+///
+/// It serializes the counts by their serialized severities and types, e.g.
+/// `{"severities": {"HIGH": 2, ...}, "types": {"BUG": 1, ...}, "untyped": 1,
+/// "total": 4, "files": 2}`, so that it can be logged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnnotationStats {
+    severities: [usize; 3],
+    types: [usize; 3],
+    untyped: usize,
+    total: usize,
+    files: usize,
+}
+
+impl AnnotationStats {
+    /// Returns the number of annotations with the given severity.
+    pub fn severity(&self, severity: Severity) -> usize {
+        self.severities[severity as usize]
+    }
+
+    /// Returns the number of annotations of the given type.
+    pub fn annotation_type(&self, annotation_type: Type) -> usize {
+        self.types[usize::from(annotation_type.rank())]
+    }
+
+    /// Returns the number of annotations without a type.
+    pub fn untyped(&self) -> usize {
+        self.untyped
+    }
+
+    /// Returns the number of annotations.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the number of distinct files that are annotated.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+}
+
+/// Serializes pairs as the entries of a map.
+struct Counts<K>([(K, usize); 3]);
+
+impl<K: Serialize> Serialize for Counts<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, count)| (key, count)))
+    }
+}
+
+impl Serialize for AnnotationStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let severities = [Severity::High, Severity::Medium, Severity::Low]
+            .map(|severity| (severity, self.severity(severity)));
+        let types = [Type::Vulnerability, Type::Bug, Type::CodeSmell]
+            .map(|annotation_type| (annotation_type, self.annotation_type(annotation_type)));
+
+        let mut stats = serializer.serialize_struct("AnnotationStats", 5)?;
+        stats.serialize_field("severities", &Counts(severities))?;
+        stats.serialize_field("types", &Counts(types))?;
+        stats.serialize_field("untyped", &self.untyped)?;
+        stats.serialize_field("total", &self.total)?;
+        stats.serialize_field("files", &self.files)?;
+        stats.end()
+    }
+}
+
+impl Annotations {
+    /// Counts the annotations by severity and type, as they are at the time
+    /// of the call.
+    pub fn stats(&self) -> AnnotationStats {
+        let mut stats = AnnotationStats::default();
+        let mut files = BTreeSet::new();
+        for annotation in &self.annotations {
+            stats.severities[annotation.severity as usize] += 1;
+            match annotation.annotation_type {
+                Some(annotation_type) => stats.types[usize::from(annotation_type.rank())] += 1,
+                None => stats.untyped += 1,
+            }
+            if let Some(path) = &annotation.path {
+                files.insert(path.as_str());
+            }
+        }
+        stats.total = self.annotations.len();
+        stats.files = files.len();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod annotation_stats {
+    use super::*;
+    use crate::AnnotationBuilder;
+
+    #[test]
+    fn counts() {
+        let annotation = |severity, annotation_type: Option<Type>, path: Option<&str>| {
+            let mut builder = AnnotationBuilder::new("Message", severity);
+            if let Some(annotation_type) = annotation_type {
+                builder = builder.annotation_type(annotation_type);
+            }
+            if let Some(path) = path {
+                builder = builder.path(path);
+            }
+            builder.build().unwrap()
+        };
+        let stats = Annotations::new(vec![
+            annotation(
+                Severity::High,
+                Some(Type::Vulnerability),
+                Some("src/lib.rs"),
+            ),
+            annotation(Severity::High, Some(Type::Bug), Some("src/lib.rs")),
+            annotation(Severity::Low, Some(Type::CodeSmell), Some("src/main.rs")),
+            annotation(Severity::Low, None, None),
+        ])
+        .stats();
+
+        assert_eq!(2, stats.severity(Severity::High));
+        assert_eq!(0, stats.severity(Severity::Medium));
+        assert_eq!(2, stats.severity(Severity::Low));
+        assert_eq!(1, stats.annotation_type(Type::Vulnerability));
+        assert_eq!(1, stats.annotation_type(Type::Bug));
+        assert_eq!(1, stats.annotation_type(Type::CodeSmell));
+        assert_eq!(1, stats.untyped());
+        assert_eq!(4, stats.total());
+        assert_eq!(2, stats.files());
+        assert_eq!(AnnotationStats::default(), Annotations::new(vec![]).stats());
+    }
+
+    fn annotations() -> Annotations {
+        Annotations::new(vec![
+            AnnotationBuilder::new("Message", Severity::High)
+                .annotation_type(Type::Bug)
+                .path("src/lib.rs")
+                .build()
+                .unwrap(),
+            AnnotationBuilder::new("Message", Severity::Low)
+                .path("src/main.rs")
+                .build()
+                .unwrap(),
+        ])
+    }
+
+    #[test]
+    fn after_filtering() {
+        let mut annotations = annotations();
+        assert_eq!(2, annotations.stats().total());
+
+        annotations.retain_severity(Severity::Medium);
+        let stats = annotations.stats();
+        assert_eq!(1, stats.total());
+        assert_eq!(0, stats.severity(Severity::Low));
+        assert_eq!(0, stats.untyped());
+        assert_eq!(1, stats.files());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialization() {
+        assert_eq!(
+            serde_json::json!({
+                "severities": {"HIGH": 1, "MEDIUM": 0, "LOW": 1},
+                "types": {"VULNERABILITY": 0, "BUG": 1, "CODE_SMELL": 0},
+                "untyped": 1,
+                "total": 2,
+                "files": 2
+            }),
+            serde_json::to_value(annotations().stats()).unwrap()
+        );
+    }
+}
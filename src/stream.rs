@@ -0,0 +1,208 @@
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+
+use serde::ser::{self, SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::annotation::Annotation;
+use crate::error::{Error, Result};
+
+/// Serializes the annotations of an iterator as the elements of a sequence,
+/// keeping the first error that is not a serialization error, since serde
+/// can only pass on its message.
+struct Stream<I> {
+    annotations: RefCell<Option<I>>,
+    limit: Option<usize>,
+    fail_at_limit: bool,
+    written: Cell<usize>,
+    error: Cell<Option<Error>>,
+}
+
+impl<I: Iterator<Item = Annotation>> Serialize for Stream<I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let fail = |error: Error| {
+            let message = error.to_string();
+            self.error.set(Some(error));
+            <S::Error as ser::Error>::custom(message)
+        };
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut annotations = self.annotations.borrow_mut().take().into_iter().flatten();
+        while self.limit.is_none_or(|limit| self.written.get() < limit) {
+            let Some(annotation) = annotations.next() else {
+                break;
+            };
+            annotation.validate_fields().map_err(fail)?;
+            seq.serialize_element(&annotation)?;
+            self.written.set(self.written.get() + 1);
+        }
+        if self.fail_at_limit && annotations.next().is_some() {
+            let limit = self.written.get();
+            return Err(fail(Error::FieldTooLong {
+                name: "annotations".to_owned(),
+                len: limit + 1,
+                limit,
+            }));
+        }
+        seq.end()
+    }
+}
+
+fn serialize<W, I>(
+    writer: W,
+    annotations: I,
+    limit: Option<usize>,
+    fail_at_limit: bool,
+) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Annotation>,
+{
+    let stream = Stream {
+        annotations: RefCell::new(Some(annotations.into_iter())),
+        limit,
+        fail_at_limit,
+        written: Cell::new(0),
+        error: Cell::new(None),
+    };
+    let mut serializer = serde_json::Serializer::new(writer);
+    let result = serializer.serialize_map(Some(1)).and_then(|mut map| {
+        map.serialize_entry("annotations", &stream)?;
+        SerializeMap::end(map)
+    });
+    if let Err(error) = result {
+        return Err(stream.error.take().unwrap_or(Error::SerdeError(error)));
+    }
+    serializer
+        .into_inner()
+        .flush()
+        .map_err(serde_json::Error::io)?;
+    Ok(stream.written.get())
+}
+
+/// Writes the annotations of `annotations` as an `{"annotations": [...]}`
+/// payload while iterating, instead of collecting them into [`Annotations`]
+/// first, and returns the number of annotations written.
+///
+/// If `limit` is set, writing stops after that many annotations and the
+/// rest of the iterator is not consumed.
+///
+/// # Errors
+///
+/// Will return `Err` if an annotation has fields that are longer than
+/// Bitbucket allows, or if writing fails. The payload is incomplete then.
+///
+/// [`Annotations`]: crate::Annotations
+pub fn serialize_annotations_from_iter<W, I>(
+    writer: W,
+    annotations: I,
+    limit: Option<usize>,
+) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Annotation>,
+{
+    serialize(writer, annotations, limit, false)
+}
+
+/// Like [`serialize_annotations_from_iter`], but fails if there are more than
+/// `limit` annotations instead of leaving out the rest.
+///
+/// The error is a [`Error::FieldTooLong`] whose length is one more than the
+/// limit, since the rest of the iterator is not consumed.
+///
+/// # Errors
+///
+/// Will return `Err` if there are more than `limit` annotations, if an
+/// annotation has fields that are longer than Bitbucket allows, or if writing
+/// fails. The payload is incomplete then.
+pub fn try_serialize_annotations_from_iter<W, I>(
+    writer: W,
+    annotations: I,
+    limit: usize,
+) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Annotation>,
+{
+    serialize(writer, annotations, Some(limit), true)
+}
+
+#[cfg(test)]
+mod streamed_annotations {
+    use super::*;
+    use crate::{AnnotationBuilder, Annotations, Severity, MESSAGE_LIMIT};
+
+    fn annotation(index: usize) -> Annotation {
+        AnnotationBuilder::new(format!("Issue {index}"), Severity::Low)
+            .path(format!("src/module_{}.rs", index % 100))
+            .line(index as u32)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn many_annotations() {
+        let mut json = Vec::new();
+        let written =
+            serialize_annotations_from_iter(&mut json, (0..10_000).map(annotation), None).unwrap();
+        assert_eq!(10_000, written);
+
+        let annotations = serde_json::from_slice::<Annotations>(&json).unwrap();
+        assert_eq!(10_000, annotations.annotations.len());
+        for (index, parsed) in annotations.annotations.iter().enumerate() {
+            assert_eq!(&annotation(index), parsed);
+        }
+
+        let collected = Annotations::new((0..10_000).map(annotation).collect::<Vec<_>>());
+        assert_eq!(serde_json::to_vec(&collected).unwrap(), json);
+    }
+
+    #[test]
+    fn empty() {
+        let mut json = Vec::new();
+        assert_eq!(
+            0,
+            serialize_annotations_from_iter(&mut json, [], None).unwrap()
+        );
+        assert_eq!(br#"{"annotations":[]}"#, json.as_slice());
+    }
+
+    #[test]
+    fn limit() {
+        let mut consumed = 0;
+        let annotations = (0..10).map(|index| {
+            consumed += 1;
+            annotation(index)
+        });
+        let mut json = Vec::new();
+        assert_eq!(
+            3,
+            serialize_annotations_from_iter(&mut json, annotations, Some(3)).unwrap()
+        );
+        assert_eq!(3, consumed);
+        let annotations = serde_json::from_slice::<Annotations>(&json).unwrap();
+        assert_eq!(3, annotations.annotations.len());
+
+        let mut json = Vec::new();
+        assert_eq!(
+            3,
+            try_serialize_annotations_from_iter(&mut json, (0..3).map(annotation), 3).unwrap()
+        );
+        assert!(matches!(
+            try_serialize_annotations_from_iter(&mut json, (0..4).map(annotation), 3),
+            Err(Error::FieldTooLong { limit: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_annotation() {
+        let mut invalid = annotation(1);
+        invalid.message = "X".repeat(MESSAGE_LIMIT + 1);
+        let error = serialize_annotations_from_iter(Vec::new(), [annotation(0), invalid], None)
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::FieldTooLong { ref name, .. } if name == "message"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::annotation::{Annotations, Severity};
+use crate::error::{Error, Result};
+use crate::finding::{Finding, FindingMapper, SeverityMap};
+
+#[derive(Deserialize)]
+struct SwiftLintViolation {
+    file: String,
+    line: Option<u32>,
+    reason: String,
+    rule_id: String,
+    severity: String,
+}
+
+/// Converts the output of `swiftlint lint --reporter json`.
+///
+/// Violations with the `Error` severity become [`Severity::High`] annotations
+/// and all others [`Severity::Medium`]. The message is the reason prefixed
+/// with the rule prefix, as in `[SwiftLint::line_length] reason`, truncated to fit within [`MESSAGE_LIMIT`]. The absolute
+/// file paths reported by SwiftLint are made relative to `root`. The
+/// severities can be overridden through `severity_map`.
+///
+/// [`MESSAGE_LIMIT`]: crate::MESSAGE_LIMIT
+///
+/// # Errors
+///
+/// Will return `Err` if `value` is not SwiftLint JSON output.
+pub fn from_swiftlint_json(
+    value: &Value,
+    root: &Path,
+    severity_map: Option<&SeverityMap>,
+) -> Result<Annotations> {
+    let violations = Vec::<SwiftLintViolation>::deserialize(value)
+        .map_err(|error| Error::parse_source("SwiftLint", error))?;
+
+    let findings = violations.into_iter().map(|violation| {
+        let severity = if violation.severity.eq_ignore_ascii_case("error") {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        Finding {
+            rule_id: Some(violation.rule_id),
+            path: Some(violation.file),
+            line: violation.line,
+            severity: Some(severity),
+            ..Finding::new("SwiftLint", violation.reason, violation.severity)
+        }
+    });
+
+    FindingMapper::new()
+        .severity_map(severity_map.cloned().unwrap_or_default())
+        .root(root)
+        .map_all(findings)
+}
+
+#[cfg(test)]
+mod conversion {
+    use super::*;
+    use crate::{EscalationRule, EscalationRules, GroupKey, MESSAGE_LIMIT};
+    use serde_json::json;
+
+    fn output(reason: &str) -> Value {
+        json!([
+            {
+                "character": 5,
+                "file": "/Users/ci/app/Sources/App/ContentView.swift",
+                "line": 12,
+                "reason": reason,
+                "rule_id": "line_length",
+                "severity": "Warning",
+                "type": "Line Length"
+            },
+            {
+                "character": null,
+                "file": "/Users/ci/app/Sources/App/Model.swift",
+                "line": 3,
+                "reason": "Force casts should be avoided",
+                "rule_id": "force_cast",
+                "severity": "Error",
+                "type": "Force Cast"
+            }
+        ])
+    }
+
+    #[test]
+    fn violations() {
+        let expected = json!({"annotations": [
+            {
+                "message": "[SwiftLint::line_length] Line should be 120 characters or less",
+                "severity": "MEDIUM",
+                "path": "Sources/App/ContentView.swift",
+                "line": 12
+            },
+            {
+                "message": "[SwiftLint::force_cast] Force casts should be avoided",
+                "severity": "HIGH",
+                "path": "Sources/App/Model.swift",
+                "line": 3
+            }
+        ]});
+        let output = output("Line should be 120 characters or less");
+        let annotations = from_swiftlint_json(&output, Path::new("/Users/ci/app"), None).unwrap();
+        assert_eq!(expected, serde_json::to_value(annotations).unwrap());
+    }
+
+    #[test]
+    fn rule_prefix() {
+        let mut output = output("Line should be 120 characters or less");
+        let line_length = output[0].clone();
+        output.as_array_mut().unwrap().push(line_length);
+        let mut annotations =
+            from_swiftlint_json(&output, Path::new("/Users/ci/app"), None).unwrap();
+        assert_eq!(
+            Some(("SwiftLint", "line_length")),
+            annotations.annotations[0].rule()
+        );
+
+        let rules = EscalationRules::new()
+            .rule(EscalationRule::new("Long lines", 2, Severity::High).rule("SwiftLint::line_*"));
+        let outcome = annotations.apply_escalations(&rules);
+        assert_eq!(2, outcome.fired()[0].matched());
+
+        let sampled = annotations.sample(1, GroupKey::RulePrefix);
+        assert_eq!(1, sampled.len());
+        assert_eq!(Some("SwiftLint::line_length"), sampled[0].rule());
+        assert_eq!(1, sampled[0].removed());
+        let severities = annotations
+            .annotations
+            .iter()
+            .map(|annotation| annotation.severity)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Severity::High, Severity::High], severities);
+    }
+
+    #[test]
+    fn long_reason_is_truncated() {
+        let output = output(&"X".repeat(MESSAGE_LIMIT));
+        let annotations = from_swiftlint_json(&output, Path::new("/Users/ci/app"), None).unwrap();
+        assert_eq!(MESSAGE_LIMIT, annotations.annotations[0].message.len());
+    }
+
+    #[test]
+    fn malformed() {
+        let result = from_swiftlint_json(&json!({"file": "a.swift"}), Path::new("/"), None);
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use crate::report::{Data, Parameter, ReportBuilder, DATA_LIMIT};
+
+/// Measures the duration of an analysis, to be shown as a data field.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportTimer {
+    started: Instant,
+    now: fn() -> Instant,
+}
+
+impl ReportTimer {
+    /// Starts a timer.
+    pub fn start() -> Self {
+        ReportTimer::start_with_clock(Instant::now)
+    }
+
+    /// Starts a timer that reads the time from `now` instead of the system
+    /// clock, e.g. to make tests deterministic.
+    pub fn start_with_clock(now: fn() -> Instant) -> Self {
+        ReportTimer {
+            started: now(),
+            now,
+        }
+    }
+
+    /// Returns the time elapsed since the timer was started.
+    pub fn elapsed(&self) -> Duration {
+        (self.now)().saturating_duration_since(self.started)
+    }
+
+    /// Returns a data field titled `title` with the elapsed time.
+    pub fn elapsed_data<T: Into<String>>(&self, title: T) -> Data {
+        Data {
+            title: title.into(),
+            parameter: Parameter::Duration(
+                u64::try_from(self.elapsed().as_millis()).unwrap_or(u64::MAX),
+            ),
+        }
+    }
+
+    /// Appends a data field titled `title` with the elapsed time to the data
+    /// fields of `builder`.
+    ///
+    /// The field is left out if `builder` already has [`DATA_LIMIT`] data
+    /// fields, so that the report can still be built.
+    pub fn finish_into<T: Into<String>>(
+        &self,
+        mut builder: ReportBuilder,
+        title: T,
+    ) -> ReportBuilder {
+        let data = builder.data.get_or_insert_with(Vec::new);
+        if data.len() < DATA_LIMIT {
+            data.push(self.elapsed_data(title));
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod report_timer {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::OnceLock;
+
+    /// A clock that advances by 1.5 seconds whenever it is read.
+    fn clock() -> Instant {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        static TICKS: AtomicU64 = AtomicU64::new(0);
+        let ticks = TICKS.fetch_add(1, Ordering::SeqCst);
+        *EPOCH.get_or_init(Instant::now) + Duration::from_millis(1500 * ticks)
+    }
+
+    #[test]
+    fn recorded_duration() {
+        let timer = ReportTimer::start_with_clock(clock);
+        let first = timer.elapsed();
+        let second = timer.elapsed();
+        assert_eq!(Duration::from_millis(1500), second - first);
+        assert_eq!(0, first.as_millis() % 1500);
+
+        let data = timer.elapsed_data("Analysis time");
+        assert_eq!("Analysis time", data.title);
+        assert_eq!(
+            Parameter::Duration(u64::try_from(second.as_millis()).unwrap() + 1500),
+            data.parameter
+        );
+    }
+
+    #[test]
+    fn respects_data_limit() {
+        let timer = ReportTimer::start();
+        let field = |index: usize| Data {
+            title: format!("Field {index}"),
+            parameter: Parameter::Boolean(true),
+        };
+
+        let builder = ReportBuilder::new("Title").data((0..DATA_LIMIT - 1).map(field));
+        let report = timer.finish_into(builder, "Analysis time").build().unwrap();
+        let data = report.data.unwrap();
+        assert_eq!(DATA_LIMIT, data.len());
+        assert_eq!("Analysis time", data[DATA_LIMIT - 1].title);
+
+        let builder = ReportBuilder::new("Title").data((0..DATA_LIMIT).map(field));
+        let report = timer.finish_into(builder, "Analysis time").build().unwrap();
+        let data = report.data.unwrap();
+        assert_eq!(DATA_LIMIT, data.len());
+        assert!(data.iter().all(|data| data.title != "Analysis time"));
+
+        let report = timer
+            .finish_into(ReportBuilder::new("Title"), "Analysis time")
+            .build()
+            .unwrap();
+        assert_eq!(Some(1), report.data.map(|data| data.len()));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::number::Number;
+use crate::render::render_duration;
+use crate::report::{Data, Parameter, Report};
+
+impl Report {
+    /// Computes the change of each numeric data field since `previous`,
+    /// returning a text data field like "Coverage Δ" with the value "+2" for
+    /// each of them.
+    ///
+    /// Data fields are paired by title. Fields that are not numbers,
+    /// percentages or durations, whose type changed, or that `previous` lacks
+    /// are skipped.
+    pub fn delta_against(&self, previous: &Report) -> Vec<Data> {
+        self.data
+            .iter()
+            .flatten()
+            .filter_map(|data| {
+                Some(Data {
+                    title: format!("{} Δ", data.title),
+                    parameter: Parameter::Text(paired_delta(previous, data)?),
+                })
+            })
+            .collect()
+    }
+
+    /// Appends the change of each numeric data field since `previous` to its
+    /// title, as in "Coverage (+2)", returning the number of changed titles.
+    ///
+    /// This keeps the number of data fields, which is limited to
+    /// [`DATA_LIMIT`](crate::DATA_LIMIT). Fields are paired as in
+    /// [`delta_against`](Report::delta_against).
+    pub fn apply_deltas(&mut self, previous: &Report) -> usize {
+        let mut changed = 0;
+        for data in self.data.iter_mut().flatten() {
+            if let Some(delta) = paired_delta(previous, data) {
+                data.title = format!("{} ({delta})", data.title);
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+/// Finds the data field of `previous` with the title of `data` and formats
+/// the difference between them.
+fn paired_delta(previous: &Report, data: &Data) -> Option<String> {
+    let old = previous
+        .data
+        .iter()
+        .flatten()
+        .find(|old| old.title == data.title)?;
+    delta(&old.parameter, &data.parameter)
+}
+
+/// Formats the difference between two numeric parameters of the same type
+/// with an explicit sign.
+fn delta(old: &Parameter, new: &Parameter) -> Option<String> {
+    match (old, new) {
+        (Parameter::Number(old), Parameter::Number(new)) => {
+            let integer = |number: &Number| {
+                number
+                    .as_i64()
+                    .map(i128::from)
+                    .or_else(|| number.as_u64().map(i128::from))
+            };
+            match (integer(old), integer(new)) {
+                (Some(old), Some(new)) => Some(signed(new - old, |delta| delta.to_string())),
+                _ => {
+                    let delta = new.as_f64() - old.as_f64();
+                    let rounded = (delta * 100.0).round() / 100.0;
+                    Some(if rounded < 0.0 {
+                        format!("-{}", -rounded)
+                    } else {
+                        format!("+{}", rounded.abs())
+                    })
+                }
+            }
+        }
+        (Parameter::Percentage(old), Parameter::Percentage(new)) => {
+            Some(signed(i128::from(*new) - i128::from(*old), |delta| {
+                delta.to_string()
+            }))
+        }
+        (Parameter::Duration(old), Parameter::Duration(new)) => {
+            Some(signed(i128::from(*new) - i128::from(*old), |millis| {
+                render_duration(u64::try_from(millis).unwrap_or(u64::MAX))
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn signed(delta: i128, format: impl Fn(i128) -> String) -> String {
+    if delta < 0 {
+        format!("-{}", format(-delta))
+    } else {
+        format!("+{}", format(delta))
+    }
+}
+
+#[cfg(test)]
+mod deltas {
+    use super::*;
+    use crate::ReportBuilder;
+
+    fn data(title: &str, parameter: Parameter) -> Data {
+        Data {
+            title: title.to_owned(),
+            parameter,
+        }
+    }
+
+    fn report(data: Vec<Data>) -> Report {
+        ReportBuilder::new("Analysis").data(data).build().unwrap()
+    }
+
+    fn previous() -> Report {
+        report(vec![
+            data("Coverage", Parameter::Percentage(82)),
+            data("Warnings", Parameter::Number(12.into())),
+            data("Score", Parameter::Number(Number::from_f64(7.5).unwrap())),
+            data("Analysis time", Parameter::Duration(200_000)),
+            data("Branch", Parameter::Text("main".to_owned())),
+        ])
+    }
+
+    fn current() -> Report {
+        report(vec![
+            data("Coverage", Parameter::Percentage(84)),
+            data("Warnings", Parameter::Number(15.into())),
+            data("Score", Parameter::Number(Number::from_f64(7.2).unwrap())),
+            data("Analysis time", Parameter::Duration(125_000)),
+            data("Branch", Parameter::Text("feature".to_owned())),
+            data("Vulnerabilities", Parameter::Number(0.into())),
+        ])
+    }
+
+    #[test]
+    fn improved_regressed_and_missing() {
+        let text = |title: &str, value: &str| data(title, Parameter::Text(value.to_owned()));
+        assert_eq!(
+            vec![
+                text("Coverage Δ", "+2"),
+                text("Warnings Δ", "+3"),
+                text("Score Δ", "-0.3"),
+                text("Analysis time Δ", "-1m 15s"),
+            ],
+            current().delta_against(&previous())
+        );
+    }
+
+    #[test]
+    fn unchanged_and_mismatched_types() {
+        let previous = report(vec![
+            data("Coverage", Parameter::Percentage(84)),
+            data("Warnings", Parameter::Text("many".to_owned())),
+        ]);
+        let current = report(vec![
+            data("Coverage", Parameter::Percentage(84)),
+            data("Warnings", Parameter::Number(15.into())),
+        ]);
+        assert_eq!(
+            vec![data("Coverage Δ", Parameter::Text("+0".to_owned()))],
+            current.delta_against(&previous)
+        );
+        assert!(current.delta_against(&report(vec![])).is_empty());
+    }
+
+    #[test]
+    fn titles() {
+        let mut current = current();
+        assert_eq!(4, current.apply_deltas(&previous()));
+        let titles = current
+            .data
+            .unwrap()
+            .into_iter()
+            .map(|data| data.title)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "Coverage (+2)",
+                "Warnings (+3)",
+                "Score (-0.3)",
+                "Analysis time (-1m 15s)",
+                "Branch",
+                "Vulnerabilities"
+            ],
+            titles
+        );
+    }
+}
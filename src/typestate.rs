@@ -0,0 +1,223 @@
+use std::marker::PhantomData;
+use std::path::Path;
+
+#[cfg(feature = "json")]
+use serde_json::Value;
+
+use crate::annotation::{Annotation, AnnotationBuilder, Severity, Type};
+use crate::error::Result;
+
+/// State of a [`TypedAnnotationBuilder`] whose path is not set yet.
+#[derive(Debug, Clone, Copy)]
+pub struct NoPath;
+
+/// State of a [`TypedAnnotationBuilder`] whose path is set.
+#[derive(Debug, Clone, Copy)]
+pub struct HasPath;
+
+/// A builder of annotations that only allows setting the line once the path
+/// is set, since Bitbucket ignores annotations with a line but no path.
+///
+/// It behaves like [`AnnotationBuilder`] otherwise, and is the recommended
+/// way to build annotations:
+///
+/// ```
+/// use code_insights::{Severity, TypedAnnotationBuilder};
+///
+/// let annotation = TypedAnnotationBuilder::new("Possible panic", Severity::High)
+///     .path("src/lib.rs")
+///     .line(40)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypedAnnotationBuilder<S> {
+    builder: AnnotationBuilder,
+    state: PhantomData<S>,
+}
+
+impl<S> TypedAnnotationBuilder<S> {
+    fn map<T>(
+        self,
+        f: impl FnOnce(AnnotationBuilder) -> AnnotationBuilder,
+    ) -> TypedAnnotationBuilder<T> {
+        TypedAnnotationBuilder {
+            builder: f(self.builder),
+            state: PhantomData,
+        }
+    }
+
+    /// Like [`AnnotationBuilder::truncate_message`].
+    pub fn truncate_message(self) -> Self {
+        self.map(AnnotationBuilder::truncate_message)
+    }
+
+    /// Like [`AnnotationBuilder::annotation_type`].
+    pub fn annotation_type(self, annotation_type: Type) -> Self {
+        self.map(|builder| builder.annotation_type(annotation_type))
+    }
+
+    /// Like [`AnnotationBuilder::quiet`].
+    pub fn quiet(self) -> Self {
+        self.map(AnnotationBuilder::quiet)
+    }
+
+    /// Like [`AnnotationBuilder::link`].
+    pub fn link<T: Into<String>>(self, link: T) -> Self {
+        self.map(|builder| builder.link(link))
+    }
+
+    /// Like [`AnnotationBuilder::external_id`].
+    pub fn external_id<T: Into<String>>(self, external_id: T) -> Self {
+        self.map(|builder| builder.external_id(external_id))
+    }
+
+    /// Like [`AnnotationBuilder::rule`].
+    ///
+    /// # Errors
+    ///
+    /// See [`AnnotationBuilder::rule`].
+    pub fn rule(mut self, tool: &str, rule: &str) -> Result<Self> {
+        self.builder = self.builder.rule(tool, rule)?;
+        Ok(self)
+    }
+
+    /// Like [`AnnotationBuilder::extension`].
+    ///
+    /// # Errors
+    ///
+    /// See [`AnnotationBuilder::extension`].
+    #[cfg(feature = "json")]
+    pub fn extension<K, V>(mut self, key: K, value: V) -> Result<Self>
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.builder = self.builder.extension(key, value)?;
+        Ok(self)
+    }
+
+    /// Returns the dynamic builder, which allows every combination of fields.
+    pub fn into_builder(self) -> AnnotationBuilder {
+        self.builder
+    }
+
+    /// Like [`AnnotationBuilder::build`].
+    ///
+    /// # Errors
+    ///
+    /// See [`AnnotationBuilder::build`].
+    pub fn build(self) -> Result<Annotation> {
+        self.builder.build()
+    }
+}
+
+impl TypedAnnotationBuilder<NoPath> {
+    /// Like [`AnnotationBuilder::new`].
+    pub fn new<T: Into<String>>(message: T, severity: Severity) -> Self {
+        TypedAnnotationBuilder {
+            builder: AnnotationBuilder::new(message, severity),
+            state: PhantomData,
+        }
+    }
+
+    /// Like [`AnnotationBuilder::allow_absolute_path`].
+    pub fn allow_absolute_path(self) -> Self {
+        self.map(AnnotationBuilder::allow_absolute_path)
+    }
+
+    /// Like [`AnnotationBuilder::path`], after which the line can be set.
+    pub fn path<T: Into<String>>(self, path: T) -> TypedAnnotationBuilder<HasPath> {
+        self.map(|builder| builder.path(path))
+    }
+
+    /// Like [`AnnotationBuilder::path_from`], after which the line can be
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// See [`AnnotationBuilder::path_from`].
+    pub fn path_from<P: AsRef<Path>>(self, path: P) -> Result<TypedAnnotationBuilder<HasPath>> {
+        let builder = self.builder.path_from(path)?;
+        Ok(TypedAnnotationBuilder {
+            builder,
+            state: PhantomData,
+        })
+    }
+
+    /// Sets both the path and the line.
+    pub fn location<T: Into<String>>(self, path: T, line: u32) -> TypedAnnotationBuilder<HasPath> {
+        self.path(path).line(line)
+    }
+}
+
+impl TypedAnnotationBuilder<HasPath> {
+    /// Like [`AnnotationBuilder::line`].
+    pub fn line(self, line: u32) -> Self {
+        self.map(|builder| builder.line(line))
+    }
+}
+
+#[cfg(test)]
+mod typed_builder {
+    use super::*;
+
+    #[test]
+    fn same_as_dynamic() {
+        let typed = TypedAnnotationBuilder::new("Possible panic", Severity::High)
+            .annotation_type(Type::Bug)
+            .external_id("panic-1")
+            .path("src/lib.rs")
+            .line(40)
+            .build()
+            .unwrap();
+        let dynamic = AnnotationBuilder::new("Possible panic", Severity::High)
+            .annotation_type(Type::Bug)
+            .external_id("panic-1")
+            .path("src/lib.rs")
+            .line(40)
+            .build()
+            .unwrap();
+        assert_eq!(dynamic, typed);
+
+        let located = TypedAnnotationBuilder::new("Possible panic", Severity::High)
+            .annotation_type(Type::Bug)
+            .external_id("panic-1")
+            .location("src/lib.rs", 40)
+            .build()
+            .unwrap();
+        assert_eq!(dynamic, located);
+    }
+
+    #[test]
+    fn without_path() {
+        let annotation = TypedAnnotationBuilder::new("Missing license", Severity::Low)
+            .build()
+            .unwrap();
+        assert_eq!(None, annotation.path);
+        assert_eq!(None, annotation.line);
+    }
+
+    #[test]
+    fn path_from() {
+        assert!(TypedAnnotationBuilder::new("Message", Severity::Low)
+            .path_from("/src/lib.rs")
+            .is_err());
+        let annotation = TypedAnnotationBuilder::new("Message", Severity::Low)
+            .allow_absolute_path()
+            .path_from("/src/lib.rs")
+            .unwrap()
+            .line(1)
+            .build()
+            .unwrap();
+        assert_eq!(Some("/src/lib.rs"), annotation.path.as_deref());
+    }
+
+    #[test]
+    fn validation() {
+        let message = "X".repeat(crate::MESSAGE_LIMIT + 1);
+        let builder = TypedAnnotationBuilder::new(message, Severity::Low).path("src/lib.rs");
+        assert!(builder.clone().build().is_err());
+        assert!(builder.truncate_message().build().is_ok());
+    }
+}
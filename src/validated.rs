@@ -0,0 +1,218 @@
+use std::ops::Deref;
+
+use serde::{Serialize, Serializer};
+#[cfg(feature = "json")]
+use serde_json::Value;
+
+use crate::annotation::{Annotation, AnnotationBuilder, Annotations};
+#[cfg(feature = "json")]
+use crate::error::Error;
+use crate::error::Result;
+use crate::report::{Report, ReportBuilder};
+
+/// A value that is known to pass the validation of Bitbucket's limits, so
+/// that it can be serialized without validating it again.
+///
+/// It can only be constructed by the builders or by validating a value, and
+/// only gives shared access to the value. Use
+/// [`into_inner`](Validated::into_inner) to change it, after which it has to
+/// be validated again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validated<T>(T);
+
+impl<T> Validated<T> {
+    /// Returns the value, which is no longer known to be valid once it is
+    /// changed.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Validated<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Serialize> Serialize for Validated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl Validated<Report> {
+    /// Validates `report`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if fields of `report` are longer than the Bitbucket
+    /// API allows.
+    pub fn validate(report: Report) -> Result<Self> {
+        report.validate_fields()?;
+        Ok(Validated(report))
+    }
+}
+
+impl Validated<Annotation> {
+    /// Validates `annotation`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if fields of `annotation` are longer than the
+    /// Bitbucket API allows.
+    pub fn validate(annotation: Annotation) -> Result<Self> {
+        annotation.validate_fields()?;
+        Ok(Validated(annotation))
+    }
+}
+
+impl Validated<Annotations> {
+    /// Validates every annotation of `annotations`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if fields of any annotation are longer than the
+    /// Bitbucket API allows.
+    pub fn validate(annotations: Annotations) -> Result<Self> {
+        annotations.validate()?;
+        Ok(Validated(annotations))
+    }
+}
+
+/// Collects annotations that are already validated without validating them
+/// again.
+impl FromIterator<Validated<Annotation>> for Validated<Annotations> {
+    fn from_iter<I: IntoIterator<Item = Validated<Annotation>>>(iter: I) -> Self {
+        Validated(Annotations::new(
+            iter.into_iter()
+                .map(Validated::into_inner)
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl AnnotationBuilder {
+    /// Like [`build`](AnnotationBuilder::build), but returns the annotation
+    /// as [`Validated`].
+    ///
+    /// # Errors
+    ///
+    /// See [`build`](AnnotationBuilder::build).
+    pub fn build_validated(self) -> Result<Validated<Annotation>> {
+        self.build().map(Validated)
+    }
+}
+
+impl ReportBuilder {
+    /// Like [`build`](ReportBuilder::build), but returns the report as
+    /// [`Validated`].
+    ///
+    /// # Errors
+    ///
+    /// See [`build`](ReportBuilder::build).
+    pub fn build_validated(self) -> Result<Validated<Report>> {
+        self.build().map(Validated)
+    }
+}
+
+/// Serializes validated values without validating them again, unlike the
+/// conversions of the values themselves.
+#[cfg(feature = "json")]
+macro_rules! serialize_validated {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<&Validated<$ty>> for String {
+                type Error = Error;
+
+                fn try_from(value: &Validated<$ty>) -> std::result::Result<Self, Self::Error> {
+                    serde_json::to_string(&value.0).map_err(Error::SerdeError)
+                }
+            }
+
+            impl TryFrom<&Validated<$ty>> for Value {
+                type Error = Error;
+
+                fn try_from(value: &Validated<$ty>) -> std::result::Result<Self, Self::Error> {
+                    serde_json::to_value(&value.0).map_err(Error::SerdeError)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "json")]
+serialize_validated!(Report, Annotation, Annotations);
+
+#[cfg(test)]
+mod validated_values {
+    use super::*;
+    use crate::{Severity, MESSAGE_LIMIT};
+
+    #[test]
+    fn builders() {
+        let report = ReportBuilder::new("Title").build_validated().unwrap();
+        assert_eq!("Title", report.title);
+        assert!(ReportBuilder::new("X".repeat(crate::TITLE_LIMIT + 1))
+            .build_validated()
+            .is_err());
+
+        let annotations = ["First", "Second"]
+            .into_iter()
+            .map(|message| AnnotationBuilder::new(message, Severity::Low).build_validated())
+            .collect::<Result<Validated<Annotations>>>()
+            .unwrap();
+        assert_eq!(2, annotations.annotations.len());
+    }
+
+    #[test]
+    fn validate() {
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .build()
+            .unwrap();
+        let mut invalid = annotation.clone();
+        invalid.message = "X".repeat(MESSAGE_LIMIT + 1);
+
+        assert!(Validated::<Annotation>::validate(invalid.clone()).is_err());
+        assert!(Validated::<Annotations>::validate(Annotations::new(vec![
+            annotation.clone(),
+            invalid
+        ]))
+        .is_err());
+
+        // Changing a validated value requires validating it again.
+        let validated = Validated::<Annotation>::validate(annotation).unwrap();
+        let mut annotation = validated.into_inner();
+        annotation.message = "X".repeat(MESSAGE_LIMIT + 1);
+        assert!(Validated::<Annotation>::validate(annotation).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialization() {
+        let annotation = AnnotationBuilder::new("Message", Severity::Low)
+            .build()
+            .unwrap();
+        let annotations = Validated::<Annotations>::validate(Annotations::new(vec![annotation]));
+        let annotations = annotations.unwrap();
+        assert_eq!(
+            r#"{"annotations":[{"message":"Message","severity":"LOW"}]}"#,
+            String::try_from(&annotations).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&*annotations).unwrap(),
+            Value::try_from(&annotations).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&annotations).unwrap(),
+            String::try_from(&annotations).unwrap()
+        );
+    }
+}
@@ -1,3 +1,33 @@
+use crate::error::{Error, Result};
+
+/// Appended to values that were shortened to fit within a limit.
+pub(crate) const ELLIPSIS: char = '…';
+
+/// Checks that an extension field does not collide with one of the `known`
+/// fields of the payload.
+#[cfg(feature = "json")]
+pub(crate) fn validate_extension_key(key: &str, known: &[&str]) -> Result<()> {
+    if known.contains(&key) {
+        return Err(Error::InvalidValue {
+            field: key.to_owned(),
+            reason: "extension collides with a known field".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `value` of the field `name` is at most `limit` bytes long.
+pub(crate) fn validate_len(name: &str, value: &str, limit: usize) -> Result<()> {
+    if value.len() > limit {
+        return Err(Error::FieldTooLong {
+            name: name.to_owned(),
+            len: value.len(),
+            limit,
+        });
+    }
+    Ok(())
+}
+
 macro_rules! validate_field {
     ($self:ident, $field:ident, $limit:expr) => {
         let len = $self.$field.len();
@@ -29,3 +59,19 @@ macro_rules! validate_optional_field {
 }
 
 pub(crate) use validate_optional_field;
+
+/// Shortens `value` to at most `limit` bytes, ending it with an ellipsis, and
+/// returns whether it had to be shortened.
+pub(crate) fn truncate(value: &mut String, limit: usize) -> bool {
+    if value.len() <= limit {
+        return false;
+    }
+
+    let mut end = limit.saturating_sub(ELLIPSIS.len_utf8());
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    value.truncate(end);
+    value.push(ELLIPSIS);
+    true
+}
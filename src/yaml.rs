@@ -0,0 +1,183 @@
+use crate::annotation::{Annotation, Annotations};
+use crate::error::Result;
+use crate::report::Report;
+
+impl Report {
+    /// Parses a report from YAML, e.g. a template or a test fixture.
+    ///
+    /// YAML is a local convenience only, reports are sent to Bitbucket as
+    /// JSON.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `yaml` is not a valid report, or if any of its
+    /// fields are longer than Bitbucket allows.
+    pub fn from_yaml_str(yaml: &str) -> Result<Report> {
+        let report = serde_yaml::from_str::<Report>(yaml)?;
+        report.validate_fields()?;
+        Ok(report)
+    }
+
+    /// Serializes the report as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the fields are longer than Bitbucket
+    /// allows.
+    pub fn to_yaml_string(&self) -> Result<String> {
+        self.validate_fields()?;
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+impl Annotation {
+    /// Parses an annotation from YAML.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `yaml` is not a valid annotation, or if any of its
+    /// fields are longer than Bitbucket allows.
+    pub fn from_yaml_str(yaml: &str) -> Result<Annotation> {
+        let annotation = serde_yaml::from_str::<Annotation>(yaml)?;
+        annotation.validate_fields()?;
+        Ok(annotation)
+    }
+
+    /// Serializes the annotation as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the fields are longer than Bitbucket
+    /// allows.
+    pub fn to_yaml_string(&self) -> Result<String> {
+        self.validate_fields()?;
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+impl Annotations {
+    /// Parses annotations from YAML.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `yaml` is not a valid set of annotations, or if
+    /// the fields of any annotation are longer than Bitbucket allows.
+    pub fn from_yaml_str(yaml: &str) -> Result<Annotations> {
+        let annotations = serde_yaml::from_str::<Annotations>(yaml)?;
+        for annotation in &annotations.annotations {
+            annotation.validate_fields()?;
+        }
+        Ok(annotations)
+    }
+
+    /// Serializes the annotations as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fields of any annotation are longer than
+    /// Bitbucket allows.
+    pub fn to_yaml_string(&self) -> Result<String> {
+        for annotation in &self.annotations {
+            annotation.validate_fields()?;
+        }
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod yaml_serialization {
+    use super::*;
+    use crate::{
+        AnnotationBuilder, Data, Error, Parameter, ReportBuilder, ReportResult, ReportType,
+        Severity, Type, MESSAGE_LIMIT, TITLE_LIMIT,
+    };
+
+    fn report() -> Report {
+        ReportBuilder::new("Static analysis")
+            .details("Findings of the linter\nacross the workspace")
+            .result(ReportResult::Fail)
+            .data(vec![
+                Data {
+                    title: "Safe to merge?".to_owned(),
+                    parameter: Parameter::Boolean(false),
+                },
+                Data {
+                    title: "Started".to_owned(),
+                    parameter: Parameter::Date(1582841968000),
+                },
+                Data {
+                    title: "Duration".to_owned(),
+                    parameter: Parameter::Duration(125_000),
+                },
+                Data {
+                    title: "Pipeline".to_owned(),
+                    parameter: Parameter::Link {
+                        linktext: "Build #12".to_owned(),
+                        href: "https://ci.example.com/12".to_owned(),
+                    },
+                },
+                Data {
+                    title: "Warnings".to_owned(),
+                    parameter: Parameter::Number(12.into()),
+                },
+                Data {
+                    title: "Coverage".to_owned(),
+                    parameter: Parameter::Percentage(84),
+                },
+            ])
+            .reporter("clippy")
+            .link("https://ci.example.com/12/report")
+            .logo_url("https://ci.example.com/logo.svg")
+            .report_type(ReportType::Bug)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn report_round_trip() {
+        let yaml = report().to_yaml_string().unwrap();
+        assert!(yaml.contains("reportType: BUG"), "{yaml}");
+        assert_eq!(report(), Report::from_yaml_str(&yaml).unwrap());
+    }
+
+    #[test]
+    fn annotations_round_trip() {
+        let annotations = Annotations::new(vec![AnnotationBuilder::new(
+            "Possible panic",
+            Severity::High,
+        )
+        .annotation_type(Type::Bug)
+        .path("src/lib.rs")
+        .line(40)
+        .link("https://example.com/rules/panic")
+        .external_id("a1")
+        .build()
+        .unwrap()]);
+        let yaml = annotations.to_yaml_string().unwrap();
+        assert_eq!(annotations, Annotations::from_yaml_str(&yaml).unwrap());
+
+        let annotation = &annotations.annotations[0];
+        let yaml = annotation.to_yaml_string().unwrap();
+        assert_eq!(annotation, &Annotation::from_yaml_str(&yaml).unwrap());
+    }
+
+    #[test]
+    fn over_limit_fields() {
+        let yaml = format!("title: {}\n", "X".repeat(TITLE_LIMIT + 1));
+        let result = Report::from_yaml_str(&yaml);
+        assert!(matches!(result, Err(Error::FieldTooLong { .. })));
+
+        let yaml = format!(
+            "annotations:\n  - message: {}\n    severity: LOW\n",
+            "X".repeat(MESSAGE_LIMIT + 1)
+        );
+        let result = Annotations::from_yaml_str(&yaml);
+        assert!(matches!(result, Err(Error::FieldTooLong { .. })));
+    }
+
+    #[test]
+    fn invalid_yaml() {
+        let result = Annotation::from_yaml_str("message: [unclosed");
+        assert!(matches!(result, Err(Error::YamlError(_))));
+    }
+}
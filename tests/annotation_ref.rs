@@ -0,0 +1,65 @@
+//! Checks that serializing borrowed annotations does not copy their strings,
+//! by counting the allocations of the current thread.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use code_insights::{AnnotationRef, AnnotationsRef, Severity};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn serialization_does_not_allocate() {
+    let arena = (0..1000)
+        .map(|n| (format!("Issue {n}"), format!("src/module_{n}.rs")))
+        .collect::<Vec<_>>();
+    let mut output = Vec::with_capacity(1 << 20);
+
+    let before = allocations();
+    let annotations = AnnotationsRef::new(
+        arena
+            .iter()
+            .map(|(message, path)| {
+                AnnotationRef::new(message, Severity::Low)
+                    .with_path(path)
+                    .with_line(1)
+            })
+            .collect::<Vec<_>>(),
+    );
+    // Only the vector of annotations is allocated, not their strings.
+    assert_eq!(1, allocations() - before);
+
+    let before = allocations();
+    annotations.validate().unwrap();
+    serde_json::to_writer(&mut output, &annotations).unwrap();
+    assert_eq!(0, allocations() - before);
+    assert!(output.starts_with(br#"{"annotations":[{"message":"Issue 0","#));
+
+    // Converting to owned annotations copies every string.
+    let before = allocations();
+    let owned = annotations.to_owned();
+    assert!(allocations() - before > 2 * arena.len());
+    drop(owned);
+}
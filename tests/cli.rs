@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn command(input: &str) -> Command {
+    let mut command = Command::cargo_bin("code-insights").unwrap();
+    command
+        .args(["--key", "lint", "--title", "Lint"])
+        .pipe_stdin(fixture(input))
+        .unwrap();
+    command
+}
+
+fn output(command: &mut Command, code: i32) -> Value {
+    let output = command.assert().code(code).get_output().stdout.clone();
+    serde_json::from_slice(&output).unwrap()
+}
+
+fn messages(bundle: &Value) -> Vec<&str> {
+    bundle["annotations"]["annotations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|annotation| annotation["message"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn passing_report() {
+    let bundle = output(
+        command("annotations.json").args([
+            "--result",
+            "pass",
+            "--reporter",
+            "clippy",
+            "--data",
+            "Coverage=87%",
+            "--data",
+            "Warnings=3",
+            "--data",
+            "Safe to merge?=true",
+            "--data",
+            "Branch=main",
+        ]),
+        0,
+    );
+    assert_eq!("lint", bundle["key"]);
+    assert_eq!(
+        json!({
+            "title": "Lint",
+            "result": "PASS",
+            "reporter": "clippy",
+            "data": [
+                {"title": "Coverage", "type": "PERCENTAGE", "value": 87},
+                {"title": "Warnings", "type": "NUMBER", "value": 3},
+                {"title": "Safe to merge?", "type": "BOOLEAN", "value": true},
+                {"title": "Branch", "type": "TEXT", "value": "main"}
+            ]
+        }),
+        bundle["report"]
+    );
+    assert_eq!(3, messages(&bundle).len());
+}
+
+#[test]
+fn failing_report() {
+    let bundle = output(command("annotations.json").args(["--result", "fail"]), 1);
+    assert_eq!("FAIL", bundle["report"]["result"]);
+}
+
+#[test]
+fn filters() {
+    let bundle = output(
+        command("annotations.json").args(["--min-severity", "medium"]),
+        0,
+    );
+    assert_eq!(vec!["Possible panic", "Generated code"], messages(&bundle));
+
+    let bundle = output(
+        command("annotations.json").args(["--exclude", "vendor/"]),
+        0,
+    );
+    assert_eq!(vec!["Possible panic", "Unused import"], messages(&bundle));
+
+    let diff = fixture("changes.diff");
+    let bundle = output(command("annotations.json").arg("--diff").arg(diff), 0);
+    assert_eq!(vec!["Possible panic"], messages(&bundle));
+}
+
+#[test]
+fn json_lines() {
+    let bundle = output(command("annotations.ndjson").arg("--ndjson"), 0);
+    assert_eq!(vec!["Possible panic", "Unused import"], messages(&bundle));
+}
+
+fn error(command: &mut Command) -> String {
+    let output = command.assert().code(2).stdout("").get_output().clone();
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn invalid_payload() {
+    let error = error(&mut command("invalid.json"));
+    assert!(error.contains("invalid annotations"), "{error}");
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in [
+        &["--data", "Coverage=120%"][..],
+        &["--result", "maybe"],
+        &["--key"],
+        &["--unknown", "x"],
+    ] {
+        error(command("annotations.json").args(args));
+    }
+
+    let mut command = Command::cargo_bin("code-insights").unwrap();
+    command
+        .args(["--title", "Lint"])
+        .write_stdin("{\"annotations\": []}");
+    let error = error(&mut command);
+    assert!(error.contains("missing --key"), "{error}");
+}
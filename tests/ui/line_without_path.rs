@@ -0,0 +1,7 @@
+use code_insights::{Severity, TypedAnnotationBuilder};
+
+fn main() {
+    let _ = TypedAnnotationBuilder::new("Possible panic", Severity::High)
+        .line(40)
+        .build();
+}
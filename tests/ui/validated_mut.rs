@@ -0,0 +1,13 @@
+use code_insights::{AnnotationBuilder, Annotations, Severity, Validated};
+
+fn main() {
+    let mut annotations = Validated::<Annotations>::validate(Annotations::new(vec![])).unwrap();
+    let annotation = AnnotationBuilder::new("X".repeat(2001), Severity::Low)
+        .truncate_message()
+        .build()
+        .unwrap();
+    // Validated values only give shared access, so that they cannot become
+    // invalid.
+    let inner: &mut Annotations = &mut annotations;
+    *inner = Annotations::new(vec![annotation]);
+}
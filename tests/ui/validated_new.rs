@@ -0,0 +1,6 @@
+use code_insights::{Annotations, Validated};
+
+fn main() {
+    // Validated values can only be constructed by validating them.
+    let _ = Validated(Annotations::new(vec![]));
+}